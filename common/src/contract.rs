@@ -0,0 +1,138 @@
+//! Contract types
+//!
+//! Boards rarely have a structured field for the kind of contract, but the
+//! listing text usually says it ("Full-Time", "Freelance"). A listing that
+//! mentions more than one kind ("full-time or contract") is left unclassified
+//! rather than guessed.
+
+use crate::Words;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// The kind of employment a job offers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ContractType {
+    FullTime,
+    PartTime,
+    Contract,
+}
+
+/// Phrases that mark each contract type, checked against whole words
+const CONTRACT_KEYWORDS: &[(&str, ContractType)] = &[
+    ("full-time", ContractType::FullTime),
+    ("full time", ContractType::FullTime),
+    ("fulltime", ContractType::FullTime),
+    ("permanent", ContractType::FullTime),
+    ("part-time", ContractType::PartTime),
+    ("part time", ContractType::PartTime),
+    ("parttime", ContractType::PartTime),
+    ("contract", ContractType::Contract),
+    ("contractor", ContractType::Contract),
+    ("freelance", ContractType::Contract),
+    ("freelancer", ContractType::Contract),
+];
+
+impl ContractType {
+    /// Every contract type, in display order
+    pub const ALL: [ContractType; 3] = [
+        ContractType::FullTime,
+        ContractType::PartTime,
+        ContractType::Contract,
+    ];
+
+    /// Name used in the API and the index, e.g. "full-time"
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ContractType::FullTime => "full-time",
+            ContractType::PartTime => "part-time",
+            ContractType::Contract => "contract",
+        }
+    }
+}
+
+impl fmt::Display for ContractType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ContractType {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim().to_lowercase();
+        ContractType::ALL
+            .into_iter()
+            .find(|contract| contract.as_str() == text)
+            .ok_or_else(|| {
+                let names: Vec<&str> = ContractType::ALL.iter().map(|c| c.as_str()).collect();
+                format!(
+                    "unknown contract_type {:?}: expected one of {}",
+                    text,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// Infers the contract type from listing text. Returns None when the text
+/// names no contract type, or names more than one.
+pub fn classify_contract(text: &str) -> Option<ContractType> {
+    let words = Words::keeping_hyphens(text);
+    let mut found: Option<ContractType> = None;
+    for (keyword, contract) in CONTRACT_KEYWORDS {
+        if !words.contains(keyword) {
+            continue;
+        }
+        match found {
+            Some(existing) if existing != *contract => return None,
+            _ => found = Some(*contract),
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_contract() {
+        assert_eq!(
+            classify_contract("Senior Rust Engineer (Full-Time)"),
+            Some(ContractType::FullTime)
+        );
+        assert_eq!(
+            classify_contract("Backend Developer - Contract, 6 months"),
+            Some(ContractType::Contract)
+        );
+        assert_eq!(
+            classify_contract("Freelance Designer"),
+            Some(ContractType::Contract)
+        );
+        assert_eq!(
+            classify_contract("Support Agent, part time"),
+            Some(ContractType::PartTime)
+        );
+
+        // Mixed or missing signals stay unclassified
+        assert_eq!(classify_contract("Full-time or contract roles"), None);
+        assert_eq!(classify_contract("Rust Developer"), None);
+        // "contract" only counts as a whole word
+        assert_eq!(classify_contract("Smart-contracts auditor"), None);
+    }
+
+    #[test]
+    fn test_contract_type_round_trips_through_text() {
+        for contract in ContractType::ALL {
+            assert_eq!(contract.as_str().parse::<ContractType>(), Ok(contract));
+            assert_eq!(
+                serde_json::to_string(&contract).unwrap(),
+                format!("\"{}\"", contract)
+            );
+        }
+        assert!("temporary".parse::<ContractType>().is_err());
+    }
+}
@@ -0,0 +1,155 @@
+//! Countries a job can be done from, parsed from its region text
+//!
+//! Region text is free-form ("USA Only", "Europe", "Anywhere in the World"),
+//! which can't be filtered on directly. Known names map to ISO 3166-1 alpha-2
+//! codes; a region covering several countries ("EU", "LATAM") maps to all of
+//! them. The table is coarse on purpose: it only knows names boards commonly
+//! use, and a region it can't place gets no countries rather than a guess.
+
+use crate::Words;
+
+/// Stands in for every country, for jobs that can be done from anywhere
+pub const WORLDWIDE: &str = "*";
+
+/// European Union member states
+const EU: &[&str] = &[
+    "AT", "BE", "BG", "HR", "CY", "CZ", "DK", "EE", "FI", "FR", "DE", "GR", "HU", "IE", "IT", "LV",
+    "LT", "LU", "MT", "NL", "PL", "PT", "RO", "SK", "SI", "ES", "SE",
+];
+
+/// Europe beyond the EU, as boards use the word
+const EUROPE_NON_EU: &[&str] = &["GB", "CH", "NO", "IS", "UA", "RS"];
+
+/// Latin American countries boards usually hire from
+const LATAM: &[&str] = &[
+    "MX", "BR", "AR", "CO", "CL", "PE", "UY", "EC", "CR", "GT", "BO", "PY", "VE",
+];
+
+/// Region names and the countries they cover, checked against whole words.
+/// "Europe" is handled separately, as the EU plus the rest of Europe.
+const COUNTRY_NAMES: &[(&str, &[&str])] = &[
+    ("usa", &["US"]),
+    ("us", &["US"]),
+    ("united states", &["US"]),
+    ("canada", &["CA"]),
+    ("mexico", &["MX"]),
+    ("north america", &["US", "CA", "MX"]),
+    ("brazil", &["BR"]),
+    ("argentina", &["AR"]),
+    ("colombia", &["CO"]),
+    ("chile", &["CL"]),
+    ("latam", LATAM),
+    ("latin america", LATAM),
+    (
+        "south america",
+        &["BR", "AR", "CO", "CL", "PE", "UY", "EC", "BO", "PY", "VE"],
+    ),
+    ("uk", &["GB"]),
+    ("united kingdom", &["GB"]),
+    ("england", &["GB"]),
+    ("ireland", &["IE"]),
+    ("germany", &["DE"]),
+    ("france", &["FR"]),
+    ("spain", &["ES"]),
+    ("portugal", &["PT"]),
+    ("italy", &["IT"]),
+    ("netherlands", &["NL"]),
+    ("poland", &["PL"]),
+    ("sweden", &["SE"]),
+    ("switzerland", &["CH"]),
+    ("eu", EU),
+    ("european union", EU),
+    ("india", &["IN"]),
+    ("philippines", &["PH"]),
+    ("singapore", &["SG"]),
+    ("japan", &["JP"]),
+    ("australia", &["AU"]),
+    ("new zealand", &["NZ"]),
+    ("south africa", &["ZA"]),
+    ("nigeria", &["NG"]),
+];
+
+/// Names meaning the job isn't tied to any country, unless the text also
+/// names a place ("Anywhere in the US")
+const WORLDWIDE_NAMES: &[&str] = &["anywhere", "worldwide", "world", "global"];
+
+/// Parses region text into the ISO country codes it covers, sorted.
+///
+/// "Europe" covers the EU and the rest of Europe; a worldwide region that
+/// names no country or region gives just `WORLDWIDE`. Returns an empty list
+/// when no known name is found.
+pub fn countries_from_region(text: &str) -> Vec<String> {
+    let words = Words::new(text);
+    let named = |name: &str| words.contains(name);
+
+    let mut codes: Vec<&str> = Vec::new();
+    for (name, countries) in COUNTRY_NAMES {
+        if named(name) {
+            codes.extend_from_slice(countries);
+        }
+    }
+    if named("europe") {
+        codes.extend_from_slice(EU);
+        codes.extend_from_slice(EUROPE_NON_EU);
+    }
+    if codes.is_empty() && WORLDWIDE_NAMES.iter().any(|name| named(name)) {
+        return vec![WORLDWIDE.to_string()];
+    }
+    codes.sort_unstable();
+    codes.dedup();
+    codes.into_iter().map(str::to_string).collect()
+}
+
+/// Checks and uppercases a two-letter country code from a request
+pub fn parse_country_code(text: &str) -> Result<String, String> {
+    let code = text.trim().to_uppercase();
+    if code.len() == 2 && code.chars().all(|c| c.is_ascii_alphabetic()) {
+        Ok(code)
+    } else {
+        Err(format!(
+            "invalid country {:?}: expected a two-letter ISO code such as US or DE",
+            text
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_region_names_map_to_country_codes() {
+        assert_eq!(countries_from_region("USA Only"), ["US"]);
+        assert_eq!(countries_from_region("United Kingdom"), ["GB"]);
+        assert_eq!(countries_from_region("US or Canada"), ["CA", "US"]);
+        assert_eq!(countries_from_region("North America"), ["CA", "MX", "US"]);
+
+        // Multi-country regions list every member
+        let eu = countries_from_region("EU timezones");
+        assert_eq!(eu.len(), 27);
+        assert!(eu.contains(&"DE".to_string()));
+        assert!(!eu.contains(&"GB".to_string()));
+        let europe = countries_from_region("Europe Only");
+        assert!(europe.contains(&"DE".to_string()));
+        assert!(europe.contains(&"GB".to_string()));
+
+        assert_eq!(countries_from_region("Anywhere in the World"), [WORLDWIDE]);
+        // "Anywhere" within a named place is that place
+        assert_eq!(countries_from_region("Anywhere in the US"), ["US"]);
+        assert_eq!(
+            countries_from_region("Anywhere in Europe"),
+            countries_from_region("Europe")
+        );
+        assert!(countries_from_region("Anywhere in Europe").contains(&"GB".to_string()));
+        // Names only count as whole words
+        assert!(countries_from_region("Business hours").is_empty());
+        assert!(countries_from_region("Remote").is_empty());
+    }
+
+    #[test]
+    fn test_parse_country_code() {
+        assert_eq!(parse_country_code("de"), Ok("DE".to_string()));
+        assert!(parse_country_code("Germany").is_err());
+        assert!(parse_country_code("*").is_err());
+    }
+}
@@ -0,0 +1,61 @@
+//! Salary currency normalization
+//!
+//! Boards list salaries in whatever currency the employer pays in, so
+//! comparing raw figures mixes dollars with euros and yen. Figures are
+//! converted to US dollars with a fixed table of approximate mid-market rates
+//! (early 2024). The table isn't refreshed automatically: it is meant to put
+//! salaries in the same ballpark, not to price them to the cent.
+
+/// US dollars per unit of each supported currency (ISO 4217 codes)
+const USD_RATES: &[(&str, f64)] = &[
+    ("USD", 1.0),
+    ("EUR", 1.08),
+    ("GBP", 1.27),
+    ("CAD", 0.74),
+    ("AUD", 0.66),
+    ("NZD", 0.61),
+    ("CHF", 1.13),
+    ("SEK", 0.096),
+    ("NOK", 0.094),
+    ("DKK", 0.145),
+    ("PLN", 0.25),
+    ("JPY", 0.0067),
+    ("INR", 0.012),
+    ("SGD", 0.74),
+    ("BRL", 0.20),
+    ("MXN", 0.058),
+];
+
+/// US dollars per unit of `code`, or None for an unsupported currency.
+/// Codes are matched ignoring case.
+pub fn usd_rate(code: &str) -> Option<f64> {
+    let code = code.trim();
+    USD_RATES
+        .iter()
+        .find(|(known, _)| known.eq_ignore_ascii_case(code))
+        .map(|&(_, rate)| rate)
+}
+
+/// Converts `amount` in `currency` to whole US dollars. A salary without a
+/// currency is taken to be in dollars; an unsupported currency gives None.
+pub fn to_usd(amount: i64, currency: Option<&str>) -> Option<i64> {
+    let rate = match currency {
+        Some(code) => usd_rate(code)?,
+        None => 1.0,
+    };
+    Some((amount as f64 * rate).round() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_usd() {
+        assert_eq!(to_usd(80_000, Some("EUR")), Some(86_400));
+        assert_eq!(to_usd(80_000, Some("eur")), Some(86_400));
+        assert_eq!(to_usd(90_000, Some("USD")), Some(90_000));
+        assert_eq!(to_usd(90_000, None), Some(90_000));
+        assert_eq!(to_usd(90_000, Some("XYZ")), None);
+    }
+}
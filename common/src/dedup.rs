@@ -0,0 +1,125 @@
+//! Deduplication keys
+//!
+//! Two jobs with the same key are the same job: the scraper keeps the first
+//! one it finds, the server merges job files the same way and replaces the
+//! indexed job when one with its key is imported again. What counts as "the
+//! same" depends on the data, so the key is configurable:
+//!
+//! - `url`: the listing URL as scraped. Never merges different jobs, but a
+//!   job reposted under a new URL, or linked with tracking parameters, is
+//!   kept twice.
+//! - `id`: the stable id of the URL without its query string, fragment and
+//!   trailing slash. Catches `?utm_source=...` copies; boards that put the
+//!   job itself in the query string (`/view?job=123`) collapse into one job.
+//! - `title_company`: the title and company, ignoring case and punctuation.
+//!   Catches the same job posted on several boards, but merges a company's
+//!   separate openings for the same role.
+//! - `composite`: title, company and location. Like `title_company`, but
+//!   keeps a role advertised in several locations apart; a board that words
+//!   the location differently keeps its copy.
+
+use crate::{Job, job_id};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Which fields decide that two jobs are the same
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DedupStrategy {
+    #[default]
+    Url,
+    Id,
+    TitleCompany,
+    Composite,
+}
+
+impl DedupStrategy {
+    /// The key `job` is deduplicated by
+    pub fn key(self, job: &Job) -> String {
+        match self {
+            DedupStrategy::Url => job.url.clone(),
+            DedupStrategy::Id => job_id(canonical_url(&job.url)),
+            DedupStrategy::TitleCompany => {
+                format!("{}|{}", fold(&job.title), fold(&job.company))
+            }
+            DedupStrategy::Composite => format!(
+                "{}|{}|{}",
+                fold(&job.title),
+                fold(&job.company),
+                fold(&job.location)
+            ),
+        }
+    }
+}
+
+impl FromStr for DedupStrategy {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.trim().to_lowercase().as_str() {
+            "url" => Ok(DedupStrategy::Url),
+            "id" => Ok(DedupStrategy::Id),
+            "title_company" => Ok(DedupStrategy::TitleCompany),
+            "composite" => Ok(DedupStrategy::Composite),
+            other => Err(format!(
+                "unknown dedup strategy {:?}: expected url, id, title_company or composite",
+                other
+            )),
+        }
+    }
+}
+
+/// `url` without its query string, fragment and trailing slash
+fn canonical_url(url: &str) -> &str {
+    let end = url.find(['?', '#']).unwrap_or(url.len());
+    url[..end].trim_end_matches('/')
+}
+
+/// Lowercase words joined by single spaces, punctuation dropped
+fn fold(text: &str) -> String {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strategies_key_the_same_jobs_differently() {
+        let job = |title: &str, location: &str, url: &str| Job {
+            title: title.to_string(),
+            company: "Acme".to_string(),
+            location: location.to_string(),
+            url: url.to_string(),
+            ..Job::default()
+        };
+        let jobs = [
+            job("Rust Developer", "Europe", "https://a.example/jobs/1"),
+            job(
+                "Rust Developer",
+                "Europe",
+                "https://a.example/jobs/1/?utm_source=x",
+            ),
+            job("Rust developer!", "Europe", "https://b.example/rust"),
+            job("Rust Developer", "USA Only", "https://b.example/rust-us"),
+        ];
+        let distinct = |strategy: DedupStrategy| {
+            let mut keys: Vec<String> = jobs.iter().map(|job| strategy.key(job)).collect();
+            keys.sort();
+            keys.dedup();
+            keys.len()
+        };
+
+        assert_eq!(distinct(DedupStrategy::Url), 4);
+        assert_eq!(distinct(DedupStrategy::Id), 3);
+        assert_eq!(distinct(DedupStrategy::TitleCompany), 1);
+        assert_eq!(distinct(DedupStrategy::Composite), 2);
+
+        assert_eq!("Title_Company".parse(), Ok(DedupStrategy::TitleCompany));
+        assert!("title+company".parse::<DedupStrategy>().is_err());
+    }
+}
@@ -0,0 +1,111 @@
+//! Error type shared by the scraper and the server
+
+use std::io;
+use std::path::PathBuf;
+use thiserror::Error as ThisError;
+
+/// Failures that can stop the scraper or the server
+#[derive(Debug, ThisError)]
+pub enum Error {
+    /// Reading or writing a file or directory failed
+    #[error("cannot access {}: {source}", path.display())]
+    Io { path: PathBuf, source: io::Error },
+
+    /// Job data couldn't be encoded or decoded
+    #[error("invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// A MessagePack job file couldn't be encoded or decoded
+    #[error("invalid MessagePack: {0}")]
+    MsgPack(String),
+
+    /// The search index couldn't be opened, written or read
+    #[error("search index error: {0}")]
+    Index(String),
+
+    /// A page couldn't be downloaded
+    #[error("failed to scrape {url}: {message}")]
+    Scrape { url: String, message: String },
+
+    /// Scraped jobs couldn't be sent to the server
+    #[error("failed to push jobs to {url}: {message}")]
+    Push { url: String, message: String },
+
+    /// The server couldn't take over its listening socket or stopped serving
+    #[error("server on {addr} failed: {message}")]
+    Serve { addr: String, message: String },
+
+    /// A config file or setting is invalid
+    #[error("invalid configuration: {0}")]
+    Config(String),
+
+    /// Scraped data looks broken and wasn't saved
+    #[error("scraped jobs failed quality checks: {0}")]
+    Quality(String),
+}
+
+impl Error {
+    /// Wraps an I/O error with the path it happened on
+    pub fn io(path: impl Into<PathBuf>, source: io::Error) -> Error {
+        Error::Io {
+            path: path.into(),
+            source,
+        }
+    }
+}
+
+/// Result type using the shared `Error`
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_variants_display_their_context() {
+        let io = Error::io(
+            "data/jobs.json",
+            io::Error::new(io::ErrorKind::NotFound, "no such file"),
+        );
+        assert!(matches!(io, Error::Io { .. }));
+        assert_eq!(io.to_string(), "cannot access data/jobs.json: no such file");
+
+        let json: Error = serde_json::from_str::<Vec<String>>("[1")
+            .unwrap_err()
+            .into();
+        assert!(matches!(json, Error::Json(_)));
+        assert!(
+            json.to_string().starts_with("invalid JSON: invalid type"),
+            "{}",
+            json
+        );
+
+        let index = Error::Index("lock busy".to_string());
+        assert!(matches!(index, Error::Index(_)));
+        assert_eq!(index.to_string(), "search index error: lock busy");
+
+        let scrape = Error::Scrape {
+            url: "https://example.com/jobs".to_string(),
+            message: "timed out".to_string(),
+        };
+        assert!(matches!(scrape, Error::Scrape { .. }));
+        assert_eq!(
+            scrape.to_string(),
+            "failed to scrape https://example.com/jobs: timed out"
+        );
+
+        let config = Error::Config("board `x` has no urls".to_string());
+        assert!(matches!(config, Error::Config(_)));
+        assert_eq!(
+            config.to_string(),
+            "invalid configuration: board `x` has no urls"
+        );
+
+        let quality = Error::Quality("no jobs were scraped".to_string());
+        assert!(matches!(quality, Error::Quality(_)));
+        assert_eq!(
+            quality.to_string(),
+            "scraped jobs failed quality checks: no jobs were scraped"
+        );
+    }
+}
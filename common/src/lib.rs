@@ -1,11 +1,186 @@
+mod contract;
+mod country;
+mod currency;
+mod dedup;
+mod error;
+mod msgpack;
+mod timezone;
+mod title;
+mod visa;
+mod words;
+
+pub use contract::{ContractType, classify_contract};
+pub use country::{WORLDWIDE, countries_from_region, parse_country_code};
+pub use currency::{to_usd, usd_rate};
+pub use dedup::DedupStrategy;
+pub use error::{Error, Result};
+pub use msgpack::{
+    MSGPACK_EXTENSION, fresh_msgpack_copy, jobs_from_msgpack, jobs_to_msgpack, read_jobs_file,
+};
 use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+pub use timezone::{MAX_UTC_OFFSET, MIN_UTC_OFFSET, TimezoneRange};
+pub use title::normalize_title;
+pub use visa::detect_visa_sponsorship;
+pub use words::Words;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
 pub struct Job {
     pub title: String,
     pub company: String,
     pub location: String,
     pub description: String,
     pub salary_min: Option<i64>,
+    /// Top of the listed salary range, when the listing gives one
+    #[serde(default)]
+    pub salary_max: Option<i64>,
+    /// ISO 4217 code of the salary figures, e.g. "USD", when the listing shows one
+    #[serde(default)]
+    pub salary_currency: Option<String>,
+    /// How often the listing says its figures are paid. Monthly figures are
+    /// annualized (×12) into `salary_min`/`salary_max`, which are always yearly;
+    /// the listing's own wording is kept in `salary_raw`.
+    #[serde(default)]
+    pub salary_period: Option<SalaryPeriod>,
+    /// The salary as the listing wrote it ("$8,000 - $9,000/month"), before
+    /// parsing and annualizing
+    #[serde(default)]
+    pub salary_raw: Option<String>,
     pub url: String,
+    /// When the board says the job was posted (RFC 3339), if it shows a date
+    #[serde(default)]
+    pub posted_date: Option<String>,
+    /// Last day to apply (RFC 3339), when the listing gives one
+    #[serde(default)]
+    pub deadline: Option<String>,
+    /// When the scraper first saw the job (RFC 3339)
+    #[serde(default)]
+    pub scraped_at: Option<String>,
+    /// UTC offsets the job can be worked from, parsed from `location`
+    #[serde(default)]
+    pub timezone: Option<TimezoneRange>,
+    /// ISO country codes the job can be done from, parsed from `location`;
+    /// `["*"]` when it can be done from anywhere, empty when unknown
+    #[serde(default)]
+    pub countries: Vec<String>,
+    /// Labels such as contract type or region, from the board's markup when
+    /// it has them, otherwise keywords found in the title and location
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Full-time, part-time or contract, when the listing makes it clear
+    #[serde(default)]
+    pub contract_type: Option<ContractType>,
+    /// Whether the board promotes the listing (e.g. a paid "featured" slot)
+    #[serde(default)]
+    pub featured: bool,
+    /// Whether the listing offers visa sponsorship: Some(false) when it rules
+    /// it out, None when it doesn't say
+    #[serde(default)]
+    pub visa_sponsorship: Option<bool>,
+    /// The board gave no company name (and none could be inferred), so
+    /// `company` is empty
+    #[serde(default)]
+    pub company_missing: bool,
+}
+
+/// Company placeholder written by earlier scraper versions when the
+/// company selector missed; never a real company
+pub const UNKNOWN_COMPANY: &str = "Unknown Company";
+
+/// Pay period a salary figure is quoted in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SalaryPeriod {
+    Yearly,
+    Monthly,
+}
+
+impl Job {
+    /// Stable identifier derived from the job URL.
+    /// Uses FNV-1a so the value doesn't change between builds or runs.
+    pub fn id(&self) -> String {
+        job_id(&self.url)
+    }
+
+    /// The company name, unless the board didn't give one. Older job files
+    /// carry the `UNKNOWN_COMPANY` placeholder instead of `company_missing`.
+    pub fn known_company(&self) -> Option<&str> {
+        let company = self.company.trim();
+        (!self.company_missing && !company.is_empty() && company != UNKNOWN_COMPANY)
+            .then_some(company)
+    }
+
+    /// Age reference for the job in unix seconds: the posted date, or when
+    /// it was scraped if the board didn't give one
+    pub fn timestamp(&self) -> Option<i64> {
+        self.posted_date
+            .as_deref()
+            .and_then(parse_timestamp)
+            .or_else(|| self.scraped_at.as_deref().and_then(parse_timestamp))
+    }
+}
+
+/// Parses an RFC 3339 date-time into unix seconds
+pub fn parse_timestamp(text: &str) -> Option<i64> {
+    OffsetDateTime::parse(text.trim(), &Rfc3339)
+        .ok()
+        .map(OffsetDateTime::unix_timestamp)
+}
+
+/// Formats unix seconds as an RFC 3339 date-time in UTC
+pub fn format_timestamp(secs: i64) -> String {
+    OffsetDateTime::from_unix_timestamp(secs)
+        .ok()
+        .and_then(|date| date.format(&Rfc3339).ok())
+        .unwrap_or_default()
+}
+
+/// Computes the stable job identifier for a URL (16 hex chars)
+pub fn job_id(url: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in url.bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_job_id_is_stable() {
+        assert_eq!(job_id(""), "cbf29ce484222325");
+        assert_eq!(
+            job_id("https://weworkremotely.com/remote-jobs/1"),
+            job_id("https://weworkremotely.com/remote-jobs/1")
+        );
+        assert_ne!(
+            job_id("https://weworkremotely.com/remote-jobs/1"),
+            job_id("https://weworkremotely.com/remote-jobs/2")
+        );
+    }
+
+    #[test]
+    fn test_timestamp_prefers_posted_date() {
+        let mut job = Job {
+            title: "Rust Developer".to_string(),
+            company: "Acme".to_string(),
+            location: "Remote".to_string(),
+            description: String::new(),
+            salary_min: None,
+            url: "https://example.com/1".to_string(),
+            scraped_at: Some(format_timestamp(1_700_000_000)),
+            ..Job::default()
+        };
+        assert_eq!(job.timestamp(), Some(1_700_000_000));
+
+        job.posted_date = Some("2023-11-01T00:00:00+01:00".to_string());
+        assert_eq!(job.timestamp(), Some(1_698_793_200));
+
+        job.posted_date = Some("last week".to_string());
+        assert_eq!(job.timestamp(), Some(1_700_000_000));
+    }
 }
@@ -0,0 +1,192 @@
+//! Working-hours timezone bands parsed from a job's region text
+//!
+//! Boards describe where a remote job can be done as free text ("Europe Only",
+//! "US timezones", "UTC-3 to UTC+3"). What matters to a candidate is whether
+//! their UTC offset falls inside the band the employer accepts.
+
+use crate::Words;
+use serde::{Deserialize, Serialize};
+
+/// Westernmost UTC offset in use (hours)
+pub const MIN_UTC_OFFSET: i32 = -12;
+
+/// Easternmost UTC offset in use (hours)
+pub const MAX_UTC_OFFSET: i32 = 14;
+
+/// Inclusive band of whole-hour UTC offsets a job accepts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimezoneRange {
+    pub min_offset: i32,
+    pub max_offset: i32,
+}
+
+/// Names meaning every offset, unless the text also names a region
+/// ("Anywhere in the US")
+const WORLDWIDE_NAMES: &[&str] = &["anywhere", "worldwide", "world", "global"];
+
+/// Region names and the offsets they span, checked against whole words
+const REGIONS: &[(&str, i32, i32)] = &[
+    ("usa", -10, -4),
+    ("us", -10, -4),
+    ("united states", -10, -4),
+    ("canada", -8, -3),
+    ("north america", -10, -3),
+    ("americas", -10, -3),
+    ("latin america", -6, -3),
+    ("latam", -6, -3),
+    ("south america", -5, -3),
+    ("uk", 0, 1),
+    ("united kingdom", 0, 1),
+    ("europe", -1, 3),
+    ("eu", -1, 3),
+    ("cet", 1, 2),
+    ("emea", -1, 4),
+    ("africa", -1, 4),
+    ("india", 5, 6),
+    ("asia", 3, 9),
+    ("apac", 5, 12),
+    ("australia", 8, 11),
+    ("new zealand", 12, 13),
+    ("oceania", 8, 13),
+];
+
+impl TimezoneRange {
+    /// Every offset, for jobs that can be done from anywhere
+    pub const WORLDWIDE: TimezoneRange = TimezoneRange {
+        min_offset: MIN_UTC_OFFSET,
+        max_offset: MAX_UTC_OFFSET,
+    };
+
+    /// Parses region text into the band of offsets it allows.
+    ///
+    /// Explicit offsets ("UTC+1", "GMT-5 to GMT+2") win over region names.
+    /// When several regions are named the band spans all of them; a
+    /// worldwide name only counts when no region is named.
+    /// Returns None when the text doesn't say anything about location.
+    pub fn from_region(text: &str) -> Option<TimezoneRange> {
+        let text = text.to_lowercase();
+
+        let offsets = explicit_offsets(&text);
+        if !offsets.is_empty() {
+            return Some(TimezoneRange::spanning(offsets.into_iter()));
+        }
+
+        let words = Words::new(&text);
+        let mut bounds = Vec::new();
+        for (name, min_offset, max_offset) in REGIONS {
+            if words.contains(name) {
+                bounds.push(*min_offset);
+                bounds.push(*max_offset);
+            }
+        }
+        if bounds.is_empty() {
+            return WORLDWIDE_NAMES
+                .iter()
+                .any(|name| words.contains(name))
+                .then_some(TimezoneRange::WORLDWIDE);
+        }
+        Some(TimezoneRange::spanning(bounds.into_iter()))
+    }
+
+    /// Smallest band containing all `offsets` (which must not be empty)
+    fn spanning(offsets: impl Iterator<Item = i32> + Clone) -> TimezoneRange {
+        TimezoneRange {
+            min_offset: offsets.clone().min().unwrap_or(MIN_UTC_OFFSET),
+            max_offset: offsets.max().unwrap_or(MAX_UTC_OFFSET),
+        }
+    }
+
+    /// Whether someone at UTC `offset` is inside the band
+    pub fn contains(&self, offset: i32) -> bool {
+        (self.min_offset..=self.max_offset).contains(&offset)
+    }
+}
+
+/// Collects offsets written as "utc+1", "gmt -5" or plain "utc" (0)
+fn explicit_offsets(text: &str) -> Vec<i32> {
+    let mut offsets = Vec::new();
+    for marker in ["utc", "gmt"] {
+        for (start, _) in text.match_indices(marker) {
+            let rest = text[start + marker.len()..].trim_start();
+            let (sign, rest) = match rest.chars().next() {
+                Some('+') => (1, &rest[1..]),
+                Some('-') => (-1, &rest[1..]),
+                Some('−') => (-1, &rest['−'.len_utf8()..]),
+                _ => (1, rest),
+            };
+            let digits: String = rest.chars().take_while(char::is_ascii_digit).collect();
+            let offset = if digits.is_empty() {
+                0
+            } else {
+                match digits.parse::<i32>() {
+                    Ok(hours) => sign * hours,
+                    Err(_) => continue,
+                }
+            };
+            if (MIN_UTC_OFFSET..=MAX_UTC_OFFSET).contains(&offset) {
+                offsets.push(offset);
+            }
+        }
+    }
+    offsets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_region_names_map_to_bands() {
+        let europe = TimezoneRange::from_region("Europe Only").unwrap();
+        assert!(europe.contains(1));
+        assert!(!europe.contains(-5));
+
+        let us = TimezoneRange::from_region("US timezones").unwrap();
+        assert!(us.contains(-5));
+        assert!(!us.contains(1));
+
+        // Several regions: the band covers both
+        let both = TimezoneRange::from_region("USA, Europe").unwrap();
+        assert!(both.contains(-5) && both.contains(1));
+
+        // "us" inside another word isn't the United States
+        assert_eq!(TimezoneRange::from_region("Campus"), None);
+    }
+
+    #[test]
+    fn test_anywhere_is_worldwide() {
+        assert_eq!(
+            TimezoneRange::from_region("Anywhere in the World"),
+            Some(TimezoneRange::WORLDWIDE)
+        );
+        assert!(TimezoneRange::WORLDWIDE.contains(MIN_UTC_OFFSET));
+        assert!(TimezoneRange::WORLDWIDE.contains(MAX_UTC_OFFSET));
+
+        // "Anywhere" within a named region is that region
+        assert_eq!(
+            TimezoneRange::from_region("Anywhere in the US"),
+            Some(TimezoneRange {
+                min_offset: -10,
+                max_offset: -4
+            })
+        );
+    }
+
+    #[test]
+    fn test_explicit_offsets_win() {
+        assert_eq!(
+            TimezoneRange::from_region("Europe, UTC-3 to UTC+3"),
+            Some(TimezoneRange {
+                min_offset: -3,
+                max_offset: 3
+            })
+        );
+        assert_eq!(
+            TimezoneRange::from_region("GMT"),
+            Some(TimezoneRange {
+                min_offset: 0,
+                max_offset: 0
+            })
+        );
+    }
+}
@@ -0,0 +1,121 @@
+//! Job title normalization
+//!
+//! Boards word the same role many ways ("Sr. Backend Engineer (Remote)",
+//! "Senior Back-End Developer"). The normalized title keeps only what the
+//! role is, so equivalent listings can be grouped; the original title is
+//! still what gets displayed.
+
+/// Words that say how senior the role is, not what it is
+const SENIORITY_WORDS: &[&str] = &[
+    "senior",
+    "sr",
+    "junior",
+    "jr",
+    "mid",
+    "midlevel",
+    "entrylevel",
+    "principal",
+    "staff",
+    "ii",
+    "iii",
+    "iv",
+];
+
+/// Words that say where the role is, not what it is
+const LOCATION_WORDS: &[&str] = &["remote", "worldwide", "anywhere", "hybrid", "onsite"];
+
+/// Interchangeable words and the one they are normalized to
+const SYNONYMS: &[(&str, &str)] = &[
+    ("engineer", "developer"),
+    ("programmer", "developer"),
+    ("dev", "developer"),
+    ("devs", "developer"),
+    ("engineers", "developer"),
+    ("developers", "developer"),
+];
+
+/// Two-word spellings written as one word elsewhere ("back end" / "backend")
+const COMPOUNDS: &[(&str, &str, &str)] = &[
+    ("back", "end", "backend"),
+    ("front", "end", "frontend"),
+    ("full", "stack", "fullstack"),
+];
+
+/// Canonical form of a job title: lowercase, without parenthesized notes,
+/// seniority or location words, and with synonyms mapped to one spelling.
+/// "Sr. Backend Engineer (Remote)" and "Senior Back-End Developer" both
+/// become "backend developer".
+pub fn normalize_title(raw: &str) -> String {
+    // Parenthesized and bracketed notes are locations, contract types and the like
+    let mut text = String::with_capacity(raw.len());
+    let mut depth = 0usize;
+    for c in raw.chars() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth = depth.saturating_sub(1),
+            _ if depth == 0 => text.push(c),
+            _ => {}
+        }
+    }
+
+    // Hyphens join words ("back-end", "mid-level"); keep `+` and `#` for C++ and C#
+    let lowercase = text.to_lowercase().replace('-', "");
+    let words: Vec<&str> = lowercase
+        .split(|c: char| !c.is_alphanumeric() && c != '+' && c != '#')
+        .filter(|word| !word.is_empty())
+        .collect();
+
+    let mut normalized: Vec<&str> = Vec::new();
+    let mut i = 0;
+    while i < words.len() {
+        let compound = words.get(i + 1).and_then(|next| {
+            COMPOUNDS
+                .iter()
+                .find(|(first, second, _)| *first == words[i] && second == next)
+        });
+        if let Some((_, _, joined)) = compound {
+            normalized.push(joined);
+            i += 2;
+            continue;
+        }
+
+        let word = words[i];
+        i += 1;
+        if SENIORITY_WORDS.contains(&word) || LOCATION_WORDS.contains(&word) {
+            continue;
+        }
+        let word = SYNONYMS
+            .iter()
+            .find(|(synonym, _)| *synonym == word)
+            .map_or(word, |&(_, canonical)| canonical);
+        normalized.push(word);
+    }
+    normalized.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_equivalent_titles_normalize_alike() {
+        for title in [
+            "Sr. Backend Engineer (Remote)",
+            "Senior Back-End Developer",
+            "Backend Developer - Remote",
+            "senior back end dev [Contract]",
+            "Backend Engineer II",
+        ] {
+            assert_eq!(normalize_title(title), "backend developer", "{}", title);
+        }
+
+        assert_eq!(
+            normalize_title("Full-Stack Software Engineer"),
+            "fullstack software developer"
+        );
+        assert_eq!(normalize_title("C++ Programmer (EU)"), "c++ developer");
+        // Different roles stay apart
+        assert_eq!(normalize_title("Frontend Developer"), "frontend developer");
+        assert_eq!(normalize_title("Product Designer"), "product designer");
+    }
+}
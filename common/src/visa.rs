@@ -0,0 +1,135 @@
+//! Visa sponsorship mentions
+//!
+//! Whether a job sponsors visas is only ever in the listing text, and the
+//! same words appear on both sides: "visa sponsorship available" and "visa
+//! sponsorship is not available". Refusals are checked first, and a
+//! sponsorship phrase right after a negation ("we cannot offer visa
+//! sponsorship") counts as a refusal too. A listing that doesn't mention
+//! sponsorship or work authorization stays unknown.
+
+use crate::Words;
+
+/// Phrases that rule sponsorship out, checked against whole words. Apostrophes
+/// split words, so "can't" is matched as "can t".
+const REFUSALS: &[&str] = &[
+    "no sponsorship",
+    "no visa",
+    "sponsorship is not available",
+    "sponsorship not available",
+    "sponsorship is unavailable",
+    "sponsorship unavailable",
+    "not sponsor",
+    "not offer sponsorship",
+    "not provide sponsorship",
+    "not be sponsoring",
+    "t sponsor",
+    "unable to sponsor",
+    "without sponsorship",
+    "without visa sponsorship",
+    "must be authorized to work",
+    "must be legally authorized to work",
+    "must be eligible to work",
+    "must have the right to work",
+    "must already have the right to work",
+    "must have work authorization",
+];
+
+/// Phrases that offer sponsorship, unless a negation comes just before them
+const OFFERS: &[&str] = &[
+    "visa sponsorship",
+    "sponsorship available",
+    "sponsor visas",
+    "sponsor your visa",
+    "sponsor work visas",
+    "will sponsor",
+    "can sponsor",
+    "we sponsor",
+    "visa support",
+    "h1b sponsorship",
+    "h 1b sponsorship",
+];
+
+/// Words that negate an offer phrase shortly after them
+const NEGATIONS: &[&str] = &[
+    "no", "not", "cannot", "unable", "without", "never", "nor", "t",
+];
+
+/// How many words before an offer phrase a negation reaches
+const NEGATION_REACH: usize = 4;
+
+/// Infers from listing text whether the job sponsors visas: Some(false) when
+/// it says it doesn't (or requires existing work authorization), Some(true)
+/// when it offers sponsorship, None when it doesn't say.
+pub fn detect_visa_sponsorship(text: &str) -> Option<bool> {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    let whole_words = Words::new(text);
+    if REFUSALS.iter().any(|phrase| whole_words.contains(phrase)) {
+        return Some(false);
+    }
+
+    let mut offered = false;
+    for phrase in OFFERS {
+        let phrase_words: Vec<&str> = phrase.split(' ').collect();
+        for start in 0..words.len() {
+            let matches = phrase_words.iter().enumerate().all(|(i, phrase_word)| {
+                words.get(start + i).is_some_and(|word| word == phrase_word)
+            });
+            if !matches {
+                continue;
+            }
+            let before = &words[start.saturating_sub(NEGATION_REACH)..start];
+            if before.iter().any(|word| NEGATIONS.contains(&word.as_str())) {
+                return Some(false);
+            }
+            offered = true;
+        }
+    }
+    offered.then_some(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sponsorship_offered() {
+        for text in [
+            "Visa sponsorship available for the right candidate",
+            "We sponsor visas and help with relocation",
+            "Relocation package and visa support",
+            "H1B sponsorship provided",
+        ] {
+            assert_eq!(detect_visa_sponsorship(text), Some(true), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_sponsorship_refused() {
+        for text in [
+            "No sponsorship is available for this role",
+            "Visa sponsorship is not available",
+            "We cannot offer visa sponsorship at this time",
+            "We are unable to sponsor visas",
+            "We can't sponsor work visas",
+            "Candidates must be authorized to work in the US",
+            "Sorry, this role does not offer sponsorship",
+        ] {
+            assert_eq!(detect_visa_sponsorship(text), Some(false), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_sponsorship_not_mentioned() {
+        for text in [
+            "Senior Rust Developer, fully remote",
+            "Sponsored by our open source program",
+            "No on-call rotation; we offer relocation help",
+        ] {
+            assert_eq!(detect_visa_sponsorship(text), None, "{}", text);
+        }
+    }
+}
@@ -0,0 +1,60 @@
+//! Whole-word matching in free text
+//!
+//! Classifiers look for keywords and short phrases ("full time", "new
+//! zealand") in listing text without matching inside longer words ("java"
+//! in "javascript"). The text is split into lowercase words and joined back
+//! with single spaces, padded at both ends, so a phrase padded the same way
+//! can only match on word boundaries.
+
+/// Listing text as lowercase words, for whole-word phrase lookups
+#[derive(Debug, Clone)]
+pub struct Words {
+    padded: String,
+}
+
+impl Words {
+    /// Splits on anything that isn't a letter or digit
+    pub fn new(text: &str) -> Self {
+        Self::split(text, |c| c.is_alphanumeric())
+    }
+
+    /// Splits like `new` but keeps hyphens inside words, so "full-time"
+    /// stays one word
+    pub fn keeping_hyphens(text: &str) -> Self {
+        Self::split(text, |c| c.is_alphanumeric() || c == '-')
+    }
+
+    fn split(text: &str, in_word: impl Fn(char) -> bool) -> Self {
+        let words: Vec<&str> = text
+            .split(|c| !in_word(c))
+            .filter(|w| !w.is_empty())
+            .collect();
+        Self {
+            padded: format!(" {} ", words.join(" ").to_lowercase()),
+        }
+    }
+
+    /// Whether `phrase`, lowercase words separated by single spaces, appears
+    /// as whole words
+    pub fn contains(&self, phrase: &str) -> bool {
+        self.padded.contains(&format!(" {} ", phrase))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_words_match_phrases_on_word_boundaries() {
+        let words = Words::new("Senior JavaScript dev, New-Zealand");
+        assert!(words.contains("javascript"));
+        assert!(!words.contains("java"));
+        assert!(words.contains("new zealand"));
+        assert!(words.contains("senior javascript dev"));
+
+        let words = Words::keeping_hyphens("Full-Time role");
+        assert!(words.contains("full-time"));
+        assert!(!words.contains("full time"));
+    }
+}
@@ -0,0 +1,103 @@
+//! WeWorkRemotely category shorthand
+//!
+//! `--categories backend,frontend,devops` stands for the listing pages of
+//! those categories, so scraping more of the board doesn't mean copying
+//! URLs into boards.toml. Slugs are looked up in a built-in table of the
+//! board's category paths.
+
+/// Name of the board in boards.toml the categories belong to
+pub const BOARD: &str = "weworkremotely";
+
+/// Category slugs and their listing paths on weworkremotely.com
+pub const CATEGORIES: [(&str, &str); 12] = [
+    ("software-developer", "/remote-software-developer-jobs"),
+    ("programming", "/categories/remote-programming-jobs"),
+    (
+        "full-stack",
+        "/categories/remote-full-stack-programming-jobs",
+    ),
+    ("backend", "/categories/remote-back-end-programming-jobs"),
+    ("frontend", "/categories/remote-front-end-programming-jobs"),
+    ("devops", "/categories/remote-devops-sysadmin-jobs"),
+    ("design", "/categories/remote-design-jobs"),
+    ("product", "/categories/remote-product-jobs"),
+    (
+        "customer-support",
+        "/categories/remote-customer-support-jobs",
+    ),
+    (
+        "sales-marketing",
+        "/categories/remote-sales-and-marketing-jobs",
+    ),
+    (
+        "management-finance",
+        "/categories/remote-management-and-finance-jobs",
+    ),
+    ("other", "/categories/all-other-remote-jobs"),
+];
+
+/// The listing URLs for `urls` followed by those of the category `slugs`
+/// under `base_url`, each once. Slugs are matched ignoring case; unknown
+/// ones are returned separately so they can be reported.
+pub fn expand_categories(
+    base_url: &str,
+    urls: &[String],
+    slugs: &[String],
+) -> (Vec<String>, Vec<String>) {
+    let base_url = base_url.trim_end_matches('/');
+    let mut expanded: Vec<String> = Vec::new();
+    let mut unknown = Vec::new();
+    let mut add = |url: String| {
+        let url = url.trim_end_matches('/').to_string();
+        if !expanded.contains(&url) {
+            expanded.push(url);
+        }
+    };
+
+    for url in urls {
+        add(url.clone());
+    }
+    for slug in slugs {
+        let slug = slug.trim().to_lowercase();
+        if slug.is_empty() {
+            continue;
+        }
+        match CATEGORIES.iter().find(|(name, _)| *name == slug) {
+            Some((_, path)) => add(format!("{}{}", base_url, path)),
+            None => unknown.push(slug),
+        }
+    }
+    (expanded, unknown)
+}
+
+/// The known slugs, for the unknown-slug warning
+pub fn known_slugs() -> String {
+    let slugs: Vec<&str> = CATEGORIES.iter().map(|(slug, _)| *slug).collect();
+    slugs.join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_categories_expand_to_urls_without_duplicates() {
+        let strings =
+            |items: &[&str]| -> Vec<String> { items.iter().map(|item| item.to_string()).collect() };
+        let (urls, unknown) = expand_categories(
+            "https://weworkremotely.com/",
+            &strings(&["https://weworkremotely.com/categories/remote-back-end-programming-jobs/"]),
+            &strings(&["backend", "Frontend", "devops", "blockchain", "devops"]),
+        );
+
+        assert_eq!(
+            urls,
+            [
+                "https://weworkremotely.com/categories/remote-back-end-programming-jobs",
+                "https://weworkremotely.com/categories/remote-front-end-programming-jobs",
+                "https://weworkremotely.com/categories/remote-devops-sysadmin-jobs",
+            ]
+        );
+        assert_eq!(unknown, ["blockchain"]);
+    }
+}
@@ -0,0 +1,150 @@
+//! Jobs whose company selector found nothing
+//!
+//! Such a job used to be saved as "Unknown Company", which the server then
+//! counted as a company in facets and filters. Now the parser leaves the
+//! name empty and sets `company_missing`; `--missing-company` decides what
+//! is written out. It is applied after the quality checks, so a selector
+//! that stopped matching is still reported.
+
+use clap::ValueEnum;
+use common::Job;
+
+/// What to do with jobs the board gave no company name for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum MissingCompany {
+    /// Leave them out
+    Drop,
+    /// Keep them with an empty company and `company_missing: true`
+    #[default]
+    Tag,
+    /// Guess the company from the title ("Rust Developer at Acme") or the
+    /// URL (`/companies/acme/...`), tagging the job when neither says
+    Infer,
+}
+
+impl MissingCompany {
+    /// Handles the jobs without a company in `jobs`; the others pass through
+    pub fn apply(self, jobs: Vec<Job>) -> Vec<Job> {
+        match self {
+            MissingCompany::Drop => jobs
+                .into_iter()
+                .filter(|job| !job.company_missing)
+                .collect(),
+            MissingCompany::Tag => jobs,
+            MissingCompany::Infer => jobs
+                .into_iter()
+                .map(|mut job| {
+                    if job.company_missing {
+                        if let Some(company) = infer_company(&job) {
+                            job.company = company;
+                            job.company_missing = false;
+                        }
+                    }
+                    job
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A company name from the job's title or URL, when either has one
+pub fn infer_company(job: &Job) -> Option<String> {
+    company_from_title(&job.title).or_else(|| company_from_url(&job.url))
+}
+
+/// "Rust Developer at Acme" or "Rust Developer @ Acme" gives "Acme"
+fn company_from_title(title: &str) -> Option<String> {
+    [" at ", " @ "]
+        .iter()
+        .filter_map(|separator| {
+            title
+                .rfind(separator)
+                .map(|i| &title[i + separator.len()..])
+        })
+        .map(str::trim)
+        .find(|company| !company.is_empty())
+        .map(str::to_string)
+}
+
+/// `.../company/acme-corp/...` or `.../companies/acme-corp` gives "Acme Corp"
+fn company_from_url(url: &str) -> Option<String> {
+    let path = url.split(['?', '#']).next()?;
+    let mut segments = path.split('/');
+    segments.find(|segment| matches!(*segment, "company" | "companies"))?;
+    let slug = segments.next().filter(|slug| !slug.is_empty())?;
+    let words: Vec<String> = slug
+        .split(['-', '_'])
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect())
+                .unwrap_or_default()
+        })
+        .collect();
+    (!words.is_empty()).then(|| words.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(title: &str, company: Option<&str>, url: &str) -> Job {
+        Job {
+            title: title.to_string(),
+            company: company.unwrap_or_default().to_string(),
+            company_missing: company.is_none(),
+            url: url.to_string(),
+            ..Job::default()
+        }
+    }
+
+    fn jobs() -> Vec<Job> {
+        vec![
+            job("Rust Developer", Some("Globex"), "https://example.com/1"),
+            job("Rust Developer at Acme", None, "https://example.com/2"),
+            job(
+                "Go Engineer",
+                None,
+                "https://example.com/companies/initech-labs/jobs/3",
+            ),
+            job("Designer", None, "https://example.com/4"),
+        ]
+    }
+
+    fn companies(jobs: &[Job]) -> Vec<(&str, bool)> {
+        jobs.iter()
+            .map(|job| (job.company.as_str(), job.company_missing))
+            .collect()
+    }
+
+    #[test]
+    fn test_drop_leaves_out_jobs_without_company() {
+        let kept = MissingCompany::Drop.apply(jobs());
+        assert_eq!(companies(&kept), [("Globex", false)]);
+    }
+
+    #[test]
+    fn test_tag_keeps_jobs_with_empty_company() {
+        let kept = MissingCompany::Tag.apply(jobs());
+        assert_eq!(
+            companies(&kept),
+            [("Globex", false), ("", true), ("", true), ("", true)]
+        );
+    }
+
+    #[test]
+    fn test_infer_reads_company_from_title_or_url() {
+        let kept = MissingCompany::Infer.apply(jobs());
+        assert_eq!(
+            companies(&kept),
+            [
+                ("Globex", false),
+                ("Acme", false),
+                ("Initech Labs", false),
+                ("", true)
+            ]
+        );
+    }
+}
@@ -0,0 +1,300 @@
+//! Board configuration
+//!
+//! Which boards to scrape, and the CSS selectors for each, live in a TOML
+//! file (`boards.toml`) so they can be changed without recompiling.
+
+use common::{DedupStrategy, Error};
+use scraper::Selector;
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+/// Built-in configuration, used when no `--config` file is given
+const DEFAULT_CONFIG: &str = include_str!("../boards.toml");
+
+/// Top level of the boards file
+#[derive(Debug, Deserialize)]
+pub struct ScraperConfig {
+    /// What makes two scraped jobs the same job, across all boards
+    #[serde(default)]
+    pub dedup: DedupStrategy,
+    pub boards: Vec<BoardConfig>,
+}
+
+/// One job board as written in the config file
+#[derive(Debug, Deserialize)]
+pub struct BoardConfig {
+    pub name: String,
+    /// Prefix for relative links found on the board
+    pub base_url: String,
+    /// Listing pages to start scraping from
+    pub urls: Vec<String>,
+    /// Set to false to leave the board out of runs without deleting it
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool,
+    /// What to do with listings marked expired or filled
+    #[serde(default)]
+    pub closed_listings: ClosedListings,
+    /// How schema.org `JobPosting` JSON-LD on the board's pages is used
+    #[serde(default)]
+    pub structured_data: StructuredData,
+    pub selectors: SelectorConfig,
+}
+
+/// Handling of listings a board still shows after they expired or were filled
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClosedListings {
+    /// Leave them out of the scraped jobs
+    #[default]
+    Skip,
+    /// Keep them, with a "closed" tag
+    Tag,
+}
+
+/// Use of the `JobPosting` JSON-LD a board embeds for search engines
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StructuredData {
+    /// Only when the CSS selectors find nothing: no listings on a page, or
+    /// no description on a detail page
+    #[default]
+    Fallback,
+    /// Before the CSS selectors, whenever a page has any
+    Primary,
+    /// Never
+    Off,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+/// Raw CSS selector strings for a board
+#[derive(Debug, Deserialize)]
+pub struct SelectorConfig {
+    pub job: String,
+    pub title: String,
+    pub company: String,
+    pub region: String,
+    pub link: String,
+    pub next_page: String,
+    pub description: String,
+    /// Tag/category labels inside a listing; optional, since not every board has them
+    #[serde(default)]
+    pub tags: Option<String>,
+    /// Marks a listing as featured when it matches the listing element itself
+    /// or something inside it; optional
+    #[serde(default)]
+    pub featured: Option<String>,
+    /// Marks a listing as expired or filled, matched like `featured`; optional.
+    /// Listings saying so in their text ("Position filled") are caught without it.
+    #[serde(default)]
+    pub closed: Option<String>,
+    /// When the listing was posted ("3 days ago", "Jan 5"); optional. A
+    /// `<time datetime="...">` element's attribute is used over its text.
+    #[serde(default)]
+    pub posted: Option<String>,
+}
+
+/// CSS selectors used to pick job data out of a listing page
+pub struct Selectors {
+    pub job: Selector,
+    pub title: Selector,
+    pub company: Selector,
+    pub region: Selector,
+    pub link: Selector,
+    pub next_page: Selector,
+    pub description: Selector,
+    pub tags: Option<Selector>,
+    pub featured: Option<Selector>,
+    pub closed: Option<Selector>,
+    pub posted: Option<Selector>,
+}
+
+/// A board ready to scrape, with its selectors compiled
+pub struct Board {
+    pub name: String,
+    pub base_url: String,
+    pub urls: Vec<String>,
+    pub enabled: bool,
+    pub closed_listings: ClosedListings,
+    pub structured_data: StructuredData,
+    pub selectors: Selectors,
+}
+
+/// Compiles one selector, naming it in the error so a bad config is easy to fix
+fn parse_selector(board: &str, name: &str, css: &str) -> Result<Selector, Error> {
+    Selector::parse(css).map_err(|e| {
+        Error::Config(format!(
+            "board `{}`: invalid `{}` selector {:?}: {:?}",
+            board, name, css, e
+        ))
+    })
+}
+
+impl Selectors {
+    pub fn from_config(board: &str, config: &SelectorConfig) -> Result<Selectors, Error> {
+        Ok(Selectors {
+            job: parse_selector(board, "job", &config.job)?,
+            title: parse_selector(board, "title", &config.title)?,
+            company: parse_selector(board, "company", &config.company)?,
+            region: parse_selector(board, "region", &config.region)?,
+            link: parse_selector(board, "link", &config.link)?,
+            next_page: parse_selector(board, "next_page", &config.next_page)?,
+            description: parse_selector(board, "description", &config.description)?,
+            tags: config
+                .tags
+                .as_deref()
+                .map(|css| parse_selector(board, "tags", css))
+                .transpose()?,
+            featured: config
+                .featured
+                .as_deref()
+                .map(|css| parse_selector(board, "featured", css))
+                .transpose()?,
+            closed: config
+                .closed
+                .as_deref()
+                .map(|css| parse_selector(board, "closed", css))
+                .transpose()?,
+            posted: config
+                .posted
+                .as_deref()
+                .map(|css| parse_selector(board, "posted", css))
+                .transpose()?,
+        })
+    }
+}
+
+impl ScraperConfig {
+    /// Parses a boards file from TOML text
+    pub fn parse(text: &str) -> Result<ScraperConfig, Error> {
+        toml::from_str(text).map_err(|e| Error::Config(format!("board file: {}", e)))
+    }
+
+    /// Reads the boards file at `path`, or the built-in one when `path` is None
+    pub fn load(path: Option<&Path>) -> Result<ScraperConfig, Error> {
+        match path {
+            Some(path) => {
+                let text = fs::read_to_string(path).map_err(|e| Error::io(path, e))?;
+                ScraperConfig::parse(&text)
+            }
+            None => ScraperConfig::parse(DEFAULT_CONFIG),
+        }
+    }
+
+    /// Compiles every board's selectors, failing on the first invalid one
+    pub fn into_boards(self) -> Result<Vec<Board>, Error> {
+        self.boards
+            .into_iter()
+            .map(|board| {
+                let selectors = Selectors::from_config(&board.name, &board.selectors)?;
+                Ok(Board {
+                    name: board.name,
+                    base_url: board.base_url,
+                    urls: board.urls,
+                    enabled: board.enabled,
+                    closed_listings: board.closed_listings,
+                    structured_data: board.structured_data,
+                    selectors,
+                })
+            })
+            .collect()
+    }
+}
+
+/// A board left out of a run, and why
+pub type SkippedBoard = (String, &'static str);
+
+/// Picks the boards to scrape this run. With `only`, exactly those boards run,
+/// even disabled ones; otherwise every enabled board does. Boards in `skip`
+/// are then left out. Returns the boards to scrape, and the skipped boards
+/// with the reason. Fails on a board name that isn't configured.
+pub fn select_boards(
+    boards: Vec<Board>,
+    only: &[String],
+    skip: &[String],
+) -> Result<(Vec<Board>, Vec<SkippedBoard>), Error> {
+    for name in only.iter().chain(skip) {
+        if !boards.iter().any(|board| &board.name == name) {
+            let names: Vec<&str> = boards.iter().map(|board| board.name.as_str()).collect();
+            return Err(Error::Config(format!(
+                "unknown board `{}`: configured boards are {}",
+                name,
+                names.join(", ")
+            )));
+        }
+    }
+
+    let mut selected = Vec::new();
+    let mut skipped = Vec::new();
+    for board in boards {
+        let reason = if skip.contains(&board.name) {
+            Some("--skip")
+        } else if !only.is_empty() {
+            (!only.contains(&board.name)).then_some("not in --only")
+        } else {
+            (!board.enabled).then_some("disabled in config")
+        };
+        match reason {
+            Some(reason) => skipped.push((board.name, reason)),
+            None => selected.push(board),
+        }
+    }
+    Ok((selected, skipped))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_config_compiles() {
+        let boards = ScraperConfig::load(None).unwrap().into_boards().unwrap();
+        assert_eq!(boards[0].name, "weworkremotely");
+        assert_eq!(boards[0].urls.len(), 4);
+    }
+
+    #[test]
+    fn test_select_boards_applies_only_and_skip() {
+        let boards = || ScraperConfig::load(None).unwrap().into_boards().unwrap();
+        let names = |boards: &[Board]| -> Vec<String> {
+            boards.iter().map(|board| board.name.clone()).collect()
+        };
+
+        let (selected, _) = select_boards(boards(), &[], &[]).unwrap();
+        assert_eq!(names(&selected), ["weworkremotely"]);
+
+        let (selected, skipped) =
+            select_boards(boards(), &[], &["weworkremotely".to_string()]).unwrap();
+        assert!(selected.is_empty());
+        assert_eq!(skipped, vec![("weworkremotely".to_string(), "--skip")]);
+
+        let error = select_boards(boards(), &["remoteok".to_string()], &[])
+            .err()
+            .expect("unknown board should be rejected")
+            .to_string();
+        assert!(error.contains("unknown board `remoteok`"), "{}", error);
+    }
+
+    #[test]
+    fn test_bad_selector_is_reported_by_name() {
+        let text = DEFAULT_CONFIG.replace(
+            r#"company = ".new-listing__company-name""#,
+            r#"company = "div[[broken""#,
+        );
+
+        let error = ScraperConfig::parse(&text)
+            .unwrap()
+            .into_boards()
+            .err()
+            .expect("bad selector should be rejected")
+            .to_string();
+
+        assert!(error.starts_with("invalid configuration"), "{}", error);
+        assert!(error.contains("weworkremotely"), "{}", error);
+        assert!(error.contains("`company` selector"), "{}", error);
+        assert!(error.contains("div[[broken"), "{}", error);
+    }
+}
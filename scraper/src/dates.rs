@@ -0,0 +1,296 @@
+//! Posting dates as boards show them
+//!
+//! Listings rarely carry a machine-readable date. Most say how long ago the
+//! job went up ("3 days ago", "5d", "Yesterday"), which only means something
+//! relative to when the page was scraped; others print a calendar date
+//! ("Jan 5, 2024"). Both are resolved to an absolute date-time so the index
+//! can sort and filter on it.
+//!
+//! Application deadlines ("Apply by March 1") are read the same way, except
+//! that they lie ahead: a date without a year is the next one, and a date
+//! without a time lasts until the end of that day.
+
+use regex::Regex;
+use std::sync::OnceLock;
+use time::format_description::well_known::Rfc3339;
+use time::{Date, Duration, Month, OffsetDateTime, Time};
+
+/// Resolves a posting date as a listing shows it against `now`, the time of
+/// the scrape. Handles "N minutes/hours/days/weeks/months ago" (and the short
+/// "5d" form), "today", "yesterday", RFC 3339, ISO dates and dates with a
+/// month name. A calendar date without a year is taken as the latest one
+/// not after `now`. Returns None for anything else.
+pub fn parse_relative_date(text: &str, now: OffsetDateTime) -> Option<OffsetDateTime> {
+    if let Ok(date) = OffsetDateTime::parse(text.trim(), &Rfc3339) {
+        return Some(date);
+    }
+
+    let text = text.trim().to_lowercase();
+    let text = text.strip_prefix("posted").unwrap_or(&text).trim();
+    match text {
+        "today" | "just now" | "new" => return Some(now),
+        "yesterday" => return Some(now - Duration::days(1)),
+        _ => {}
+    }
+
+    // "3 days ago", "2 hrs ago", "5d", "1 month ago"
+    static RELATIVE: OnceLock<Regex> = OnceLock::new();
+    let relative = RELATIVE.get_or_init(|| {
+        Regex::new(
+            r"^(\d+)\s*(minutes?|mins?|m|hours?|hrs?|h|days?|d|weeks?|wks?|w|months?|mos?)(?:\s+ago)?$",
+        )
+        .expect("relative date pattern is valid")
+    });
+    if let Some(caps) = relative.captures(text) {
+        let amount: i64 = caps[1].parse().ok()?;
+        let unit = match &caps[2] {
+            "m" | "min" | "mins" | "minute" | "minutes" => Duration::minutes(1),
+            "h" | "hr" | "hrs" | "hour" | "hours" => Duration::hours(1),
+            "d" | "day" | "days" => Duration::days(1),
+            "w" | "wk" | "wks" | "week" | "weeks" => Duration::weeks(1),
+            // Boards round to whole months, so 30 days is as close as it gets
+            _ => Duration::days(30),
+        };
+        return now.checked_sub(unit.checked_mul(i32::try_from(amount).ok()?)?);
+    }
+
+    let (year, month, day) = calendar_date(text)?;
+    match year {
+        Some(year) => {
+            let date = Date::from_calendar_date(year, month, day).ok()?;
+            Some(at_midnight(date, now))
+        }
+        None => {
+            let this_year = Date::from_calendar_date(now.year(), month, day).ok()?;
+            let date = at_midnight(this_year, now);
+            if date <= now {
+                Some(date)
+            } else {
+                let last_year = Date::from_calendar_date(now.year() - 1, month, day).ok()?;
+                Some(at_midnight(last_year, now))
+            }
+        }
+    }
+}
+
+/// Resolves an application deadline against `now`: RFC 3339, or a calendar
+/// date as `parse_relative_date` reads them, lasting to the end of that day.
+/// A date without a year is the next such day from today on.
+pub fn parse_deadline(text: &str, now: OffsetDateTime) -> Option<OffsetDateTime> {
+    if let Ok(date) = OffsetDateTime::parse(text.trim(), &Rfc3339) {
+        return Some(date);
+    }
+
+    let text = text.trim().to_lowercase();
+    let (year, month, day) = calendar_date(&text)?;
+    let date = match year {
+        Some(year) => Date::from_calendar_date(year, month, day).ok()?,
+        None => {
+            let this_year = Date::from_calendar_date(now.year(), month, day).ok()?;
+            if this_year >= now.date() {
+                this_year
+            } else {
+                Date::from_calendar_date(now.year() + 1, month, day).ok()?
+            }
+        }
+    };
+    let end_of_day = Time::from_hms(23, 59, 59).ok()?;
+    Some(date.with_time(end_of_day).assume_offset(now.offset()))
+}
+
+/// Finds an application deadline in listing text ("Apply by Jan 31, 2025",
+/// "Deadline: 2025-01-31", "Applications close 5 March") and resolves it
+/// with `parse_deadline`
+pub fn find_deadline(text: &str, now: OffsetDateTime) -> Option<OffsetDateTime> {
+    static PHRASE: OnceLock<Regex> = OnceLock::new();
+    let phrase = PHRASE.get_or_init(|| {
+        Regex::new(
+            r"(?i)\b(?:apply by|apply before|deadline|closing date|closes on|applications close(?: on)?)\s*:?\s+(\d{4}-\d{2}-\d{2}|[a-z]+\.?\s+\d{1,2}(?:st|nd|rd|th)?(?:,?\s+\d{4})?|\d{1,2}(?:st|nd|rd|th)?\s+[a-z]+\.?(?:,?\s+\d{4})?)",
+        )
+        .expect("deadline pattern is valid")
+    });
+    phrase
+        .captures_iter(text)
+        .find_map(|caps| parse_deadline(&caps[1], now))
+}
+
+/// Year (if given), month and day of a lowercased calendar date:
+/// "2024-01-05" (possibly followed by a time), "jan 5, 2024", "january 5th"
+/// or "5 jan 2024"
+fn calendar_date(text: &str) -> Option<(Option<i32>, Month, u8)> {
+    static ISO_DATE: OnceLock<Regex> = OnceLock::new();
+    static MONTH_FIRST: OnceLock<Regex> = OnceLock::new();
+    static DAY_FIRST: OnceLock<Regex> = OnceLock::new();
+
+    let iso_date = ISO_DATE.get_or_init(|| {
+        Regex::new(r"^(\d{4})-(\d{2})-(\d{2})\b").expect("ISO date pattern is valid")
+    });
+    if let Some(caps) = iso_date.captures(text) {
+        let month = Month::try_from(caps[2].parse::<u8>().ok()?).ok()?;
+        return Some((Some(caps[1].parse().ok()?), month, caps[3].parse().ok()?));
+    }
+
+    let month_first = MONTH_FIRST.get_or_init(|| {
+        Regex::new(r"^([a-z]+)\.?\s+(\d{1,2})(?:st|nd|rd|th)?,?(?:\s+(\d{4}))?$")
+            .expect("month-first date pattern is valid")
+    });
+    let day_first = DAY_FIRST.get_or_init(|| {
+        Regex::new(r"^(\d{1,2})(?:st|nd|rd|th)?\s+([a-z]+)\.?,?(?:\s+(\d{4}))?$")
+            .expect("day-first date pattern is valid")
+    });
+    let (month, day, year) = if let Some(caps) = month_first.captures(text) {
+        (month_named(&caps[1])?, caps[2].parse().ok()?, caps.get(3))
+    } else if let Some(caps) = day_first.captures(text) {
+        (month_named(&caps[2])?, caps[1].parse().ok()?, caps.get(3))
+    } else {
+        return None;
+    };
+    let year = year.map(|year| year.as_str().parse()).transpose().ok()?;
+    Some((year, month, day))
+}
+
+/// Start of `date` in the offset of `now`
+fn at_midnight(date: Date, now: OffsetDateTime) -> OffsetDateTime {
+    date.with_time(Time::MIDNIGHT).assume_offset(now.offset())
+}
+
+/// Month for an English month name or its abbreviation ("jan", "sept")
+fn month_named(name: &str) -> Option<Month> {
+    const MONTHS: [(&str, Month); 12] = [
+        ("january", Month::January),
+        ("february", Month::February),
+        ("march", Month::March),
+        ("april", Month::April),
+        ("may", Month::May),
+        ("june", Month::June),
+        ("july", Month::July),
+        ("august", Month::August),
+        ("september", Month::September),
+        ("october", Month::October),
+        ("november", Month::November),
+        ("december", Month::December),
+    ];
+    MONTHS
+        .iter()
+        .find(|(full, _)| name.len() >= 3 && full.starts_with(name))
+        .map(|&(_, month)| month)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2024-05-10 15:30 UTC
+    fn now() -> OffsetDateTime {
+        OffsetDateTime::parse("2024-05-10T15:30:00Z", &Rfc3339).unwrap()
+    }
+
+    fn resolved(text: &str) -> Option<String> {
+        parse_relative_date(text, now()).map(|date| date.format(&Rfc3339).unwrap())
+    }
+
+    #[test]
+    fn test_relative_phrases() {
+        assert_eq!(
+            resolved("3 days ago").as_deref(),
+            Some("2024-05-07T15:30:00Z")
+        );
+        assert_eq!(
+            resolved("Posted 1 day ago").as_deref(),
+            Some("2024-05-09T15:30:00Z")
+        );
+        assert_eq!(
+            resolved("2 hours ago").as_deref(),
+            Some("2024-05-10T13:30:00Z")
+        );
+        assert_eq!(
+            resolved("45 mins ago").as_deref(),
+            Some("2024-05-10T14:45:00Z")
+        );
+        assert_eq!(
+            resolved("2 weeks ago").as_deref(),
+            Some("2024-04-26T15:30:00Z")
+        );
+        assert_eq!(
+            resolved("1 month ago").as_deref(),
+            Some("2024-04-10T15:30:00Z")
+        );
+        assert_eq!(resolved("5d").as_deref(), Some("2024-05-05T15:30:00Z"));
+    }
+
+    #[test]
+    fn test_today_and_yesterday() {
+        assert_eq!(resolved("Today").as_deref(), Some("2024-05-10T15:30:00Z"));
+        assert_eq!(
+            resolved("posted yesterday").as_deref(),
+            Some("2024-05-09T15:30:00Z")
+        );
+    }
+
+    #[test]
+    fn test_absolute_dates() {
+        assert_eq!(
+            resolved("2024-01-05T09:00:00+01:00").as_deref(),
+            Some("2024-01-05T09:00:00+01:00")
+        );
+        assert_eq!(
+            resolved("2024-01-05").as_deref(),
+            Some("2024-01-05T00:00:00Z")
+        );
+        assert_eq!(
+            resolved("Jan 5, 2024").as_deref(),
+            Some("2024-01-05T00:00:00Z")
+        );
+        assert_eq!(
+            resolved("5 January 2024").as_deref(),
+            Some("2024-01-05T00:00:00Z")
+        );
+        // Without a year: the latest such date up to now
+        assert_eq!(resolved("May 1st").as_deref(), Some("2024-05-01T00:00:00Z"));
+        assert_eq!(resolved("Dec 20").as_deref(), Some("2023-12-20T00:00:00Z"));
+    }
+
+    #[test]
+    fn test_deadlines_last_the_day_and_lie_ahead() {
+        let deadline =
+            |text: &str| parse_deadline(text, now()).map(|date| date.format(&Rfc3339).unwrap());
+        assert_eq!(
+            deadline("2024-06-01").as_deref(),
+            Some("2024-06-01T23:59:59Z")
+        );
+        assert_eq!(
+            deadline("2024-06-01T12:00:00Z").as_deref(),
+            Some("2024-06-01T12:00:00Z")
+        );
+        // Without a year: the next such day, today included
+        assert_eq!(deadline("May 10").as_deref(), Some("2024-05-10T23:59:59Z"));
+        assert_eq!(deadline("Jan 5").as_deref(), Some("2025-01-05T23:59:59Z"));
+        assert_eq!(deadline("3 days ago"), None);
+    }
+
+    #[test]
+    fn test_find_deadline_in_listing_text() {
+        let found =
+            |text: &str| find_deadline(text, now()).map(|date| date.format(&Rfc3339).unwrap());
+        assert_eq!(
+            found("Senior Rust Developer. Apply by June 30, 2024!").as_deref(),
+            Some("2024-06-30T23:59:59Z")
+        );
+        assert_eq!(
+            found("Remote | Deadline: 2024-05-20 | $90k").as_deref(),
+            Some("2024-05-20T23:59:59Z")
+        );
+        assert_eq!(
+            found("Applications close 1 July").as_deref(),
+            Some("2024-07-01T23:59:59Z")
+        );
+        assert_eq!(found("Posted May 1st, apply now"), None);
+    }
+
+    #[test]
+    fn test_undetectable_dates() {
+        for text in ["", "Featured", "soon", "Feb 30, 2024", "3 fortnights ago"] {
+            assert_eq!(resolved(text), None, "{:?}", text);
+        }
+    }
+}
@@ -0,0 +1,380 @@
+//! Schema.org `JobPosting` structured data
+//!
+//! Many boards embed their listings as JSON-LD (`<script
+//! type="application/ld+json">`) for search engines. It is written for
+//! machines, so it names each field outright and survives redesigns that
+//! break the CSS selectors. Postings may sit at the top level of a block, in
+//! an array, under `@graph` or inside an `ItemList`.
+
+use crate::dates::{parse_deadline, parse_relative_date};
+use crate::tags::keyword_tags;
+use common::{
+    Job, SalaryPeriod, TimezoneRange, WORLDWIDE, classify_contract, countries_from_region,
+    detect_visa_sponsorship,
+};
+use scraper::{Html, Selector};
+use serde_json::Value;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+
+/// Jobs from every `JobPosting` in the page's JSON-LD blocks. A posting
+/// without a URL gets `page_url`, or is skipped without one; `now` resolves
+/// relative dates. Blocks that aren't valid JSON and postings without a
+/// title are skipped too.
+pub fn job_postings(document: &Html, page_url: Option<&str>, now: OffsetDateTime) -> Vec<Job> {
+    let Ok(selector) = Selector::parse(r#"script[type="application/ld+json"]"#) else {
+        return Vec::new();
+    };
+    let mut jobs = Vec::new();
+    for script in document.select(&selector) {
+        let Ok(value) = serde_json::from_str::<Value>(&script.text().collect::<String>()) else {
+            continue;
+        };
+        let mut postings = Vec::new();
+        find_postings(&value, &mut postings);
+        jobs.extend(
+            postings
+                .into_iter()
+                .filter_map(|posting| to_job(posting, page_url, now)),
+        );
+    }
+    jobs
+}
+
+/// Collects the `JobPosting` objects in `value`, wherever they are nested
+fn find_postings<'a>(value: &'a Value, postings: &mut Vec<&'a Value>) {
+    match value {
+        Value::Array(items) => {
+            for item in items {
+                find_postings(item, postings);
+            }
+        }
+        Value::Object(object) => {
+            if has_type(value, "JobPosting") {
+                postings.push(value);
+                return;
+            }
+            for key in ["@graph", "itemListElement", "item"] {
+                if let Some(nested) = object.get(key) {
+                    find_postings(nested, postings);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Whether `@type` is `name`, alone or in a list
+fn has_type(value: &Value, name: &str) -> bool {
+    match value.get("@type") {
+        Some(Value::String(kind)) => kind == name,
+        Some(Value::Array(kinds)) => kinds.iter().any(|kind| kind.as_str() == Some(name)),
+        _ => false,
+    }
+}
+
+/// Text of a field given either as a string or as an object with a `name`
+fn text_of(value: Option<&Value>) -> Option<String> {
+    let text = match value? {
+        Value::String(text) => text.as_str(),
+        object @ Value::Object(_) => object.get("name")?.as_str()?,
+        _ => return None,
+    };
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// A number given as JSON or as a string ("85000.00")
+fn number_of(value: Option<&Value>) -> Option<i64> {
+    match value? {
+        Value::Number(number) => number.as_f64().map(|n| n.round() as i64),
+        Value::String(text) => text.trim().parse::<f64>().ok().map(|n| n.round() as i64),
+        _ => None,
+    }
+}
+
+/// Plain text of a description, which is usually HTML
+fn plain_text(html: &str) -> String {
+    Html::parse_fragment(html)
+        .root_element()
+        .text()
+        .flat_map(str::split_whitespace)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// "City, Region, Country" from one `Place`, or its name
+fn place_name(place: &Value) -> Option<String> {
+    let Some(address) = place.get("address") else {
+        return text_of(Some(place));
+    };
+    if let Value::String(address) = address {
+        return Some(address.trim().to_string()).filter(|address| !address.is_empty());
+    }
+    let parts: Vec<String> = ["addressLocality", "addressRegion", "addressCountry"]
+        .iter()
+        .filter_map(|key| text_of(address.get(*key)))
+        .collect();
+    (!parts.is_empty()).then(|| parts.join(", "))
+}
+
+/// Where the job is: "Remote" (with the allowed regions, if any) for
+/// telecommute postings, otherwise the listed places
+fn location_of(posting: &Value) -> Option<String> {
+    let places = |key: &str| -> Vec<String> {
+        match posting.get(key) {
+            Some(Value::Array(items)) => items.iter().filter_map(place_name).collect(),
+            Some(item) => place_name(item).into_iter().collect(),
+            None => Vec::new(),
+        }
+    };
+    let remote = posting
+        .get("jobLocationType")
+        .and_then(Value::as_str)
+        .is_some_and(|kind| kind.eq_ignore_ascii_case("TELECOMMUTE"));
+    if remote {
+        let regions = places("applicantLocationRequirements");
+        return Some(if regions.is_empty() {
+            "Remote".to_string()
+        } else {
+            format!("Remote - {}", regions.join(", "))
+        });
+    }
+    let places = places("jobLocation");
+    (!places.is_empty()).then(|| places.join("; "))
+}
+
+/// ISO codes given as `addressCountry` of the job's places ("DE"), which
+/// the region text matching doesn't know
+fn country_codes(posting: &Value) -> Vec<String> {
+    let places = match posting.get("jobLocation") {
+        Some(Value::Array(items)) => items.iter().collect(),
+        Some(item) => vec![item],
+        None => Vec::new(),
+    };
+    places
+        .into_iter()
+        .filter_map(|place| text_of(place.get("address")?.get("addressCountry")))
+        .filter(|code| code.len() == 2 && code.chars().all(|c| c.is_ascii_alphabetic()))
+        .map(|code| code.to_uppercase())
+        .collect()
+}
+
+/// Salary range, currency and period from `baseSalary`. Monthly figures are
+/// annualized like listing salaries; other periods (hourly, daily) are left
+/// out, since turning them into a yearly figure would be a guess.
+fn salary_of(
+    posting: &Value,
+) -> (
+    Option<i64>,
+    Option<i64>,
+    Option<String>,
+    Option<SalaryPeriod>,
+) {
+    let Some(salary) = posting.get("baseSalary") else {
+        return (None, None, None, None);
+    };
+    let amount = salary.get("value").unwrap_or(salary);
+    let (min, max) = match amount {
+        Value::Object(_) => {
+            let min = number_of(amount.get("minValue")).or(number_of(amount.get("value")));
+            let max = number_of(amount.get("maxValue")).filter(|max| Some(*max) != min);
+            (min, max)
+        }
+        other => (number_of(Some(other)), None),
+    };
+    let period = match amount.get("unitText").and_then(Value::as_str) {
+        Some(unit) if unit.eq_ignore_ascii_case("YEAR") => Some(SalaryPeriod::Yearly),
+        Some(unit) if unit.eq_ignore_ascii_case("MONTH") => Some(SalaryPeriod::Monthly),
+        Some(_) => return (None, None, None, None),
+        None => None,
+    };
+    let annualize = |salary: Option<i64>| match period {
+        Some(SalaryPeriod::Monthly) => salary.map(|salary| salary * 12),
+        _ => salary,
+    };
+    let currency = min
+        .and_then(|_| text_of(salary.get("currency")))
+        .map(|currency| currency.to_uppercase());
+    (
+        annualize(min),
+        annualize(max),
+        currency,
+        period.filter(|_| min.is_some()),
+    )
+}
+
+/// Maps one `JobPosting` onto a job
+fn to_job(posting: &Value, page_url: Option<&str>, now: OffsetDateTime) -> Option<Job> {
+    let title = text_of(posting.get("title"))?;
+    let company = text_of(posting.get("hiringOrganization"));
+    let location = location_of(posting).unwrap_or_else(|| "Remote".to_string());
+    let description = posting
+        .get("description")
+        .and_then(Value::as_str)
+        .map(plain_text)
+        .unwrap_or_default();
+    let url = text_of(posting.get("url")).or_else(|| page_url.map(str::to_string))?;
+    let (salary_min, salary_max, salary_currency, salary_period) = salary_of(posting);
+    let posted_date = text_of(posting.get("datePosted"))
+        .and_then(|text| parse_relative_date(&text, now))
+        .and_then(|date| date.format(&Rfc3339).ok());
+    let deadline = text_of(posting.get("validThrough"))
+        .and_then(|text| parse_deadline(&text, now))
+        .and_then(|date| date.format(&Rfc3339).ok());
+
+    // "FULL_TIME", or a list of them
+    let employment = match posting.get("employmentType") {
+        Some(Value::Array(kinds)) => kinds
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>()
+            .join(" "),
+        Some(Value::String(kind)) => kind.clone(),
+        _ => String::new(),
+    }
+    .replace('_', "-");
+    let full_text = format!("{} {}", title, description);
+    let contract_type = classify_contract(&employment).or_else(|| classify_contract(&full_text));
+    let mut countries = countries_from_region(&location);
+    if !countries.iter().any(|code| code == WORLDWIDE) {
+        countries.extend(country_codes(posting));
+        countries.sort_unstable();
+        countries.dedup();
+    }
+
+    Some(Job {
+        tags: keyword_tags(&format!("{} {} {}", title, location, employment)),
+        timezone: TimezoneRange::from_region(&location),
+        countries,
+        visa_sponsorship: detect_visa_sponsorship(&full_text),
+        company_missing: company.is_none(),
+        company: company.unwrap_or_default(),
+        title,
+        location,
+        description,
+        salary_min,
+        salary_max,
+        salary_currency,
+        salary_period,
+        // The structured value itself: JSON-LD has no written-out salary
+        salary_raw: salary_min.and(posting.get("baseSalary").map(Value::to_string)),
+        url,
+        posted_date,
+        deadline,
+        scraped_at: None,
+        contract_type,
+        featured: false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::ContractType;
+
+    /// 2024-05-10 15:30 UTC
+    fn now() -> OffsetDateTime {
+        OffsetDateTime::parse("2024-05-10T15:30:00Z", &Rfc3339).unwrap()
+    }
+
+    #[test]
+    fn test_job_posting_fields_map_onto_job() {
+        let html = r#"<html><head>
+            <script type="application/ld+json">{"@type": "Organization", "name": "Board"}</script>
+            <script type="application/ld+json">
+            {
+              "@context": "https://schema.org",
+              "@type": "JobPosting",
+              "title": "Senior Rust Engineer",
+              "description": "<p>Build <b>fast</b> services.</p><p>We sponsor visas.</p>",
+              "datePosted": "2024-05-01",
+              "validThrough": "2024-06-30T23:59:59+02:00",
+              "employmentType": "FULL_TIME",
+              "url": "https://example.com/jobs/42",
+              "hiringOrganization": {"@type": "Organization", "name": "Ferris Labs"},
+              "jobLocation": {
+                "@type": "Place",
+                "address": {
+                  "@type": "PostalAddress",
+                  "addressLocality": "Berlin",
+                  "addressCountry": "DE"
+                }
+              },
+              "baseSalary": {
+                "@type": "MonetaryAmount",
+                "currency": "eur",
+                "value": {
+                  "@type": "QuantitativeValue",
+                  "minValue": 6000,
+                  "maxValue": "7500",
+                  "unitText": "MONTH"
+                }
+              }
+            }
+            </script>
+            <script type="application/ld+json">{ not json</script>
+            </head><body></body></html>"#;
+
+        let jobs = job_postings(
+            &Html::parse_document(html),
+            Some("https://example.com/list"),
+            now(),
+        );
+        assert_eq!(jobs.len(), 1);
+        let job = &jobs[0];
+        assert_eq!(job.title, "Senior Rust Engineer");
+        assert_eq!(job.company, "Ferris Labs");
+        assert_eq!(job.location, "Berlin, DE");
+        assert_eq!(job.description, "Build fast services. We sponsor visas.");
+        assert_eq!(job.url, "https://example.com/jobs/42");
+        assert_eq!(job.salary_min, Some(72_000));
+        assert_eq!(job.salary_max, Some(90_000));
+        assert_eq!(job.salary_currency.as_deref(), Some("EUR"));
+        assert_eq!(job.salary_period, Some(SalaryPeriod::Monthly));
+        assert!(
+            job.salary_raw
+                .as_ref()
+                .unwrap()
+                .contains(r#""minValue":6000"#)
+        );
+        assert_eq!(job.posted_date.as_deref(), Some("2024-05-01T00:00:00Z"));
+        assert_eq!(job.deadline.as_deref(), Some("2024-06-30T23:59:59+02:00"));
+        assert_eq!(job.contract_type, Some(ContractType::FullTime));
+        assert_eq!(job.visa_sponsorship, Some(true));
+        assert_eq!(job.countries, ["DE"]);
+    }
+
+    #[test]
+    fn test_nested_and_remote_postings() {
+        let html = r#"<script type="application/ld+json">
+            {"@graph": [
+              {"@type": "ItemList", "itemListElement": [
+                {"@type": "ListItem", "item": {
+                  "@type": "JobPosting",
+                  "title": "Go Developer",
+                  "hiringOrganization": "Gopher Inc",
+                  "jobLocationType": "TELECOMMUTE",
+                  "applicantLocationRequirements": {"@type": "Country", "name": "USA"},
+                  "baseSalary": {"currency": "USD", "value": {"value": 40, "unitText": "HOUR"}}
+                }}
+              ]},
+              {"@type": "JobPosting", "description": "No title, so skipped"}
+            ]}
+            </script>"#;
+
+        let jobs = job_postings(
+            &Html::parse_document(html),
+            Some("https://example.com/go"),
+            now(),
+        );
+        assert_eq!(jobs.len(), 1);
+        let job = &jobs[0];
+        assert_eq!(job.company, "Gopher Inc");
+        assert_eq!(job.location, "Remote - USA");
+        assert_eq!(job.url, "https://example.com/go");
+        // Hourly pay can't be annualized reliably
+        assert_eq!(job.salary_min, None);
+        assert_eq!(job.salary_currency, None);
+    }
+}
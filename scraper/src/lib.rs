@@ -0,0 +1,1752 @@
+//! WeWorkRemotely Job Scraper
+//!
+//! Scrapes backend programming jobs from WeWorkRemotely.com
+//! and saves them to data/jobs.json
+//!
+//! The `scraper` binary only parses its flags and calls `run`. The pieces
+//! are public so other crates can scrape too: `scrape_board` walks a board's
+//! listing pages through any `Fetcher`, and `push_page` sends each page to a
+//! running server with a `push::BatchPusher`.
+
+mod categories;
+pub mod company;
+pub mod config;
+mod dates;
+mod jsonld;
+pub mod metrics;
+pub mod push;
+pub mod quality;
+mod robots;
+mod seen;
+mod tags;
+
+use categories::expand_categories;
+use clap::{Parser, ValueEnum};
+use common::{
+    DedupStrategy, Error, Job, MSGPACK_EXTENSION, SalaryPeriod, TimezoneRange, classify_contract,
+    countries_from_region, detect_visa_sponsorship, format_timestamp, fresh_msgpack_copy,
+    jobs_to_msgpack, read_jobs_file,
+};
+use company::MissingCompany;
+use config::{Board, ClosedListings, ScraperConfig, StructuredData, select_boards};
+use dates::{find_deadline, parse_relative_date};
+use metrics::{SelectorField, SelectorHits, SelectorReport};
+use push::{BatchPusher, HttpSink};
+use quality::{QualityThresholds, check_quality};
+use regex::Regex;
+use robots::{PoliteFetcher, USER_AGENT};
+use scraper::Html;
+use seen::SeenStore;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+
+/// Finds the salary-sized numbers in a raw salary string, in order
+fn salary_numbers(salary_raw: &str) -> Vec<i64> {
+    // Match numbers that may have commas (e.g., "50,000" or "50000")
+    static NUMBER: OnceLock<Regex> = OnceLock::new();
+    let re = NUMBER.get_or_init(|| {
+        Regex::new(r"\$?(\d{1,3}(?:,\d{3})+|\d+)").expect("salary number pattern is valid")
+    });
+
+    re.captures_iter(salary_raw)
+        .filter_map(|cap| cap.get(1))
+        .filter_map(|matched| {
+            // Remove commas and parse as i64
+            let clean_number: String = matched
+                .as_str()
+                .chars()
+                .filter(|c| c.is_ascii_digit())
+                .collect();
+            clean_number.parse::<i64>().ok()
+        })
+        // Only consider reasonable salary values (at least 1000)
+        .filter(|num| *num >= 1000)
+        .collect()
+}
+
+/// Extracts the minimum salary from a raw salary string using regex.
+/// Looks for numbers and returns the first one found (likely the minimum).
+fn extract_salary(salary_raw: &str) -> Option<i64> {
+    salary_numbers(salary_raw).first().copied()
+}
+
+/// Extracts the top of a salary range such as "$50,000 - $70,000": the
+/// number after the minimum, when it is larger
+fn extract_salary_max(salary_raw: &str) -> Option<i64> {
+    let numbers = salary_numbers(salary_raw);
+    match numbers.as_slice() {
+        [min, max, ..] if max > min => Some(*max),
+        _ => None,
+    }
+}
+
+/// ISO 4217 codes recognised next to a salary figure
+const CURRENCY_CODES: &[&str] = &[
+    "USD", "EUR", "GBP", "CAD", "AUD", "NZD", "CHF", "SEK", "NOK", "DKK", "PLN", "JPY", "INR",
+    "SGD", "BRL", "MXN",
+];
+
+/// Currency symbols and the code each stands for; longer prefixes come first
+/// so "CA$" isn't read as a plain dollar sign
+const CURRENCY_SYMBOLS: &[(&str, &str)] = &[
+    ("CA$", "CAD"),
+    ("C$", "CAD"),
+    ("AU$", "AUD"),
+    ("A$", "AUD"),
+    ("$", "USD"),
+    ("€", "EUR"),
+    ("£", "GBP"),
+];
+
+/// Detects the currency of a salary string, from a symbol ("$90,000") or an
+/// ISO code written right before or after a number ("USD 90,000", "90000 EUR").
+/// Uppercase words that aren't next to a number, like "EURO trip", don't count.
+fn detect_currency(salary_raw: &str) -> Option<&'static str> {
+    static CODE: OnceLock<Regex> = OnceLock::new();
+    let re = CODE.get_or_init(|| {
+        let codes = CURRENCY_CODES.join("|");
+        Regex::new(&format!(
+            r"\b({codes})\s*\d|\d[\d,.]*\s*[kK]?\s*({codes})\b",
+            codes = codes
+        ))
+        .expect("currency code pattern is valid")
+    });
+    if let Some(caps) = re.captures(salary_raw) {
+        let code = caps.get(1).or_else(|| caps.get(2))?.as_str();
+        return CURRENCY_CODES.iter().copied().find(|known| *known == code);
+    }
+
+    // A symbol counts when it is attached to a number
+    CURRENCY_SYMBOLS.iter().find_map(|(symbol, code)| {
+        salary_raw.match_indices(symbol).find_map(|(start, _)| {
+            let after = salary_raw[start + symbol.len()..].trim_start();
+            after
+                .starts_with(|c: char| c.is_ascii_digit())
+                .then_some(*code)
+        })
+    })
+}
+
+/// Finds the salary as written in listing text: the first salary-sized
+/// figure with its currency, the top of its range and its pay period when
+/// they follow ("$8,000 - $9,000/month", "EUR 60,000 per year")
+fn salary_text(text: &str) -> Option<String> {
+    static SALARY: OnceLock<Regex> = OnceLock::new();
+    let re = SALARY.get_or_init(|| {
+        let codes = CURRENCY_CODES.join("|");
+        let figure = format!(
+            r"(?:[A-Z]{{1,2}}\$|[$€£]|\b(?:{codes})\s*)?(?:\d{{1,3}}(?:,\d{{3}})+|\d{{4,}})(?:\s*[kK]\b)?(?:\s*(?:{codes})\b)?",
+            codes = codes
+        );
+        let period = r"(?i:/\s*(?:month|mo|year|yr)|per\s+(?:month|year|annum)|a\s+(?:month|year)|monthly|annually|yearly)\b";
+        Regex::new(&format!(
+            r"{figure}(?:\s*(?:-|–|to)\s*{figure})?(?:\s*{period})?",
+            figure = figure,
+            period = period
+        ))
+        .expect("salary pattern is valid")
+    });
+    re.find(text).map(|found| found.as_str().to_string())
+}
+
+/// Detects the pay period written right after a salary figure ("$8,000/month",
+/// "90k per year", "6000 EUR monthly"). Period words elsewhere, like "12 month
+/// contract" or "Monthly offsites", don't count.
+fn detect_salary_period(salary_raw: &str) -> Option<SalaryPeriod> {
+    static PERIOD: OnceLock<Regex> = OnceLock::new();
+    let re = PERIOD.get_or_init(|| {
+        Regex::new(
+            r"\d[\d,]*(?:\.\d+)?\s*[kK]?\s*(?:[A-Z]{3}\s*)?(?i:(/\s*(?:month|mo)|per\s+month|a\s+month|monthly)|(/\s*(?:year|yr)|per\s+(?:year|annum)|a\s+year|annually|yearly))\b",
+        )
+        .expect("salary period pattern is valid")
+    });
+    let caps = re.captures(salary_raw)?;
+    if caps.get(1).is_some() {
+        Some(SalaryPeriod::Monthly)
+    } else {
+        Some(SalaryPeriod::Yearly)
+    }
+}
+
+/// Wording boards use on listings that are no longer open, lowercase
+const CLOSED_PHRASES: &[&str] = &[
+    "position filled",
+    "position has been filled",
+    "job has expired",
+    "job expired",
+    "listing expired",
+    "no longer accepting applications",
+];
+
+/// True when listing text says the job is expired or filled
+fn says_closed(text: &str) -> bool {
+    let text = text.to_lowercase();
+    CLOSED_PHRASES.iter().any(|phrase| text.contains(phrase))
+}
+
+/// Files a run saves its jobs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    /// data/jobs.json, readable and diffable
+    Json,
+    /// data/jobs.msgpack, compact and faster for the server to load
+    Msgpack,
+    /// Both files, with the same jobs
+    Both,
+}
+
+/// Command-line options for a scrape run
+#[derive(Debug, Parser)]
+#[command(about = "Scrapes remote programming jobs from WeWorkRemotely")]
+pub struct Args {
+    /// Maximum number of listing pages to follow per category URL
+    #[arg(long, default_value_t = 5)]
+    max_pages: usize,
+
+    /// Fetch each job's detail page to replace the listing blurb with the full description
+    #[arg(long)]
+    full_descriptions: bool,
+
+    /// Maximum number of detail pages fetched at the same time
+    #[arg(long, default_value_t = 4)]
+    max_concurrent_requests: usize,
+
+    /// Pause after each detail fetch, per worker, in milliseconds
+    #[arg(long, default_value_t = 500)]
+    request_delay_ms: u64,
+
+    /// Minimum gap between requests to the same host, in milliseconds,
+    /// when its robots.txt doesn't set a longer Crawl-delay
+    #[arg(long, default_value_t = 250)]
+    crawl_delay_ms: u64,
+
+    /// Idle connections kept open per host for reuse by later requests
+    #[arg(long, default_value_t = 8)]
+    pool_size: usize,
+
+    /// How long an idle pooled connection is kept open, in seconds
+    #[arg(long, default_value_t = 90)]
+    keep_alive_secs: u64,
+
+    /// Forget remembered job URLs not seen for this many days
+    #[arg(long, default_value_t = 30)]
+    seen_max_age_days: u64,
+
+    /// Board config file (TOML); defaults to the built-in boards.toml
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// Scrape only this board, even if disabled in the config (repeatable)
+    #[arg(long, value_name = "BOARD")]
+    only: Vec<String>,
+
+    /// Leave this board out of the run (repeatable)
+    #[arg(long, value_name = "BOARD")]
+    skip: Vec<String>,
+
+    /// Scrape this WeWorkRemotely listing URL instead of the configured
+    /// ones (repeatable)
+    #[arg(long, value_name = "URL")]
+    url: Vec<String>,
+
+    /// WeWorkRemotely categories to scrape instead of the configured URLs,
+    /// e.g. `backend,frontend,devops`; added to any --url
+    #[arg(
+        long,
+        value_name = "SLUGS",
+        value_delimiter = ',',
+        env = "SCRAPER_CATEGORIES"
+    )]
+    categories: Vec<String>,
+
+    /// Minimum share of jobs (0-1) that must have a company name
+    #[arg(long, default_value_t = 0.5)]
+    min_company_ratio: f64,
+
+    /// Minimum share of jobs (0-1) that must have a title and link
+    #[arg(long, default_value_t = 0.8)]
+    min_valid_ratio: f64,
+
+    /// Minimum share of jobs (0-1) that must have a salary (0 disables the check)
+    #[arg(long, default_value_t = 0.0)]
+    min_salary_ratio: f64,
+
+    /// Write jobs.json even when quality checks fail, only warning about them
+    #[arg(long)]
+    allow_low_quality: bool,
+
+    /// What to do with jobs whose company the board doesn't show
+    #[arg(long, value_enum, default_value_t = MissingCompany::Tag)]
+    missing_company: MissingCompany,
+
+    /// Format of the saved jobs; the server reads jobs.msgpack when it is
+    /// at least as new as jobs.json
+    #[arg(long, value_enum, default_value_t = OutputFormat::Json)]
+    output_format: OutputFormat,
+
+    /// Send jobs to the search server at this URL (`POST /jobs`) as each
+    /// listing page is scraped, instead of writing jobs.json
+    #[arg(long, value_name = "SERVER_URL")]
+    push_to: Option<String>,
+
+    /// Jobs per `POST /jobs` request with --push-to
+    #[arg(long, default_value_t = 100)]
+    push_batch_size: usize,
+}
+
+/// Source of raw HTML for a URL. Abstracted so tests can serve fixtures.
+/// `Sync` so detail pages can be fetched from several worker threads.
+pub trait Fetcher: Sync {
+    fn fetch(&self, url: &str) -> Result<String, Error>;
+}
+
+/// Fetches pages over HTTP with reqwest. One client serves every listing
+/// and detail page, so connections (and TLS sessions) to a board are reused.
+pub struct HttpFetcher {
+    client: reqwest::blocking::Client,
+}
+
+impl HttpFetcher {
+    /// Creates a client that identifies itself as the scraper and keeps up to
+    /// `pool_size` idle connections per host open for `keep_alive`
+    pub fn new(pool_size: usize, keep_alive: Duration) -> Result<HttpFetcher, Error> {
+        let client = reqwest::blocking::Client::builder()
+            .user_agent(USER_AGENT)
+            .pool_max_idle_per_host(pool_size)
+            .pool_idle_timeout(keep_alive)
+            .tcp_keepalive(keep_alive)
+            .build()
+            .map_err(|e| Error::Config(format!("cannot create HTTP client: {}", e)))?;
+        Ok(HttpFetcher { client })
+    }
+}
+
+impl Fetcher for HttpFetcher {
+    fn fetch(&self, url: &str) -> Result<String, Error> {
+        let scrape_error = |e: reqwest::Error| Error::Scrape {
+            url: url.to_string(),
+            message: e.to_string(),
+        };
+        let response = self
+            .client
+            .get(url)
+            .send()
+            .and_then(|response| response.error_for_status())
+            .map_err(scrape_error)?;
+        response.text().map_err(scrape_error)
+    }
+}
+
+/// Turns a relative href from the board into an absolute URL
+fn absolute_url(base_url: &str, href: &str) -> String {
+    if href.starts_with("http") {
+        href.to_string()
+    } else {
+        format!("{}{}", base_url, href)
+    }
+}
+
+/// Parses one listing page into jobs, plus the "next page" URL if the page links one
+fn parse_listing_page(
+    html_content: &str,
+    board: &Board,
+    hits: &mut SelectorHits,
+) -> (Vec<Job>, Option<String>) {
+    // Relative dates ("3 days ago") count back from now
+    let now = OffsetDateTime::now_utc();
+    let selectors = &board.selectors;
+    let document = Html::parse_document(html_content);
+    let next_page = document
+        .select(&selectors.next_page)
+        .next()
+        .and_then(|el| el.value().attr("href"))
+        .map(|href| absolute_url(&board.base_url, href));
+    // Listing pages link each posting, so postings without a URL are of no use
+    let structured_jobs = || -> Vec<Job> {
+        jsonld::job_postings(&document, None, now)
+            .into_iter()
+            .map(|mut job| {
+                job.url = absolute_url(&board.base_url, &job.url);
+                job
+            })
+            .collect()
+    };
+    if board.structured_data == StructuredData::Primary {
+        let jobs = structured_jobs();
+        if !jobs.is_empty() {
+            return (jobs, next_page);
+        }
+    }
+    let mut jobs = Vec::new();
+
+    // Iterate through job listings
+    for element in document.select(&selectors.job) {
+        hits.add_listing();
+        let mut text_of = |selector, field| {
+            let text = element
+                .select(selector)
+                .next()
+                .map(|el| el.text().collect::<String>().trim().to_string());
+            hits.record(field, text.is_some());
+            text
+        };
+
+        // Extract title, company name and location/region
+        let title = text_of(&selectors.title, SelectorField::Title)
+            .unwrap_or_else(|| "Unknown Title".to_string());
+        let company = text_of(&selectors.company, SelectorField::Company);
+        let location = text_of(&selectors.region, SelectorField::Region)
+            .unwrap_or_else(|| "Remote".to_string());
+
+        // Extract job URL
+        let job_url = element
+            .select(&selectors.link)
+            .next()
+            .and_then(|el| el.value().attr("href"))
+            .map(|href| absolute_url(&board.base_url, href));
+        hits.record(SelectorField::Link, job_url.is_some());
+        let job_url = job_url.unwrap_or_else(|| "No URL".to_string());
+
+        // Get full text for salary extraction
+        let full_text = element.text().collect::<String>();
+        let salary_period =
+            extract_salary(&full_text).and_then(|_| detect_salary_period(&full_text));
+        // Figures are stored per year, whatever period the listing quotes
+        let annualize = |salary: Option<i64>| match salary_period {
+            Some(SalaryPeriod::Monthly) => salary.map(|salary| salary * 12),
+            _ => salary,
+        };
+        let salary_min = annualize(extract_salary(&full_text));
+        let salary_max = annualize(extract_salary_max(&full_text));
+        let salary_currency = salary_min
+            .and_then(|_| detect_currency(&full_text))
+            .map(str::to_string);
+
+        let mut tags = tags::extract_tags(&element, selectors, &title, &location);
+        let closed = says_closed(&full_text)
+            || selectors.closed.as_ref().is_some_and(|selector| {
+                selector.matches(&element) || element.select(selector).next().is_some()
+            });
+        if closed {
+            if board.closed_listings == ClosedListings::Skip {
+                continue;
+            }
+            if !tags.iter().any(|tag| tag == "closed") {
+                tags.push("closed".to_string());
+            }
+        }
+        let contract_type = classify_contract(&full_text);
+        let posted_date = selectors
+            .posted
+            .as_ref()
+            .and_then(|selector| element.select(selector).next())
+            .map(|el| match el.value().attr("datetime") {
+                Some(datetime) => datetime.to_string(),
+                None => el.text().collect::<String>(),
+            })
+            .and_then(|text| parse_relative_date(&text, now))
+            .and_then(|date| date.format(&Rfc3339).ok());
+        let deadline = find_deadline(&full_text, now).and_then(|date| date.format(&Rfc3339).ok());
+        let featured = selectors.featured.as_ref().is_some_and(|selector| {
+            selector.matches(&element) || element.select(selector).next().is_some()
+        });
+
+        // Create Job struct
+        let timezone = TimezoneRange::from_region(&location);
+        let countries = countries_from_region(&location);
+        let job = Job {
+            title,
+            company_missing: company.is_none(),
+            company: company.unwrap_or_default(),
+            location,
+            description: full_text.trim().replace('\n', " ").replace("  ", " "),
+            salary_min,
+            salary_max,
+            salary_currency,
+            salary_period,
+            salary_raw: salary_min.and_then(|_| salary_text(&full_text)),
+            url: job_url,
+            posted_date,
+            deadline,
+            scraped_at: None,
+            timezone,
+            countries,
+            tags,
+            contract_type,
+            featured,
+            visa_sponsorship: detect_visa_sponsorship(&full_text),
+        };
+
+        // Only keep jobs with a valid title
+        if job.title != "Unknown Title" && !job.title.is_empty() {
+            jobs.push(job);
+        }
+    }
+
+    if jobs.is_empty() && board.structured_data == StructuredData::Fallback {
+        jobs = structured_jobs();
+    }
+
+    (jobs, next_page)
+}
+
+/// Dedup keys of the jobs scraped so far in this run
+#[derive(Debug, Default)]
+pub struct SeenKeys {
+    strategy: DedupStrategy,
+    keys: HashSet<String>,
+}
+
+impl SeenKeys {
+    pub fn new(strategy: DedupStrategy) -> Self {
+        SeenKeys {
+            strategy,
+            keys: HashSet::new(),
+        }
+    }
+
+    /// Records `job`'s key; false when a job with the same key was already seen
+    fn insert(&mut self, job: &Job) -> bool {
+        self.keys.insert(self.strategy.key(job))
+    }
+}
+
+/// Where the jobs of each scraped listing page go, as soon as it is parsed
+pub trait PageSink {
+    fn page(&mut self, jobs: Vec<Job>) -> Result<(), Error>;
+}
+
+/// Collects every page's jobs
+impl PageSink for Vec<Job> {
+    fn page(&mut self, jobs: Vec<Job>) -> Result<(), Error> {
+        self.extend(jobs);
+        Ok(())
+    }
+}
+
+/// Hands each page's jobs to a closure, say one pushing them to the server
+impl<F: FnMut(Vec<Job>) -> Result<(), Error>> PageSink for F {
+    fn page(&mut self, jobs: Vec<Job>) -> Result<(), Error> {
+        self(jobs)
+    }
+}
+
+/// Scrapes a category URL, following "next page" links up to `max_pages`,
+/// and hands each page's new jobs to `jobs` before fetching the next.
+/// Stops early when a page yields no jobs that haven't been seen already.
+/// Returns how often the listing selectors matched across its pages, or the
+/// first error from `jobs`.
+fn scrape_category(
+    fetcher: &dyn Fetcher,
+    board: &Board,
+    url: &str,
+    max_pages: usize,
+    seen: &mut SeenKeys,
+    jobs: &mut dyn PageSink,
+) -> Result<SelectorHits, Error> {
+    let mut page_url = Some(url.to_string());
+    let mut pages_fetched = 0;
+    let mut hits = SelectorHits::default();
+
+    while let Some(current_url) = page_url.take() {
+        if pages_fetched >= max_pages {
+            println!("⏹️  Reached page limit ({}) for {}", max_pages, url);
+            break;
+        }
+        pages_fetched += 1;
+
+        println!("📡 Fetching jobs from: {}", current_url);
+
+        let html_content = match fetcher.fetch(&current_url) {
+            Ok(text) => text,
+            Err(e) => {
+                eprintln!("❌ {}", e);
+                break; // Skip to next URL instead of stopping
+            }
+        };
+
+        println!(
+            "✅ Fetched {} bytes from {}",
+            html_content.len(),
+            current_url
+        );
+
+        let (page_jobs, next_page) = parse_listing_page(&html_content, board, &mut hits);
+
+        let mut new_jobs = Vec::new();
+        for job in page_jobs {
+            // Skip duplicates (same job may appear on multiple category pages)
+            if !seen.insert(&job) {
+                continue;
+            }
+            println!("📋 Found: {} at {}", job.title, job.company);
+            new_jobs.push(job);
+        }
+
+        if new_jobs.is_empty() {
+            println!("⏹️  No new jobs on {}, stopping pagination", current_url);
+            break;
+        }
+        jobs.page(new_jobs)?;
+
+        page_url = next_page;
+    }
+    Ok(hits)
+}
+
+/// Scrapes every category URL of a board, handing each page's jobs to
+/// `jobs` and adding each URL's selector hit rates to `report`
+pub fn scrape_board(
+    fetcher: &dyn Fetcher,
+    board: &Board,
+    max_pages: usize,
+    seen: &mut SeenKeys,
+    report: &mut SelectorReport,
+    jobs: &mut dyn PageSink,
+) -> Result<(), Error> {
+    for url in &board.urls {
+        let hits = scrape_category(fetcher, board, url, max_pages, seen, jobs)?;
+        report.add(url, hits);
+
+        println!(); // Blank line between URL fetches
+    }
+    Ok(())
+}
+
+/// Extracts the full job description from a detail page, if the page has one,
+/// from the description selector or the page's `JobPosting` JSON-LD as the
+/// board's `structured_data` setting says
+fn parse_detail_description(html_content: &str, url: &str, board: &Board) -> Option<String> {
+    let document = Html::parse_document(html_content);
+    let structured = || {
+        jsonld::job_postings(&document, Some(url), OffsetDateTime::now_utc())
+            .into_iter()
+            .map(|job| job.description)
+            .find(|description| !description.is_empty())
+    };
+    let selected = || {
+        document
+            .select(&board.selectors.description)
+            .next()
+            .map(|el| {
+                el.text()
+                    .map(str::trim)
+                    .filter(|t| !t.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .filter(|text| !text.is_empty())
+    };
+    match board.structured_data {
+        StructuredData::Primary => structured().or_else(selected),
+        StructuredData::Fallback => selected().or_else(structured),
+        StructuredData::Off => selected(),
+    }
+}
+
+/// Replaces each job's listing blurb with the description from its detail page.
+/// At most `max_concurrent` detail pages are in flight at once: a fixed pool of
+/// workers pulls jobs off a shared counter, and each worker waits `delay` after
+/// every request so the site isn't hammered.
+fn fetch_full_descriptions(
+    fetcher: &dyn Fetcher,
+    jobs: &mut [Job],
+    board: &Board,
+    max_concurrent: usize,
+    delay: Duration,
+) {
+    let next_job = AtomicUsize::new(0);
+    let urls: Vec<&str> = jobs.iter().map(|job| job.url.as_str()).collect();
+    let workers = max_concurrent.max(1).min(urls.len());
+
+    let descriptions: Vec<(usize, String)> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut found = Vec::new();
+                    loop {
+                        let i = next_job.fetch_add(1, Ordering::SeqCst);
+                        let Some(url) = urls.get(i) else { break };
+
+                        match fetcher.fetch(url) {
+                            Ok(html) => {
+                                if let Some(text) = parse_detail_description(&html, url, board) {
+                                    found.push((i, text));
+                                }
+                            }
+                            Err(e) => eprintln!("❌ Failed to fetch details: {}", e),
+                        }
+
+                        thread::sleep(delay);
+                    }
+                    found
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("Detail worker panicked"))
+            .collect()
+    });
+
+    for (i, description) in descriptions {
+        // The full text is where sponsorship is usually spelled out
+        if let Some(sponsors) = detect_visa_sponsorship(&description) {
+            jobs[i].visa_sponsorship = Some(sponsors);
+        }
+        jobs[i].description = description;
+    }
+}
+
+/// Current time as unix seconds
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Gives each job its detail page's description. Jobs already scraped in an
+/// earlier run keep their saved description instead of being fetched again.
+fn add_full_descriptions(
+    fetcher: &dyn Fetcher,
+    board: &Board,
+    previous: &HashMap<String, Job>,
+    new_urls: &HashSet<String>,
+    jobs: &mut [Job],
+    args: &Args,
+) {
+    let mut pending = Vec::new();
+    for (i, job) in jobs.iter_mut().enumerate() {
+        match previous.get(&job.url) {
+            Some(old) if !new_urls.contains(&job.url) => job.description = old.description.clone(),
+            _ => pending.push(i),
+        }
+    }
+
+    println!(
+        "📄 Fetching full descriptions for {} jobs ({} at a time)...",
+        pending.len(),
+        args.max_concurrent_requests
+    );
+    let mut pending_jobs: Vec<Job> = pending.iter().map(|&i| jobs[i].clone()).collect();
+    fetch_full_descriptions(
+        fetcher,
+        &mut pending_jobs,
+        board,
+        args.max_concurrent_requests,
+        Duration::from_millis(args.request_delay_ms),
+    );
+    for (i, job) in pending.into_iter().zip(pending_jobs) {
+        jobs[i] = job;
+    }
+}
+
+/// Records every scraped job in the seen store and returns the URLs that are new this run.
+/// Each job's `scraped_at` is set to when it was first seen.
+fn record_seen(store: &mut SeenStore, jobs: &mut [Job], now: u64) -> HashSet<String> {
+    let mut new_urls = HashSet::new();
+    for job in jobs.iter_mut() {
+        if store.record(&job.url, now) {
+            new_urls.insert(job.url.clone());
+        }
+        job.scraped_at = store
+            .first_seen(&job.url)
+            .map(|secs| format_timestamp(secs as i64));
+    }
+    new_urls
+}
+
+/// Loads the jobs written by the previous run, keyed by URL, from its
+/// MessagePack file when that is the newer one
+fn load_previous_jobs(path: &Path) -> HashMap<String, Job> {
+    let path = fresh_msgpack_copy(path).unwrap_or_else(|| path.to_path_buf());
+    read_jobs_file(&path)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|job| (job.url.clone(), job))
+        .collect()
+}
+
+/// Points the WeWorkRemotely board at `urls` and the listing pages of the
+/// `categories` slugs instead of its configured URLs, when either is given.
+/// Unknown slugs are reported and skipped.
+fn override_board_urls(
+    boards: &mut [Board],
+    urls: &[String],
+    categories: &[String],
+) -> Result<(), Error> {
+    if urls.is_empty() && categories.is_empty() {
+        return Ok(());
+    }
+    let Some(board) = boards
+        .iter_mut()
+        .find(|board| board.name == categories::BOARD)
+    else {
+        return Err(Error::Config(format!(
+            "--url and --categories need the `{}` board in this run",
+            categories::BOARD
+        )));
+    };
+    let (expanded, unknown) = expand_categories(&board.base_url, urls, categories);
+    for slug in &unknown {
+        eprintln!(
+            "⚠️  Unknown category {:?}, skipping it (known: {})",
+            slug,
+            categories::known_slugs()
+        );
+    }
+    if expanded.is_empty() {
+        return Err(Error::Config(
+            "no known categories or URLs to scrape".to_string(),
+        ));
+    }
+    println!(
+        "🗂️  Scraping {} listing URLs from {}",
+        expanded.len(),
+        board.name
+    );
+    board.urls = expanded;
+    Ok(())
+}
+
+/// Scrapes every configured board and writes data/jobs.json
+pub fn run(args: &Args) -> Result<(), Error> {
+    // Load boards and compile their selectors once (reused for all pages)
+    let config = ScraperConfig::load(args.config.as_deref())?;
+    let dedup = config.dedup;
+    let boards = config.into_boards()?;
+    let (mut boards, skipped) = select_boards(boards, &args.only, &args.skip)?;
+    for (name, reason) in &skipped {
+        println!("⏭️  Skipping board {} ({})", name, reason);
+    }
+    override_board_urls(&mut boards, &args.url, &args.categories)?;
+    let http = HttpFetcher::new(args.pool_size, Duration::from_secs(args.keep_alive_secs))?;
+    let fetcher = PoliteFetcher::new(&http, Duration::from_millis(args.crawl_delay_ms));
+
+    // Create data directory if it doesn't exist
+    let data_dir = Path::new("data");
+    if !data_dir.exists() {
+        fs::create_dir_all(data_dir).map_err(|e| Error::io(data_dir, e))?;
+        println!("📁 Created 'data' directory");
+    }
+    let output_path = data_dir.join("jobs.json");
+
+    // Compare against URLs remembered from earlier runs
+    let now = unix_now();
+    let seen_path = data_dir.join("seen_jobs.json");
+    let mut seen_store = SeenStore::load(&seen_path, args.seen_max_age_days * 86_400, now);
+    let previous = load_previous_jobs(&output_path);
+
+    // Refuse to replace the previous output with obviously broken data
+    let thresholds = QualityThresholds {
+        min_company_ratio: args.min_company_ratio,
+        min_valid_ratio: args.min_valid_ratio,
+        min_salary_ratio: args.min_salary_ratio,
+    };
+    let mut sink = args
+        .push_to
+        .as_deref()
+        .map(|server_url| HttpSink::new(&http, server_url));
+    let mut pusher = sink
+        .as_mut()
+        .map(|sink| BatchPusher::new(sink, args.push_batch_size));
+    let mut rejected_boards = Vec::new();
+
+    let mut jobs: Vec<Job> = Vec::new();
+    let mut total_jobs = 0;
+    let mut seen = SeenKeys::new(dedup);
+    let mut selector_report = SelectorReport::default();
+    let mut new_job_count = 0;
+
+    for board in &boards {
+        println!("🧭 Scraping board: {}", board.name);
+        let mut rejected_pages = 0;
+        let mut handle_page = |mut page_jobs: Vec<Job>| -> Result<(), Error> {
+            let new_urls = record_seen(&mut seen_store, &mut page_jobs, now);
+            new_job_count += new_urls.len();
+            if args.full_descriptions {
+                add_full_descriptions(&fetcher, board, &previous, &new_urls, &mut page_jobs, args);
+            }
+            total_jobs += page_jobs.len();
+            match pusher.as_mut() {
+                Some(pusher) => {
+                    if !push_page(
+                        pusher,
+                        board,
+                        page_jobs,
+                        &thresholds,
+                        args.allow_low_quality,
+                        args.missing_company,
+                    )? {
+                        rejected_pages += 1;
+                    }
+                }
+                None => jobs.extend(page_jobs),
+            }
+            Ok(())
+        };
+        scrape_board(
+            &fetcher,
+            board,
+            args.max_pages,
+            &mut seen,
+            &mut selector_report,
+            &mut handle_page,
+        )?;
+        if rejected_pages > 0 {
+            rejected_boards.push(format!("{} ({} pages)", board.name, rejected_pages));
+        }
+    }
+
+    println!("📊 Total unique jobs found: {}", total_jobs);
+    println!("🆕 {} new jobs this run", new_job_count);
+    // A selector matching almost nothing means the board's markup changed
+    println!("🎯 Selector hit rates:");
+    for line in selector_report.lines() {
+        println!("   {}", line);
+    }
+
+    if let Some(mut pusher) = pusher {
+        pusher.flush()?;
+        println!(
+            "📤 Pushed {} jobs to {}",
+            pusher.pushed,
+            args.push_to.as_deref().unwrap_or_default()
+        );
+        if !rejected_boards.is_empty() {
+            return Err(Error::Quality(format!(
+                "jobs from {} were not pushed",
+                rejected_boards.join(", ")
+            )));
+        }
+    } else {
+        save_jobs(jobs, &output_path, &thresholds, args)?;
+    }
+
+    seen_store.save(&seen_path)?;
+    println!(
+        "🧠 Remembering {} job URLs in {:?}",
+        seen_store.len(),
+        seen_path
+    );
+    println!("\n✨ Scraping complete!");
+    Ok(())
+}
+
+/// Sends one listing page's jobs to the server as soon as the page is
+/// scraped, so they are searchable while the rest of the board is still
+/// being fetched. They go live straight away, so each page is checked on its
+/// own; returns false when its jobs failed the checks and weren't sent.
+pub fn push_page(
+    pusher: &mut BatchPusher,
+    board: &Board,
+    jobs: Vec<Job>,
+    thresholds: &QualityThresholds,
+    allow_low_quality: bool,
+    missing_company: MissingCompany,
+) -> Result<bool, Error> {
+    let failures = check_quality(&jobs, thresholds);
+    for failure in &failures {
+        eprintln!("⚠️  Quality check failed for {}: {}", board.name, failure);
+    }
+    if !failures.is_empty() && !allow_low_quality {
+        println!("🛑 Not pushing a page of jobs from {}", board.name);
+        return Ok(false);
+    }
+    pusher.push(missing_company.apply(jobs))?;
+    pusher.flush()?;
+    Ok(true)
+}
+
+/// Writes the run's jobs to `output_path` (and/or its MessagePack copy),
+/// unless they fail the quality checks
+fn save_jobs(
+    jobs: Vec<Job>,
+    output_path: &Path,
+    thresholds: &QualityThresholds,
+    args: &Args,
+) -> Result<(), Error> {
+    let failures = check_quality(&jobs, thresholds);
+    if !failures.is_empty() {
+        for failure in &failures {
+            eprintln!("⚠️  Quality check failed: {}", failure);
+        }
+        if !args.allow_low_quality {
+            println!("🛑 Keeping the previous {:?}", output_path);
+            return Err(Error::Quality(failures.join("; ")));
+        }
+    }
+    let jobs = &args.missing_company.apply(jobs);
+
+    // Save to JSON and/or MessagePack; JSON first, so a copy written in the
+    // same run is never older than it
+    if args.output_format != OutputFormat::Msgpack {
+        let json_output = serde_json::to_string_pretty(jobs)?;
+        fs::write(output_path, &json_output).map_err(|e| Error::io(output_path, e))?;
+        println!("💾 Saved {} jobs to {:?}", jobs.len(), output_path);
+    }
+    if args.output_format != OutputFormat::Json {
+        let msgpack_path = output_path.with_extension(MSGPACK_EXTENSION);
+        fs::write(&msgpack_path, jobs_to_msgpack(jobs)?)
+            .map_err(|e| Error::io(&msgpack_path, e))?;
+        println!("💾 Saved {} jobs to {:?}", jobs.len(), msgpack_path);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    /// The built-in WeWorkRemotely board
+    fn test_board() -> Board {
+        ScraperConfig::load(None)
+            .unwrap()
+            .into_boards()
+            .unwrap()
+            .remove(0)
+    }
+
+    #[test]
+    fn test_extract_salary_with_dollar_sign() {
+        assert_eq!(extract_salary("$50,000 - $70,000"), Some(50000));
+    }
+
+    #[test]
+    fn test_extract_salary_without_dollar_sign() {
+        assert_eq!(extract_salary("Salary: 60000 USD"), Some(60000));
+    }
+
+    #[test]
+    fn test_extract_salary_no_salary() {
+        assert_eq!(extract_salary("Competitive salary"), None);
+    }
+
+    #[test]
+    fn test_extract_salary_max_from_range() {
+        assert_eq!(extract_salary_max("$50,000 - $70,000"), Some(70000));
+        assert_eq!(extract_salary_max("$120,000/year"), None);
+    }
+
+    #[test]
+    fn test_detect_salary_period() {
+        assert_eq!(
+            detect_salary_period("$8,000/month"),
+            Some(SalaryPeriod::Monthly)
+        );
+        assert_eq!(
+            detect_salary_period("6,500 EUR per month"),
+            Some(SalaryPeriod::Monthly)
+        );
+        assert_eq!(
+            detect_salary_period("$7k / mo"),
+            Some(SalaryPeriod::Monthly)
+        );
+        assert_eq!(
+            detect_salary_period("$120,000/year"),
+            Some(SalaryPeriod::Yearly)
+        );
+        // "month" away from the figure is not a pay period
+        assert_eq!(
+            detect_salary_period("12 month contract. Salary $90,000. Monthly team offsites"),
+            None
+        );
+        assert_eq!(detect_salary_period("$90,000"), None);
+    }
+
+    #[test]
+    fn test_parse_listing_page_skips_or_tags_closed_listings() {
+        let html = r#"<html><body><ul>
+            <li class="new-listing-container">
+                <a class="listing-link--unlocked" href="/remote-jobs/1"></a>
+                <h3 class="new-listing__header__title">Rust Developer</h3>
+            </li>
+            <li class="new-listing-container">
+                <a class="listing-link--unlocked" href="/remote-jobs/2"></a>
+                <h3 class="new-listing__header__title">Go Developer</h3>
+                <span>Position Filled</span>
+            </li>
+            <li class="new-listing-container">
+                <a class="listing-link--unlocked" href="/remote-jobs/3"></a>
+                <h3 class="new-listing__header__title">Python Developer</h3>
+                <span class="expired-badge"></span>
+            </li>
+        </ul></body></html>"#;
+        let mut board = test_board();
+        board.selectors.closed = Some(scraper::Selector::parse(".expired-badge").unwrap());
+
+        let (jobs, _) = parse_listing_page(html, &board, &mut SelectorHits::default());
+        let titles: Vec<&str> = jobs.iter().map(|job| job.title.as_str()).collect();
+        assert_eq!(titles, ["Rust Developer"]);
+
+        board.closed_listings = ClosedListings::Tag;
+        let (jobs, _) = parse_listing_page(html, &board, &mut SelectorHits::default());
+        assert_eq!(jobs.len(), 3);
+        assert!(!jobs[0].tags.contains(&"closed".to_string()));
+        assert!(jobs[1].tags.contains(&"closed".to_string()));
+        assert!(jobs[2].tags.contains(&"closed".to_string()));
+    }
+
+    #[test]
+    fn test_parse_listing_page_annualizes_monthly_salaries() {
+        let listing = |id: u32, title: &str| {
+            format!(
+                r#"<li class="new-listing-container">
+                    <a class="listing-link--unlocked" href="/remote-jobs/{id}"></a>
+                    <h3 class="new-listing__header__title">{title}</h3>
+                </li>"#
+            )
+        };
+        let html = format!(
+            "<html><body><ul>{}{}</ul></body></html>",
+            listing(1, "Rust Developer $8,000 - $9,000/month"),
+            listing(2, "Go Developer, 6 month contract, $90,000"),
+        );
+
+        let (jobs, _) = parse_listing_page(&html, &test_board(), &mut SelectorHits::default());
+
+        assert_eq!(jobs[0].salary_min, Some(96_000));
+        assert_eq!(jobs[0].salary_max, Some(108_000));
+        assert_eq!(jobs[0].salary_period, Some(SalaryPeriod::Monthly));
+        assert_eq!(jobs[0].salary_raw.as_deref(), Some("$8,000 - $9,000/month"));
+        assert_eq!(jobs[1].salary_min, Some(90_000));
+        assert_eq!(jobs[1].salary_period, None);
+        assert_eq!(jobs[1].salary_raw.as_deref(), Some("$90,000"));
+    }
+
+    #[test]
+    fn test_salary_text_keeps_the_figures_as_written() {
+        assert_eq!(
+            salary_text("Backend Engineer EUR 60,000 per year, 6 month trial").as_deref(),
+            Some("EUR 60,000 per year")
+        );
+        assert_eq!(
+            salary_text("Team of 12. CA$90,000 to CA$110,000").as_deref(),
+            Some("CA$90,000 to CA$110,000")
+        );
+        assert_eq!(salary_text("12 month contract"), None);
+    }
+
+    #[test]
+    fn test_detect_currency_from_codes_and_symbols() {
+        assert_eq!(detect_currency("USD 90,000"), Some("USD"));
+        assert_eq!(detect_currency("90000 EUR"), Some("EUR"));
+        assert_eq!(detect_currency("€60,000 - €80,000"), Some("EUR"));
+        assert_eq!(detect_currency("CA$100,000"), Some("CAD"));
+        assert_eq!(detect_currency("$50,000"), Some("USD"));
+        // Not a currency code, and not next to a number
+        assert_eq!(detect_currency("EURO trip"), None);
+        assert_eq!(detect_currency("USD accounts team"), None);
+    }
+
+    #[test]
+    fn test_extract_salary_with_k_notation() {
+        // This would need enhancement to handle "50k" notation
+        assert_eq!(extract_salary("$120,000/year"), Some(120000));
+    }
+
+    /// Serves canned HTML keyed by URL
+    struct MockFetcher {
+        pages: std::collections::HashMap<String, String>,
+    }
+
+    impl Fetcher for MockFetcher {
+        fn fetch(&self, url: &str) -> Result<String, Error> {
+            self.pages.get(url).cloned().ok_or_else(|| Error::Scrape {
+                url: url.to_string(),
+                message: "no fixture".to_string(),
+            })
+        }
+    }
+
+    fn listing_html(jobs: &[(&str, &str)], next_href: Option<&str>) -> String {
+        let items: String = jobs
+            .iter()
+            .map(|(title, href)| {
+                format!(
+                    r#"<li class="new-listing-container">
+                        <a class="listing-link--unlocked" href="{}"></a>
+                        <h3 class="new-listing__header__title">{}</h3>
+                        <p class="new-listing__company-name">Acme</p>
+                    </li>"#,
+                    href, title
+                )
+            })
+            .collect();
+        let next = next_href
+            .map(|href| format!(r#"<a rel="next" href="{}">Next</a>"#, href))
+            .unwrap_or_default();
+        format!("<html><body><ul>{}</ul>{}</body></html>", items, next)
+    }
+
+    /// Scrapes a two-page category with `max_pages`: page 1 lists a Rust job
+    /// and links page 2, which lists `page_two` and links `page_two_next`
+    fn scrape_two_pages(
+        page_two: &[(&str, &str)],
+        page_two_next: Option<&str>,
+        max_pages: usize,
+    ) -> Vec<Job> {
+        let mut pages = std::collections::HashMap::new();
+        pages.insert(
+            "https://weworkremotely.com/jobs".to_string(),
+            listing_html(
+                &[("Rust Developer", "/remote-jobs/1")],
+                Some("/jobs?page=2"),
+            ),
+        );
+        pages.insert(
+            "https://weworkremotely.com/jobs?page=2".to_string(),
+            listing_html(page_two, page_two_next),
+        );
+        let fetcher = MockFetcher { pages };
+
+        let mut jobs = Vec::new();
+        scrape_category(
+            &fetcher,
+            &test_board(),
+            "https://weworkremotely.com/jobs",
+            max_pages,
+            &mut SeenKeys::default(),
+            &mut jobs,
+        )
+        .unwrap();
+        jobs
+    }
+
+    #[test]
+    fn test_scrape_category_follows_next_page() {
+        let jobs = scrape_two_pages(&[("Go Developer", "/remote-jobs/2")], None, 5);
+
+        let titles: Vec<&str> = jobs.iter().map(|j| j.title.as_str()).collect();
+        assert_eq!(titles, vec!["Rust Developer", "Go Developer"]);
+        assert_eq!(jobs[1].url, "https://weworkremotely.com/remote-jobs/2");
+    }
+
+    #[test]
+    fn test_selector_hit_rates_per_url() {
+        // Page 1: a full listing and one missing its company, region and
+        // link; page 2: a listing with only a title
+        let mut pages = std::collections::HashMap::new();
+        pages.insert(
+            "https://weworkremotely.com/jobs".to_string(),
+            r#"<html><body><ul>
+                <li class="new-listing-container">
+                    <a class="listing-link--unlocked" href="/remote-jobs/1"></a>
+                    <h3 class="new-listing__header__title">Rust Developer</h3>
+                    <p class="new-listing__company-name">Acme</p>
+                    <p class="new-listing__company-headquarters">Anywhere in the World</p>
+                </li>
+                <li class="new-listing-container">
+                    <h3 class="new-listing__header__title">Go Developer</h3>
+                </li>
+            </ul><a rel="next" href="/jobs?page=2">Next</a></body></html>"#
+                .to_string(),
+        );
+        pages.insert(
+            "https://weworkremotely.com/jobs?page=2".to_string(),
+            r#"<html><body><ul>
+                <li class="new-listing-container">
+                    <h3 class="new-listing__header__title">Designer</h3>
+                </li>
+            </ul></body></html>"#
+                .to_string(),
+        );
+        let fetcher = MockFetcher { pages };
+        let mut jobs = Vec::new();
+        let hits = scrape_category(
+            &fetcher,
+            &test_board(),
+            "https://weworkremotely.com/jobs",
+            5,
+            &mut SeenKeys::new(DedupStrategy::Composite),
+            &mut jobs,
+        )
+        .unwrap();
+
+        assert_eq!(hits.listings, 3);
+        let rate = |field| hits.rate(field).unwrap().round();
+        assert_eq!(rate(SelectorField::Title), 100.0);
+        assert_eq!(rate(SelectorField::Company), 33.0);
+        assert_eq!(rate(SelectorField::Region), 33.0);
+        assert_eq!(rate(SelectorField::Link), 33.0);
+
+        let mut report = SelectorReport::default();
+        report.add("https://weworkremotely.com/jobs", hits);
+        report.add("https://weworkremotely.com/empty", SelectorHits::default());
+        assert_eq!(
+            report.lines(),
+            [
+                "https://weworkremotely.com/jobs: title 100%, company 33%, region 33%, link 33% (3 listings)",
+                "https://weworkremotely.com/empty: title -, company -, region -, link - (0 listings)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scrape_category_dedups_by_strategy() {
+        let mut pages = std::collections::HashMap::new();
+        pages.insert(
+            "https://weworkremotely.com/jobs".to_string(),
+            listing_html(
+                &[
+                    ("Rust Developer", "/remote-jobs/1"),
+                    ("Rust Developer", "/remote-jobs/1?ref=feed"),
+                    ("Rust Developer", "/remote-jobs/7"),
+                ],
+                None,
+            ),
+        );
+        let fetcher = MockFetcher { pages };
+        let scraped = |strategy: DedupStrategy| {
+            let mut jobs = Vec::new();
+            scrape_category(
+                &fetcher,
+                &test_board(),
+                "https://weworkremotely.com/jobs",
+                5,
+                &mut SeenKeys::new(strategy),
+                &mut jobs,
+            )
+            .unwrap();
+            jobs.len()
+        };
+
+        assert_eq!(scraped(DedupStrategy::Url), 3);
+        assert_eq!(scraped(DedupStrategy::Id), 2);
+        assert_eq!(scraped(DedupStrategy::TitleCompany), 1);
+    }
+
+    #[test]
+    fn test_parse_listing_page_reads_tag_markup() {
+        let html = r#"<html><body><ul>
+            <li class="feature new-listing-container">
+                <a class="listing-link--unlocked" href="/remote-jobs/1"></a>
+                <h3 class="new-listing__header__title">Senior Rust Developer</h3>
+                <p class="new-listing__company-name">Acme</p>
+                <div class="new-listing__categories">
+                    <p class="new-listing__categories__category">Full-Time</p>
+                    <p class="new-listing__categories__category"> Anywhere in the World </p>
+                </div>
+            </li>
+            <li class="new-listing-container">
+                <a class="listing-link--unlocked" href="/remote-jobs/2"></a>
+                <h3 class="new-listing__header__title">Senior Go Developer (Contract)</h3>
+                <p class="new-listing__company-name">Globex</p>
+            </li>
+        </ul></body></html>"#;
+
+        let (jobs, _) = parse_listing_page(html, &test_board(), &mut SelectorHits::default());
+
+        assert_eq!(jobs[0].tags, vec!["full-time", "anywhere in the world"]);
+        // No tag markup: keywords from the title instead
+        assert_eq!(jobs[1].tags, vec!["contract", "senior"]);
+        assert_eq!(jobs[0].contract_type, Some(common::ContractType::FullTime));
+        assert_eq!(jobs[1].contract_type, Some(common::ContractType::Contract));
+        // Only the first listing is in a featured slot
+        assert!(jobs[0].featured);
+        assert!(!jobs[1].featured);
+    }
+
+    #[test]
+    fn test_parse_listing_page_resolves_posted_dates() {
+        let html = r#"<html><body><ul>
+            <li class="new-listing-container">
+                <a class="listing-link--unlocked" href="/remote-jobs/1"></a>
+                <h3 class="new-listing__header__title">Rust Developer</h3>
+                <time datetime="2024-05-01T12:00:00Z">9d</time>
+            </li>
+            <li class="new-listing-container">
+                <a class="listing-link--unlocked" href="/remote-jobs/2"></a>
+                <h3 class="new-listing__header__title">Go Developer</h3>
+                <span class="posted">Posted Jan 5, 2024</span>
+            </li>
+            <li class="new-listing-container">
+                <a class="listing-link--unlocked" href="/remote-jobs/3"></a>
+                <h3 class="new-listing__header__title">Python Developer</h3>
+                <span class="posted">Featured</span>
+            </li>
+        </ul></body></html>"#;
+        let mut board = test_board();
+        board.selectors.posted = Some(scraper::Selector::parse("time, .posted").unwrap());
+
+        let (jobs, _) = parse_listing_page(html, &board, &mut SelectorHits::default());
+
+        assert_eq!(jobs[0].posted_date.as_deref(), Some("2024-05-01T12:00:00Z"));
+        assert_eq!(jobs[1].posted_date.as_deref(), Some("2024-01-05T00:00:00Z"));
+        assert_eq!(jobs[2].posted_date, None);
+    }
+
+    #[test]
+    fn test_parse_listing_page_falls_back_to_json_ld() {
+        let html = r#"<html><head><script type="application/ld+json">
+            [{"@type": "JobPosting", "title": "Rust Developer", "url": "/remote-jobs/1",
+              "hiringOrganization": {"name": "Acme"}, "jobLocationType": "TELECOMMUTE"}]
+            </script></head><body><div class="redesigned-listing">Rust Developer</div></body></html>"#;
+        let mut board = test_board();
+
+        let (jobs, _) = parse_listing_page(html, &board, &mut SelectorHits::default());
+        assert_eq!(jobs.len(), 1);
+        assert_eq!(jobs[0].company, "Acme");
+        assert_eq!(jobs[0].url, "https://weworkremotely.com/remote-jobs/1");
+
+        board.structured_data = StructuredData::Off;
+        let (jobs, _) = parse_listing_page(html, &board, &mut SelectorHits::default());
+        assert!(jobs.is_empty());
+    }
+
+    #[test]
+    fn test_disabled_board_contributes_no_jobs() {
+        let board_toml = |name: &str, enabled: bool| {
+            format!(
+                r##"
+[[boards]]
+name = "{name}"
+base_url = "https://{name}.example"
+urls = ["https://{name}.example/jobs"]
+enabled = {enabled}
+
+[boards.selectors]
+job = ".new-listing-container"
+title = ".new-listing__header__title"
+company = ".new-listing__company-name"
+region = ".new-listing__company-headquarters"
+link = ".listing-link--unlocked"
+next_page = "a[rel='next']"
+description = "#description"
+"##
+            )
+        };
+        let text = board_toml("steady", true) + &board_toml("flaky", false);
+        let boards = ScraperConfig::parse(&text).unwrap().into_boards().unwrap();
+
+        let mut pages = std::collections::HashMap::new();
+        pages.insert(
+            "https://steady.example/jobs".to_string(),
+            listing_html(&[("Rust Developer", "/remote-jobs/1")], None),
+        );
+        pages.insert(
+            "https://flaky.example/jobs".to_string(),
+            listing_html(&[("Go Developer", "/remote-jobs/2")], None),
+        );
+        let fetcher = MockFetcher { pages };
+
+        let (selected, skipped) = select_boards(boards, &[], &[]).unwrap();
+        assert_eq!(skipped, vec![("flaky".to_string(), "disabled in config")]);
+
+        let mut seen = SeenKeys::default();
+        let mut jobs: Vec<Job> = Vec::new();
+        for board in &selected {
+            scrape_board(
+                &fetcher,
+                board,
+                5,
+                &mut seen,
+                &mut SelectorReport::default(),
+                &mut jobs,
+            )
+            .unwrap();
+        }
+        let titles: Vec<&str> = jobs.iter().map(|j| j.title.as_str()).collect();
+        assert_eq!(titles, vec!["Rust Developer"]);
+    }
+
+    #[test]
+    fn test_scrape_category_respects_max_pages() {
+        let jobs = scrape_two_pages(&[("Go Developer", "/remote-jobs/2")], None, 1);
+        assert_eq!(jobs.len(), 1);
+    }
+
+    #[test]
+    fn test_scrape_category_stops_when_page_has_no_new_jobs() {
+        // Page 2 links back to itself and only repeats page 1's job
+        let jobs = scrape_two_pages(
+            &[("Rust Developer", "/remote-jobs/1")],
+            Some("/jobs?page=2"),
+            10,
+        );
+        assert_eq!(jobs.len(), 1);
+    }
+
+    /// Counts how many fetches are in flight at once
+    struct CountingFetcher {
+        in_flight: AtomicUsize,
+        max_in_flight: AtomicUsize,
+    }
+
+    impl Fetcher for CountingFetcher {
+        fn fetch(&self, url: &str) -> Result<String, Error> {
+            let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_in_flight.fetch_max(now, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(20));
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+            Ok(format!(
+                r#"<div class="lis-container__job__content__description">Details for {}</div>"#,
+                url
+            ))
+        }
+    }
+
+    #[test]
+    fn test_fetch_full_descriptions_bounds_concurrency() {
+        let mut jobs: Vec<Job> = (0..10)
+            .map(|i| Job {
+                title: format!("Job {}", i),
+                company: "Acme".to_string(),
+                location: "Remote".to_string(),
+                description: "blurb".to_string(),
+                salary_min: None,
+                url: format!("https://weworkremotely.com/remote-jobs/{}", i),
+                ..Job::default()
+            })
+            .collect();
+        let fetcher = CountingFetcher {
+            in_flight: AtomicUsize::new(0),
+            max_in_flight: AtomicUsize::new(0),
+        };
+
+        fetch_full_descriptions(&fetcher, &mut jobs, &test_board(), 3, Duration::ZERO);
+
+        let max_in_flight = fetcher.max_in_flight.load(Ordering::SeqCst);
+        assert!((1..=3).contains(&max_in_flight));
+        assert_eq!(
+            jobs[7].description,
+            "Details for https://weworkremotely.com/remote-jobs/7"
+        );
+    }
+
+    #[test]
+    fn test_second_run_reports_no_new_jobs() {
+        let mut pages = std::collections::HashMap::new();
+        pages.insert(
+            "https://weworkremotely.com/jobs".to_string(),
+            listing_html(
+                &[
+                    ("Rust Developer", "/remote-jobs/1"),
+                    ("Go Developer", "/remote-jobs/2"),
+                ],
+                None,
+            ),
+        );
+        let fetcher = MockFetcher { pages };
+        let mut store = SeenStore::default();
+
+        let mut run = |now: u64| {
+            let mut seen = SeenKeys::default();
+            let mut jobs = Vec::new();
+            scrape_category(
+                &fetcher,
+                &test_board(),
+                "https://weworkremotely.com/jobs",
+                5,
+                &mut seen,
+                &mut jobs,
+            )
+            .unwrap();
+            let new_count = record_seen(&mut store, &mut jobs, now).len();
+            (new_count, jobs)
+        };
+
+        assert_eq!(run(1_000).0, 2);
+        let (new_count, jobs) = run(2_000);
+        assert_eq!(new_count, 0);
+        // Jobs keep the time they were first scraped
+        assert_eq!(jobs[0].scraped_at, Some(format_timestamp(1_000)));
+    }
+
+    /// Serves "ok" to every HTTP/1.1 request on a local port, counting the
+    /// connections opened to it
+    fn keep_alive_server() -> (String, Arc<AtomicUsize>) {
+        use std::io::{BufRead, BufReader, Write};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+        let connections = Arc::new(AtomicUsize::new(0));
+        let counter = connections.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else { break };
+                counter.fetch_add(1, Ordering::SeqCst);
+                thread::spawn(move || {
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    let mut writer = stream;
+                    let mut line = String::new();
+                    // Answer each request once its headers end
+                    while reader.read_line(&mut line).unwrap_or(0) > 0 {
+                        if line == "\r\n" {
+                            let response = "HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok";
+                            if writer.write_all(response.as_bytes()).is_err() {
+                                break;
+                            }
+                        }
+                        line.clear();
+                    }
+                });
+            }
+        });
+        (address, connections)
+    }
+
+    #[test]
+    fn test_http_fetcher_reuses_connections() {
+        let (address, connections) = keep_alive_server();
+        let fetcher = HttpFetcher::new(8, Duration::from_secs(90)).unwrap();
+
+        for i in 0..5 {
+            let body = fetcher.fetch(&format!("{}/page/{}", address, i)).unwrap();
+            assert_eq!(body, "ok");
+        }
+
+        // Every request after the first went over the pooled connection
+        assert_eq!(connections.load(Ordering::SeqCst), 1);
+    }
+
+    /// Stands in for the search server's `POST /jobs`: keeps each request
+    /// body and answers 200
+    fn job_receiver() -> (String, Arc<std::sync::Mutex<Vec<String>>>) {
+        use std::io::{BufRead, BufReader, Read, Write};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+        let bodies = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let received = bodies.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else { break };
+                let received = received.clone();
+                thread::spawn(move || {
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    let mut writer = stream;
+                    let mut line = String::new();
+                    let mut content_length = 0;
+                    while reader.read_line(&mut line).unwrap_or(0) > 0 {
+                        if let Some((name, value)) = line.split_once(':') {
+                            if name.eq_ignore_ascii_case("content-length") {
+                                content_length = value.trim().parse().unwrap();
+                            }
+                        }
+                        if line == "\r\n" {
+                            let mut body = vec![0; content_length];
+                            reader.read_exact(&mut body).unwrap();
+                            received
+                                .lock()
+                                .unwrap()
+                                .push(String::from_utf8(body).unwrap());
+                            let response = "HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n{}";
+                            if writer.write_all(response.as_bytes()).is_err() {
+                                break;
+                            }
+                            content_length = 0;
+                        }
+                        line.clear();
+                    }
+                });
+            }
+        });
+        (address, bodies)
+    }
+
+    #[test]
+    fn test_scraped_pages_are_pushed_to_the_server_as_they_are_parsed() {
+        let mut pages = std::collections::HashMap::new();
+        pages.insert(
+            "https://weworkremotely.com/remote-software-developer-jobs".to_string(),
+            listing_html(
+                &[
+                    ("Rust Developer", "/remote-jobs/1"),
+                    ("Go Developer", "/remote-jobs/2"),
+                    ("Python Developer", "/remote-jobs/3"),
+                ],
+                Some("/remote-software-developer-jobs?page=2"),
+            ),
+        );
+        pages.insert(
+            "https://weworkremotely.com/remote-software-developer-jobs?page=2".to_string(),
+            listing_html(&[("Java Developer", "/remote-jobs/4")], None),
+        );
+        let fetcher = MockFetcher { pages };
+        let board = test_board();
+
+        let (address, bodies) = job_receiver();
+        let http = HttpFetcher::new(1, Duration::from_secs(5)).unwrap();
+        let mut sink = HttpSink::new(&http, &format!("{}/", address));
+        let mut pusher = BatchPusher::new(&mut sink, 2);
+        let thresholds = QualityThresholds {
+            min_company_ratio: 0.5,
+            min_valid_ratio: 0.8,
+            min_salary_ratio: 0.0,
+        };
+
+        // Requests the server had received as each page was handed over
+        let mut sent_before_page = Vec::new();
+        scrape_board(
+            &fetcher,
+            &board,
+            5,
+            &mut SeenKeys::default(),
+            &mut SelectorReport::default(),
+            &mut |page: Vec<Job>| {
+                sent_before_page.push(bodies.lock().unwrap().len());
+                let pushed = push_page(
+                    &mut pusher,
+                    &board,
+                    page,
+                    &thresholds,
+                    false,
+                    MissingCompany::Tag,
+                )?;
+                assert!(pushed);
+                Ok(())
+            },
+        )
+        .unwrap();
+        // The first page went out, in batches of two, before the second was fetched
+        assert_eq!(sent_before_page, [0, 2]);
+        assert_eq!(pusher.pushed, 4);
+
+        let batches: Vec<Vec<Job>> = bodies
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|body| serde_json::from_str(body).unwrap())
+            .collect();
+        let titles: Vec<Vec<&str>> = batches
+            .iter()
+            .map(|batch| batch.iter().map(|job| job.title.as_str()).collect())
+            .collect();
+        assert_eq!(
+            titles,
+            [
+                vec!["Rust Developer", "Go Developer"],
+                vec!["Python Developer"],
+                vec!["Java Developer"]
+            ]
+        );
+
+        // A page failing its checks isn't sent
+        let unusable = vec![Job::default()];
+        assert!(
+            !push_page(
+                &mut pusher,
+                &board,
+                unusable,
+                &thresholds,
+                false,
+                MissingCompany::Tag
+            )
+            .unwrap()
+        );
+        assert_eq!(bodies.lock().unwrap().len(), 3);
+    }
+}
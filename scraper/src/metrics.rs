@@ -0,0 +1,90 @@
+//! Selector hit rates
+//!
+//! A selector that stops matching doesn't fail the scrape: the listing gets
+//! a placeholder ("Unknown Title", "Remote", ...) and the run carries on.
+//! Counting how often each selector found something, per category URL,
+//! shows markup drift in the run report before the data visibly degrades.
+
+/// Listing selectors whose misses fall back to a placeholder
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectorField {
+    Title,
+    Company,
+    Region,
+    Link,
+}
+
+impl SelectorField {
+    pub const ALL: [SelectorField; 4] = [
+        SelectorField::Title,
+        SelectorField::Company,
+        SelectorField::Region,
+        SelectorField::Link,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SelectorField::Title => "title",
+            SelectorField::Company => "company",
+            SelectorField::Region => "region",
+            SelectorField::Link => "link",
+        }
+    }
+}
+
+/// How many listings were parsed, and how many of them each selector matched
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SelectorHits {
+    pub listings: usize,
+    matched: [usize; 4],
+}
+
+impl SelectorHits {
+    /// Counts one more listing
+    pub fn add_listing(&mut self) {
+        self.listings += 1;
+    }
+
+    /// Counts a match of `field`'s selector in the current listing
+    pub fn record(&mut self, field: SelectorField, matched: bool) {
+        if matched {
+            self.matched[field as usize] += 1;
+        }
+    }
+
+    /// Share of listings `field`'s selector matched, in percent; None
+    /// before any listing was parsed
+    pub fn rate(&self, field: SelectorField) -> Option<f64> {
+        (self.listings > 0)
+            .then(|| self.matched[field as usize] as f64 * 100.0 / self.listings as f64)
+    }
+}
+
+/// Hit rates per category URL for the run report
+#[derive(Debug, Default)]
+pub struct SelectorReport {
+    pub urls: Vec<(String, SelectorHits)>,
+}
+
+impl SelectorReport {
+    pub fn add(&mut self, url: &str, hits: SelectorHits) {
+        self.urls.push((url.to_string(), hits));
+    }
+
+    /// One line per URL, e.g. `<url>: title 100%, company 50%, ... (2 listings)`
+    pub fn lines(&self) -> Vec<String> {
+        self.urls
+            .iter()
+            .map(|(url, hits)| {
+                let rates: Vec<String> = SelectorField::ALL
+                    .iter()
+                    .map(|&field| match hits.rate(field) {
+                        Some(rate) => format!("{} {:.0}%", field.name(), rate),
+                        None => format!("{} -", field.name()),
+                    })
+                    .collect();
+                format!("{}: {} ({} listings)", url, rates.join(", "), hits.listings)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,88 @@
+//! Pushing scraped jobs straight into a running server
+//!
+//! With `--push-to`, jobs are sent to the server's `POST /jobs` as each
+//! listing page is scraped instead of being written to `data/jobs.json` for
+//! the next restart, so search picks them up within seconds and nothing is
+//! written and read back. A page's jobs are sent in batches of at most
+//! `--push-batch-size`, each one commit on the server.
+
+use crate::HttpFetcher;
+use common::{Error, Job};
+
+/// Somewhere jobs can be sent. Abstracted so tests can record batches.
+pub trait JobSink {
+    fn send(&mut self, jobs: &[Job]) -> Result<(), Error>;
+}
+
+/// Sends jobs to a server's `POST /jobs`, which upserts them by URL
+pub struct HttpSink {
+    client: reqwest::blocking::Client,
+    endpoint: String,
+}
+
+impl HttpSink {
+    /// Sends to `<server_url>/jobs`, sharing `http`'s connection pool
+    pub fn new(http: &HttpFetcher, server_url: &str) -> HttpSink {
+        HttpSink {
+            client: http.client.clone(),
+            endpoint: format!("{}/jobs", server_url.trim_end_matches('/')),
+        }
+    }
+}
+
+impl JobSink for HttpSink {
+    fn send(&mut self, jobs: &[Job]) -> Result<(), Error> {
+        self.client
+            .post(&self.endpoint)
+            .json(jobs)
+            .send()
+            .and_then(|response| response.error_for_status())
+            .map(|_| ())
+            .map_err(|e| Error::Push {
+                url: self.endpoint.clone(),
+                message: e.to_string(),
+            })
+    }
+}
+
+/// Buffers jobs and sends them `batch_size` at a time
+pub struct BatchPusher<'a> {
+    sink: &'a mut dyn JobSink,
+    batch_size: usize,
+    pending: Vec<Job>,
+    /// Jobs sent so far
+    pub pushed: usize,
+}
+
+impl<'a> BatchPusher<'a> {
+    pub fn new(sink: &'a mut dyn JobSink, batch_size: usize) -> BatchPusher<'a> {
+        BatchPusher {
+            sink,
+            batch_size: batch_size.max(1),
+            pending: Vec::new(),
+            pushed: 0,
+        }
+    }
+
+    /// Queues `jobs`, sending every full batch
+    pub fn push(&mut self, jobs: impl IntoIterator<Item = Job>) -> Result<(), Error> {
+        for job in jobs {
+            self.pending.push(job);
+            if self.pending.len() >= self.batch_size {
+                self.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Sends whatever is queued
+    pub fn flush(&mut self) -> Result<(), Error> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        self.sink.send(&self.pending)?;
+        self.pushed += self.pending.len();
+        self.pending.clear();
+        Ok(())
+    }
+}
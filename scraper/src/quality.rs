@@ -0,0 +1,140 @@
+//! Sanity checks on scraped jobs before they are written
+//!
+//! A board redesign usually doesn't break the scraper outright: the selectors
+//! still match something, and every job comes out without a company name
+//! or without a link. These checks catch that before jobs.json is overwritten.
+
+use common::Job;
+
+/// Minimum share of jobs that must pass each check (0.0 to 1.0)
+#[derive(Debug, Clone)]
+pub struct QualityThresholds {
+    /// Jobs with a company name the selector actually found
+    pub min_company_ratio: f64,
+    /// Jobs with a title and an absolute link
+    pub min_valid_ratio: f64,
+    /// Jobs with a parsed salary; 0 disables the check, since many
+    /// postings don't list one
+    pub min_salary_ratio: f64,
+}
+
+/// Whether the scraper found a real company name for the job
+fn has_company(job: &Job) -> bool {
+    job.known_company().is_some()
+}
+
+/// Whether the job has what the server needs to index and link it
+fn is_valid(job: &Job) -> bool {
+    !job.title.trim().is_empty()
+        && job.title != "Unknown Title"
+        && (job.url.starts_with("http://") || job.url.starts_with("https://"))
+}
+
+/// A check's description, the test each job must pass, and the share required
+type Check = (&'static str, fn(&Job) -> bool, f64);
+
+/// Runs every check over `jobs` and describes the ones that failed
+pub fn check_quality(jobs: &[Job], thresholds: &QualityThresholds) -> Vec<String> {
+    if jobs.is_empty() {
+        return vec!["no jobs were scraped".to_string()];
+    }
+
+    let checks: [Check; 3] = [
+        (
+            "have a company name",
+            has_company,
+            thresholds.min_company_ratio,
+        ),
+        (
+            "have a title and link",
+            is_valid,
+            thresholds.min_valid_ratio,
+        ),
+        (
+            "have a salary",
+            |job| job.salary_min.is_some(),
+            thresholds.min_salary_ratio,
+        ),
+    ];
+
+    let mut failures = Vec::new();
+    for (name, passes, min_ratio) in checks {
+        let passed = jobs.iter().filter(|job| passes(job)).count();
+        let ratio = passed as f64 / jobs.len() as f64;
+        if ratio < min_ratio {
+            failures.push(format!(
+                "only {} of {} jobs ({:.0}%) {}, need at least {:.0}%",
+                passed,
+                jobs.len(),
+                ratio * 100.0,
+                name,
+                min_ratio * 100.0
+            ));
+        }
+    }
+    failures
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn thresholds() -> QualityThresholds {
+        QualityThresholds {
+            min_company_ratio: 0.5,
+            min_valid_ratio: 0.8,
+            min_salary_ratio: 0.0,
+        }
+    }
+
+    fn job(company: &str, url: &str) -> Job {
+        Job {
+            title: "Rust Developer".to_string(),
+            company: company.to_string(),
+            location: "Remote".to_string(),
+            url: url.to_string(),
+            ..Job::default()
+        }
+    }
+
+    #[test]
+    fn test_healthy_jobs_pass() {
+        let jobs = vec![
+            job("Acme", "https://example.com/1"),
+            job("Globex", "https://example.com/2"),
+        ];
+        assert!(check_quality(&jobs, &thresholds()).is_empty());
+    }
+
+    #[test]
+    fn test_degraded_jobs_trip_thresholds() {
+        let jobs = vec![
+            job("Unknown Company", "https://example.com/1"),
+            job("Unknown Company", "No URL"),
+            job("Unknown Company", "No URL"),
+            job("Acme", "https://example.com/4"),
+        ];
+
+        let failures = check_quality(&jobs, &thresholds());
+
+        assert_eq!(failures.len(), 2, "{:?}", failures);
+        assert!(failures[0].contains("only 1 of 4 jobs (25%) have a company name"));
+        assert!(failures[1].contains("have a title and link"));
+    }
+
+    #[test]
+    fn test_salary_check_and_empty_run() {
+        let strict = QualityThresholds {
+            min_salary_ratio: 0.5,
+            ..thresholds()
+        };
+        let failures = check_quality(&[job("Acme", "https://example.com/1")], &strict);
+        assert_eq!(failures.len(), 1);
+        assert!(failures[0].contains("have a salary"));
+
+        assert_eq!(
+            check_quality(&[], &thresholds()),
+            vec!["no jobs were scraped"]
+        );
+    }
+}
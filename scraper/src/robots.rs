@@ -0,0 +1,289 @@
+//! robots.txt support
+//!
+//! Before fetching from a host the scraper reads its robots.txt once, skips
+//! paths it disallows, and waits at least its `Crawl-delay` between requests
+//! to that host (or a default delay when the site doesn't set one).
+
+use crate::Fetcher;
+use common::Error;
+use reqwest::Url;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Name matched against `User-agent` lines
+pub const USER_AGENT: &str = "niche-job-scraper";
+
+/// The rules of one robots.txt that apply to us
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Robots {
+    allow: Vec<String>,
+    disallow: Vec<String>,
+    pub crawl_delay: Option<Duration>,
+}
+
+impl Robots {
+    /// Parses robots.txt, keeping the group for `user_agent` if there is one,
+    /// otherwise the `*` group
+    pub fn parse(text: &str, user_agent: &str) -> Robots {
+        let user_agent = user_agent.to_lowercase();
+        let mut specific: Option<Robots> = None;
+        let mut wildcard: Option<Robots> = None;
+
+        // Agents named by the current group, and whether its rules have started
+        let mut agents: Vec<String> = Vec::new();
+        let mut in_rules = false;
+        let mut current = Robots::default();
+
+        let mut finish_group = |agents: &[String], rules: Robots| {
+            if agents
+                .iter()
+                .any(|agent| user_agent.contains(agent.as_str()))
+            {
+                specific.get_or_insert(rules);
+            } else if agents.iter().any(|agent| agent == "*") {
+                wildcard.get_or_insert(rules);
+            }
+        };
+
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let key = key.trim().to_lowercase();
+            let value = value.trim();
+
+            if key == "user-agent" {
+                // A user-agent line after rules starts a new group
+                if in_rules {
+                    finish_group(&agents, std::mem::take(&mut current));
+                    agents.clear();
+                    in_rules = false;
+                }
+                agents.push(value.to_lowercase());
+                continue;
+            }
+
+            in_rules = true;
+            match key.as_str() {
+                "allow" if !value.is_empty() => current.allow.push(value.to_string()),
+                "disallow" if !value.is_empty() => current.disallow.push(value.to_string()),
+                "crawl-delay" => {
+                    current.crawl_delay = value
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|secs| secs.is_finite() && *secs >= 0.0)
+                        .map(Duration::from_secs_f64);
+                }
+                _ => {}
+            }
+        }
+        finish_group(&agents, current);
+
+        specific.or(wildcard).unwrap_or_default()
+    }
+
+    /// Whether `path` may be fetched. The longest matching rule wins,
+    /// and `Allow` wins a tie.
+    pub fn is_allowed(&self, path: &str) -> bool {
+        let longest = |rules: &[String]| {
+            rules
+                .iter()
+                .filter(|rule| path.starts_with(rule.as_str()))
+                .map(String::len)
+                .max()
+        };
+        match (longest(&self.allow), longest(&self.disallow)) {
+            (_, None) => true,
+            (None, Some(_)) => false,
+            (Some(allow), Some(disallow)) => allow >= disallow,
+        }
+    }
+}
+
+/// What we know about one host
+struct HostState {
+    robots: Robots,
+    /// Earliest time the next request to the host may start
+    next_request: Instant,
+}
+
+/// Wraps a fetcher to obey each host's robots.txt and keep requests to the
+/// same host at least `Crawl-delay` (or `default_delay`) apart, even across
+/// worker threads
+pub struct PoliteFetcher<'a> {
+    inner: &'a dyn Fetcher,
+    default_delay: Duration,
+    hosts: Mutex<HashMap<String, HostState>>,
+}
+
+impl<'a> PoliteFetcher<'a> {
+    pub fn new(inner: &'a dyn Fetcher, default_delay: Duration) -> Self {
+        PoliteFetcher {
+            inner,
+            default_delay,
+            hosts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Reads robots.txt for the host the first time it is seen; a missing
+    /// or unreadable file allows everything
+    fn load_robots(&self, origin: &str) -> Robots {
+        let robots_url = format!("{}/robots.txt", origin);
+        let robots = match self.inner.fetch(&robots_url) {
+            Ok(text) => Robots::parse(&text, USER_AGENT),
+            Err(_) => Robots::default(),
+        };
+        if let Some(delay) = robots.crawl_delay {
+            println!("🐢 {} asks for {:?} between requests", origin, delay);
+        }
+        robots
+    }
+}
+
+impl Fetcher for PoliteFetcher<'_> {
+    fn fetch(&self, url: &str) -> Result<String, Error> {
+        let parsed = Url::parse(url).map_err(|e| Error::Scrape {
+            url: url.to_string(),
+            message: e.to_string(),
+        })?;
+        let origin = parsed.origin().ascii_serialization();
+
+        // robots.txt is fetched without holding the lock, so a slow host
+        // doesn't hold up requests to the others. Two workers meeting a new
+        // host at once may both fetch it; the first to finish is kept.
+        let known = self.hosts.lock().unwrap().contains_key(&origin);
+        let loaded = (!known).then(|| self.load_robots(&origin));
+
+        // Reserve a slot under the lock, then wait for it outside the lock
+        let start_at = {
+            let mut hosts = self.hosts.lock().unwrap();
+            let host = hosts.entry(origin).or_insert_with(|| HostState {
+                robots: loaded.unwrap_or_default(),
+                next_request: Instant::now(),
+            });
+
+            if !host.robots.is_allowed(parsed.path()) {
+                return Err(Error::Scrape {
+                    url: url.to_string(),
+                    message: "disallowed by robots.txt".to_string(),
+                });
+            }
+
+            let delay = host
+                .robots
+                .crawl_delay
+                .map_or(self.default_delay, |delay| delay.max(self.default_delay));
+            let start_at = host.next_request.max(Instant::now());
+            host.next_request = start_at + delay;
+            start_at
+        };
+
+        let wait = start_at.saturating_duration_since(Instant::now());
+        if !wait.is_zero() {
+            thread::sleep(wait);
+        }
+        self.inner.fetch(url)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROBOTS: &str = "\
+User-agent: Googlebot
+Disallow: /
+
+User-agent: *
+Crawl-delay: 0.05
+Disallow: /admin
+Allow: /admin/public
+";
+
+    #[test]
+    fn test_parse_uses_wildcard_group() {
+        let robots = Robots::parse(ROBOTS, USER_AGENT);
+
+        assert_eq!(robots.crawl_delay, Some(Duration::from_millis(50)));
+        assert!(robots.is_allowed("/remote-jobs/1"));
+        assert!(!robots.is_allowed("/admin/users"));
+        assert!(robots.is_allowed("/admin/public/jobs"));
+
+        // Googlebot's own group applies to Googlebot only
+        assert!(!Robots::parse(ROBOTS, "Googlebot").is_allowed("/remote-jobs/1"));
+    }
+
+    /// Serves ROBOTS and records when each page was requested
+    struct TimedFetcher {
+        requests: Mutex<Vec<Instant>>,
+    }
+
+    impl Fetcher for TimedFetcher {
+        fn fetch(&self, url: &str) -> Result<String, Error> {
+            if url.ends_with("/robots.txt") {
+                return Ok(ROBOTS.to_string());
+            }
+            self.requests.lock().unwrap().push(Instant::now());
+            Ok(String::new())
+        }
+    }
+
+    #[test]
+    fn test_polite_fetcher_spaces_requests_by_crawl_delay() {
+        let inner = TimedFetcher {
+            requests: Mutex::new(Vec::new()),
+        };
+        let fetcher = PoliteFetcher::new(&inner, Duration::from_millis(10));
+
+        let started = Instant::now();
+        for i in 0..3 {
+            fetcher
+                .fetch(&format!("https://example.com/remote-jobs/{}", i))
+                .unwrap();
+        }
+        let disallowed = fetcher.fetch("https://example.com/admin/users");
+
+        assert!(disallowed.is_err());
+        let requests = inner.requests.lock().unwrap();
+        assert_eq!(requests.len(), 3);
+        // Crawl-delay (50ms) beats the shorter default: the third page
+        // can't start until two delays have passed
+        assert!(requests[2] - started >= Duration::from_millis(100));
+    }
+
+    /// Takes `ROBOTS_DELAY` to answer for slow.example's robots.txt
+    struct SlowRobotsFetcher;
+
+    const ROBOTS_DELAY: Duration = Duration::from_millis(300);
+
+    impl Fetcher for SlowRobotsFetcher {
+        fn fetch(&self, url: &str) -> Result<String, Error> {
+            if url == "https://slow.example/robots.txt" {
+                thread::sleep(ROBOTS_DELAY);
+            }
+            Ok(String::new())
+        }
+    }
+
+    #[test]
+    fn test_slow_robots_txt_does_not_hold_up_other_hosts() {
+        let fetcher = PoliteFetcher::new(&SlowRobotsFetcher, Duration::ZERO);
+
+        let started = Instant::now();
+        let fast_done = thread::scope(|scope| {
+            scope.spawn(|| fetcher.fetch("https://slow.example/jobs").unwrap());
+            // Give the slow host's robots.txt fetch a head start
+            thread::sleep(Duration::from_millis(50));
+            let fast = scope.spawn(|| {
+                fetcher.fetch("https://fast.example/jobs").unwrap();
+                started.elapsed()
+            });
+            fast.join().unwrap()
+        });
+
+        assert!(fast_done < ROBOTS_DELAY);
+    }
+}
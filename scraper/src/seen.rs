@@ -0,0 +1,106 @@
+//! Persistent record of job URLs seen by previous scraper runs
+//!
+//! Stored as a JSON sidecar next to jobs.json so incremental runs can tell
+//! new postings from ones already collected.
+
+use common::{Error, job_id};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// When a URL was first and most recently seen (unix seconds)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeenEntry {
+    pub id: String,
+    pub first_seen: u64,
+    pub last_seen: u64,
+}
+
+/// Known job URLs, keyed by URL
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SeenStore {
+    entries: HashMap<String, SeenEntry>,
+}
+
+impl SeenStore {
+    /// Loads the store from `path`, dropping entries not seen within `max_age_secs` of `now`.
+    /// A missing or unreadable file yields an empty store.
+    pub fn load(path: &Path, max_age_secs: u64, now: u64) -> SeenStore {
+        let mut store: SeenStore = fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+
+        store
+            .entries
+            .retain(|_, entry| now.saturating_sub(entry.last_seen) <= max_age_secs);
+        store
+    }
+
+    /// Writes the store to `path` as JSON
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json).map_err(|e| Error::io(path, e))
+    }
+
+    /// Marks `url` as seen at `now`. Returns true if it wasn't known before.
+    pub fn record(&mut self, url: &str, now: u64) -> bool {
+        match self.entries.get_mut(url) {
+            Some(entry) => {
+                entry.last_seen = now;
+                false
+            }
+            None => {
+                self.entries.insert(
+                    url.to_string(),
+                    SeenEntry {
+                        id: job_id(url),
+                        first_seen: now,
+                        last_seen: now,
+                    },
+                );
+                true
+            }
+        }
+    }
+
+    /// When `url` was first seen (unix seconds), if it is known
+    pub fn first_seen(&self, url: &str) -> Option<u64> {
+        self.entries.get(url).map(|entry| entry.first_seen)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_reports_new_urls_once() {
+        let mut store = SeenStore::default();
+        assert!(store.record("https://example.com/1", 100));
+        assert!(!store.record("https://example.com/1", 200));
+        assert_eq!(store.len(), 1);
+    }
+
+    #[test]
+    fn test_load_expires_old_entries() {
+        let path = std::env::temp_dir().join(format!("seen-expiry-{}.json", std::process::id()));
+
+        let mut store = SeenStore::default();
+        store.record("https://example.com/old", 1_000);
+        store.record("https://example.com/fresh", 9_000);
+        store.save(&path).unwrap();
+
+        let mut loaded = SeenStore::load(&path, 5_000, 10_000);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.len(), 1);
+        assert!(loaded.record("https://example.com/old", 10_000));
+        assert!(!loaded.record("https://example.com/fresh", 10_000));
+    }
+}
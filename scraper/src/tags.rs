@@ -0,0 +1,94 @@
+//! Job tags
+//!
+//! Boards that label listings (contract type, region, category) have their
+//! labels read straight from the markup. Otherwise tags are guessed from
+//! well-known keywords in the job title and location.
+
+use crate::config::Selectors;
+use common::Words;
+use scraper::ElementRef;
+
+/// Keywords looked for when a listing has no tag markup, and the tag each gives
+const KEYWORD_TAGS: &[(&str, &str)] = &[
+    ("full-time", "full-time"),
+    ("full time", "full-time"),
+    ("part-time", "part-time"),
+    ("part time", "part-time"),
+    ("contract", "contract"),
+    ("freelance", "contract"),
+    ("internship", "internship"),
+    ("intern", "internship"),
+    ("senior", "senior"),
+    ("junior", "junior"),
+    ("lead", "lead"),
+    ("backend", "backend"),
+    ("back-end", "backend"),
+    ("frontend", "frontend"),
+    ("front-end", "frontend"),
+    ("full stack", "full-stack"),
+    ("full-stack", "full-stack"),
+    ("fullstack", "full-stack"),
+    ("devops", "devops"),
+    ("anywhere", "worldwide"),
+    ("worldwide", "worldwide"),
+];
+
+/// Tags for one listing: the text of its tag elements when the board has a
+/// `tags` selector and any matched, otherwise keywords from `title` and `location`
+pub fn extract_tags(
+    element: &ElementRef,
+    selectors: &Selectors,
+    title: &str,
+    location: &str,
+) -> Vec<String> {
+    let mut tags = Vec::new();
+    if let Some(selector) = &selectors.tags {
+        for tag_element in element.select(selector) {
+            push_tag(&mut tags, &tag_element.text().collect::<String>());
+        }
+    }
+    if tags.is_empty() {
+        tags = keyword_tags(&format!("{} {}", title, location));
+    }
+    tags
+}
+
+/// Tags for the keywords that appear as whole words in `text`
+pub fn keyword_tags(text: &str) -> Vec<String> {
+    let words = Words::keeping_hyphens(text);
+    let mut tags = Vec::new();
+    for (keyword, tag) in KEYWORD_TAGS {
+        if words.contains(keyword) {
+            push_tag(&mut tags, tag);
+        }
+    }
+    tags
+}
+
+/// Adds a trimmed, lowercased tag unless it is empty or already present
+fn push_tag(tags: &mut Vec<String>, text: &str) {
+    let tag = text
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+    if !tag.is_empty() && !tags.contains(&tag) {
+        tags.push(tag);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keyword_tags_match_whole_words() {
+        assert_eq!(
+            keyword_tags("Senior Back-End Engineer (Contract) Anywhere in the World"),
+            vec!["contract", "senior", "backend", "worldwide"]
+        );
+        // "intern" inside "international" isn't an internship
+        assert!(keyword_tags("International Sales Lead").contains(&"lead".to_string()));
+        assert!(!keyword_tags("International Sales Lead").contains(&"internship".to_string()));
+    }
+}
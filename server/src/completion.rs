@@ -0,0 +1,74 @@
+//! Type-ahead completions from the index
+//!
+//! The query log only knows what has been searched; a fresh deployment has
+//! nothing to suggest. Completions are read from the term dictionaries of
+//! untokenized fields instead: normalized titles ("backend developer") and
+//! tags ("rust"). Walking a sorted dictionary from the prefix is cheap, so
+//! no search runs per keystroke.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tantivy::{Searcher, schema::Field};
+use utoipa::ToSchema;
+
+/// A completion and how many jobs have it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct Completion {
+    pub text: String,
+    pub jobs: u64,
+}
+
+/// Up to `limit` terms of `fields` starting with `prefix` (lowercased, with
+/// whitespace collapsed), each once, most common first and alphabetical
+/// among ties. A term found in several fields counts its most common one.
+/// Counts include deleted jobs until their segment is merged.
+pub fn complete(
+    searcher: &Searcher,
+    fields: &[Field],
+    prefix: &str,
+    limit: usize,
+) -> tantivy::Result<Vec<Completion>> {
+    let prefix = prefix
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+    if prefix.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut counts: HashMap<String, u64> = HashMap::new();
+    for &field in fields {
+        let mut field_counts: HashMap<String, u64> = HashMap::new();
+        for segment_reader in searcher.segment_readers() {
+            let inverted_index = segment_reader.inverted_index(field)?;
+            let mut terms = inverted_index
+                .terms()
+                .range()
+                .ge(prefix.as_bytes())
+                .into_stream()?;
+            while terms.advance() {
+                let Ok(text) = std::str::from_utf8(terms.key()) else {
+                    continue;
+                };
+                if !text.starts_with(&prefix) {
+                    break;
+                }
+                *field_counts.entry(text.to_string()).or_default() +=
+                    u64::from(terms.value().doc_freq);
+            }
+        }
+        for (text, jobs) in field_counts {
+            let count = counts.entry(text).or_default();
+            *count = (*count).max(jobs);
+        }
+    }
+
+    let mut completions: Vec<Completion> = counts
+        .into_iter()
+        .map(|(text, jobs)| Completion { text, jobs })
+        .collect();
+    completions.sort_by(|a, b| b.jobs.cmp(&a.jobs).then_with(|| a.text.cmp(&b.text)));
+    completions.truncate(limit);
+    Ok(completions)
+}
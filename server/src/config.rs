@@ -0,0 +1,320 @@
+//! Server configuration
+//!
+//! Settings are read once at startup from environment variables, so a
+//! deployment can be tuned without recompiling.
+
+use crate::ranking::FieldBoosts;
+use common::DedupStrategy;
+use std::collections::BTreeMap;
+use std::env;
+use std::str::FromStr;
+
+/// Server settings read from environment variables
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Index text fields with the CJK bigram tokenizer (`CJK_TOKENIZER`).
+    /// Off by default: Latin-only deployments don't need the larger index.
+    pub cjk_tokenizer: bool,
+
+    /// Job files to load and merge at startup (`JOBS_GLOB`, default `data/*.json`)
+    pub jobs_glob: String,
+
+    /// How long an `Idempotency-Key` is remembered (`IDEMPOTENCY_TTL_SECS`, default 24h)
+    pub idempotency_ttl_secs: u64,
+
+    /// What makes two jobs the same job when job files are merged and jobs
+    /// are re-imported (`DEDUP_STRATEGY`, default `url`)
+    pub dedup_strategy: DedupStrategy,
+
+    /// How long GET /dashboard reuses its numbers (`DASHBOARD_CACHE_SECS`, default 60)
+    pub dashboard_cache_secs: u64,
+
+    /// Markup placed before a highlighted match (`HIGHLIGHT_PRE_TAG`, default `<em>`)
+    pub highlight_pre_tag: String,
+
+    /// Markup placed after a highlighted match (`HIGHLIGHT_POST_TAG`, default `</em>`)
+    pub highlight_post_tag: String,
+
+    /// Return all jobs, best paid first, when `q` is empty (`ALLOW_MATCH_ALL`).
+    /// Off by default, so an empty search returns nothing.
+    pub allow_match_all: bool,
+
+    /// Store whole job descriptions in the index, and whole jobs for export
+    /// (`STORE_DESCRIPTIONS`). Off by default: descriptions are most of a
+    /// job's text, so storing them grows the index several times. Without
+    /// them only the start of each description is stored, for results.
+    pub store_descriptions: bool,
+
+    /// Rebuild the index when its schema or analyzers differ from these
+    /// settings (`REBUILD_INDEX`, or `--rebuild-index`). Off by default, so
+    /// such a mismatch stops startup instead of discarding the index.
+    pub rebuild_index: bool,
+
+    /// Bearer token required by the `/admin` endpoints (`ADMIN_TOKEN`).
+    /// Unset by default, which disables them.
+    pub admin_token: Option<String>,
+
+    /// BM25 term-frequency saturation (`BM25_K1`, default 1.2, from 0).
+    /// Lower values stop repeated words from adding much score.
+    pub bm25_k1: f32,
+
+    /// BM25 length normalization (`BM25_B`, default 0.75, 0 to 1).
+    /// 0 ignores field length; 1 fully favours shorter fields.
+    pub bm25_b: f32,
+
+    /// Weight of keyword matches per field for searches that don't set
+    /// `boost_*` (`BOOST_TITLE`, `BOOST_DESCRIPTION`, `BOOST_COMPANY`;
+    /// default 2, 1 and 0, each at least 0)
+    pub field_boosts: FieldBoosts,
+
+    /// File saved searches are kept in (`SAVED_SEARCHES_PATH`, default `saved_searches.json`).
+    /// Kept out of `data/` so it isn't mistaken for a job file.
+    pub saved_searches_path: String,
+
+    /// File the counts behind GET /suggest are kept in (`QUERY_LOG_PATH`).
+    /// Unset by default, which keeps them in memory only.
+    pub query_log_path: Option<String>,
+
+    /// Most distinct queries GET /suggest remembers (`QUERY_LOG_CAPACITY`,
+    /// default 10000, 0 to stop logging)
+    pub query_log_capacity: usize,
+
+    /// Most description tokens indexed per job (`MAX_DESCRIPTION_TOKENS`, default 2000,
+    /// 0 for no cap). Words past the cap aren't searchable, but a stored
+    /// description is kept whole.
+    pub max_description_tokens: Option<usize>,
+
+    /// Most jobs kept in the index (`MAX_INDEXED_JOBS`, default unlimited, 0
+    /// for unlimited too). Adding past it evicts the jobs scraped longest ago.
+    pub max_indexed_jobs: Option<usize>,
+
+    /// What to do when the query parser rejects a search (`QUERY_FALLBACK`,
+    /// `escape` by default, or `strip` / `off`)
+    pub query_fallback: QueryFallback,
+
+    /// Tokenizer per text field (`FIELD_TOKENIZERS`, e.g. `company=raw,description=en_stem`).
+    /// Fields left out keep their usual analyzer. Names are checked when the
+    /// index is built.
+    pub field_tokenizers: BTreeMap<String, String>,
+
+    /// Longest a search may run before the request gets a 503
+    /// (`SEARCH_TIMEOUT_MS`, default 2000, 0 for no limit)
+    pub search_timeout_ms: Option<u64>,
+
+    /// Query synonym groups, one comma-separated group per line
+    /// (`SYNONYMS_PATH`, default `synonyms.txt`; a missing file means none)
+    pub synonyms_path: String,
+
+    /// Words left out of queries, one per line
+    /// (`STOPWORDS_PATH`, default `stopwords.txt`; a missing file means none)
+    pub stopwords_path: String,
+
+    /// Fields a result's `snippet` is taken from, first match first
+    /// (`SNIPPET_FIELDS`, default `description,title,company`). Unknown names
+    /// are ignored; an empty list turns snippets off.
+    pub snippet_fields: Vec<SnippetField>,
+}
+
+/// A field a result snippet can come from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnippetField {
+    Title,
+    Company,
+    Description,
+}
+
+impl FromStr for SnippetField {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.trim().to_lowercase().as_str() {
+            "title" => Ok(SnippetField::Title),
+            "company" => Ok(SnippetField::Company),
+            "description" => Ok(SnippetField::Description),
+            other => Err(format!(
+                "unknown snippet field {:?}: expected title, company or description",
+                other
+            )),
+        }
+    }
+}
+
+/// How a search the query parser rejects ("frontend: react") is retried
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueryFallback {
+    /// Quote every word so operators like `:` are read as text
+    Escape,
+    /// Drop everything but letters, digits and spaces
+    Strip,
+    /// Don't retry: the search has no results
+    Off,
+}
+
+impl FromStr for QueryFallback {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.trim().to_lowercase().as_str() {
+            "escape" => Ok(QueryFallback::Escape),
+            "strip" => Ok(QueryFallback::Strip),
+            "off" => Ok(QueryFallback::Off),
+            other => Err(format!(
+                "unknown query fallback {:?}: expected escape, strip or off",
+                other
+            )),
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            cjk_tokenizer: false,
+            jobs_glob: "data/*.json".to_string(),
+            idempotency_ttl_secs: 24 * 60 * 60,
+            dedup_strategy: DedupStrategy::Url,
+            dashboard_cache_secs: 60,
+            highlight_pre_tag: "<em>".to_string(),
+            highlight_post_tag: "</em>".to_string(),
+            allow_match_all: false,
+            store_descriptions: false,
+            rebuild_index: false,
+            admin_token: None,
+            bm25_k1: 1.2,
+            bm25_b: 0.75,
+            field_boosts: FieldBoosts::default(),
+            saved_searches_path: "saved_searches.json".to_string(),
+            query_log_path: None,
+            query_log_capacity: 10_000,
+            max_description_tokens: Some(2_000),
+            max_indexed_jobs: None,
+            query_fallback: QueryFallback::Escape,
+            field_tokenizers: BTreeMap::new(),
+            search_timeout_ms: Some(2_000),
+            synonyms_path: "synonyms.txt".to_string(),
+            stopwords_path: "stopwords.txt".to_string(),
+            snippet_fields: vec![
+                SnippetField::Description,
+                SnippetField::Title,
+                SnippetField::Company,
+            ],
+        }
+    }
+}
+
+impl Config {
+    /// Reads the configuration from the process environment
+    pub fn from_env() -> Config {
+        let defaults = Config::default();
+        Config {
+            cjk_tokenizer: env_flag("CJK_TOKENIZER"),
+            jobs_glob: env::var("JOBS_GLOB").unwrap_or(defaults.jobs_glob),
+            idempotency_ttl_secs: env_parse("IDEMPOTENCY_TTL_SECS")
+                .unwrap_or(defaults.idempotency_ttl_secs),
+            dedup_strategy: env_parse("DEDUP_STRATEGY").unwrap_or(defaults.dedup_strategy),
+            dashboard_cache_secs: env_parse("DASHBOARD_CACHE_SECS")
+                .unwrap_or(defaults.dashboard_cache_secs),
+            highlight_pre_tag: env::var("HIGHLIGHT_PRE_TAG").unwrap_or(defaults.highlight_pre_tag),
+            highlight_post_tag: env::var("HIGHLIGHT_POST_TAG")
+                .unwrap_or(defaults.highlight_post_tag),
+            allow_match_all: env_flag("ALLOW_MATCH_ALL"),
+            store_descriptions: env_flag("STORE_DESCRIPTIONS"),
+            rebuild_index: env_flag("REBUILD_INDEX"),
+            admin_token: env::var("ADMIN_TOKEN")
+                .ok()
+                .filter(|token| !token.trim().is_empty()),
+            bm25_k1: env_parse("BM25_K1")
+                .filter(|k1: &f32| k1.is_finite() && *k1 >= 0.0)
+                .unwrap_or(defaults.bm25_k1),
+            bm25_b: env_parse("BM25_B")
+                .filter(|b: &f32| (0.0..=1.0).contains(b))
+                .unwrap_or(defaults.bm25_b),
+            field_boosts: FieldBoosts {
+                title: env_weight("BOOST_TITLE").unwrap_or(defaults.field_boosts.title),
+                description: env_weight("BOOST_DESCRIPTION")
+                    .unwrap_or(defaults.field_boosts.description),
+                company: env_weight("BOOST_COMPANY").unwrap_or(defaults.field_boosts.company),
+            },
+            saved_searches_path: env::var("SAVED_SEARCHES_PATH")
+                .unwrap_or(defaults.saved_searches_path),
+            query_log_path: env::var("QUERY_LOG_PATH")
+                .ok()
+                .filter(|path| !path.is_empty()),
+            query_log_capacity: env_parse("QUERY_LOG_CAPACITY")
+                .unwrap_or(defaults.query_log_capacity),
+            max_description_tokens: match env_parse("MAX_DESCRIPTION_TOKENS") {
+                Some(0) => None,
+                Some(limit) => Some(limit),
+                None => defaults.max_description_tokens,
+            },
+            max_indexed_jobs: match env_parse("MAX_INDEXED_JOBS") {
+                Some(0) => None,
+                Some(limit) => Some(limit),
+                None => defaults.max_indexed_jobs,
+            },
+            query_fallback: env_parse("QUERY_FALLBACK").unwrap_or(defaults.query_fallback),
+            field_tokenizers: env::var("FIELD_TOKENIZERS")
+                .map(|text| parse_field_tokenizers(&text))
+                .unwrap_or(defaults.field_tokenizers),
+            search_timeout_ms: match env_parse("SEARCH_TIMEOUT_MS") {
+                Some(0) => None,
+                Some(ms) => Some(ms),
+                None => defaults.search_timeout_ms,
+            },
+            synonyms_path: env::var("SYNONYMS_PATH").unwrap_or(defaults.synonyms_path),
+            stopwords_path: env::var("STOPWORDS_PATH").unwrap_or(defaults.stopwords_path),
+            snippet_fields: env::var("SNIPPET_FIELDS")
+                .map(|text| parse_snippet_fields(&text))
+                .unwrap_or(defaults.snippet_fields),
+        }
+    }
+}
+
+/// Parses `field=tokenizer` pairs separated by commas. An entry without `=`
+/// gets an empty tokenizer name, so it is reported rather than dropped.
+fn parse_field_tokenizers(text: &str) -> BTreeMap<String, String> {
+    text.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (field, tokenizer) = entry.split_once('=').unwrap_or((entry, ""));
+            (field.trim().to_lowercase(), tokenizer.trim().to_string())
+        })
+        .collect()
+}
+
+/// Parses a comma-separated field priority list, dropping unknown and repeated names
+fn parse_snippet_fields(text: &str) -> Vec<SnippetField> {
+    let mut fields = Vec::new();
+    for field in text.split(',').filter_map(|name| name.parse().ok()) {
+        if !fields.contains(&field) {
+            fields.push(field);
+        }
+    }
+    fields
+}
+
+/// Returns true when `name` is set to 1/true/yes/on (case-insensitive)
+fn env_flag(name: &str) -> bool {
+    env::var(name)
+        .map(|value| {
+            matches!(
+                value.trim().to_lowercase().as_str(),
+                "1" | "true" | "yes" | "on"
+            )
+        })
+        .unwrap_or(false)
+}
+
+/// Parses `name` as a field weight, returning None when unset, malformed,
+/// negative or not finite
+fn env_weight(name: &str) -> Option<f32> {
+    env_parse(name).filter(|weight: &f32| weight.is_finite() && *weight >= 0.0)
+}
+
+/// Parses `name` into `T`, returning None when unset or malformed
+fn env_parse<T: FromStr>(name: &str) -> Option<T> {
+    env::var(name)
+        .ok()
+        .and_then(|value| value.trim().parse().ok())
+}
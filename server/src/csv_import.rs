@@ -0,0 +1,206 @@
+//! Job import from CSV
+//!
+//! Spreadsheets export CSV, so recruiters can load jobs without writing
+//! JSON. The first row names the columns, after `Job`'s fields; each later
+//! row is one job. Cells are converted to the field's type and the row is
+//! checked before it is indexed, and a bad row is reported without failing
+//! the rest. List fields (`tags`, `countries`) separate their values with `;`.
+//!
+//! Parsing is the `csv` crate's, following RFC 4180: cells containing
+//! commas, quotes or line breaks are quoted, with quotes inside doubled
+//! (`"Rust, ""Senior"""`).
+
+use common::{Job, parse_timestamp};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use utoipa::ToSchema;
+
+/// Columns a CSV may have, each named after a `Job` field
+const COLUMNS: &[&str] = &[
+    "title",
+    "company",
+    "location",
+    "description",
+    "url",
+    "salary_min",
+    "salary_max",
+    "salary_currency",
+    "salary_period",
+    "salary_raw",
+    "posted_date",
+    "deadline",
+    "contract_type",
+    "tags",
+    "countries",
+    "featured",
+    "visa_sponsorship",
+];
+
+/// Columns every CSV needs
+const REQUIRED_COLUMNS: &[&str] = &["title", "url"];
+
+/// Why one row wasn't imported
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct RowError {
+    /// Line number of the row's start in the file, the header being line 1
+    pub line: usize,
+    pub message: String,
+}
+
+/// Jobs read from a CSV, and what happened to the other rows
+#[derive(Debug, Default)]
+pub struct CsvJobs {
+    pub jobs: Vec<Job>,
+    /// Rows whose cells are all empty, left out without complaint (the
+    /// reader drops empty lines before they become rows)
+    pub skipped: usize,
+    pub errors: Vec<RowError>,
+}
+
+/// Reads jobs from CSV text. Fails as a whole only when the header is
+/// unusable or the text isn't readable CSV; a bad row is reported in `errors`.
+pub fn jobs_from_csv(text: &str) -> Result<CsvJobs, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .flexible(true)
+        .from_reader(text.trim_start_matches('\u{feff}').as_bytes());
+    let header: Vec<String> = reader
+        .headers()
+        .map_err(|e| format!("unreadable CSV header: {}", e))?
+        .iter()
+        .map(|name| name.trim().to_lowercase())
+        .collect();
+    if header.iter().all(|name| name.is_empty()) {
+        return Err("CSV is empty: expected a header row".to_string());
+    }
+    if let Some(unknown) = header.iter().find(|name| !COLUMNS.contains(&name.as_str())) {
+        return Err(format!(
+            "unknown column {:?}: expected some of {}",
+            unknown,
+            COLUMNS.join(", ")
+        ));
+    }
+    for required in REQUIRED_COLUMNS {
+        if !header.iter().any(|name| name == required) {
+            return Err(format!("missing required column {:?}", required));
+        }
+    }
+
+    let mut result = CsvJobs::default();
+    for record in reader.records() {
+        let record = record.map_err(|e| format!("unreadable CSV: {}", e))?;
+        let line = record
+            .position()
+            .map_or(0, |position| position.line() as usize);
+        if record.iter().all(|cell| cell.trim().is_empty()) {
+            result.skipped += 1;
+            continue;
+        }
+        match row_to_job(&header, &record) {
+            Ok(job) => result.jobs.push(job),
+            Err(message) => result.errors.push(RowError { line, message }),
+        }
+    }
+    Ok(result)
+}
+
+/// Converts one row into a job, checking it along the way
+fn row_to_job(header: &[String], record: &csv::StringRecord) -> Result<Job, String> {
+    if record.len() != header.len() {
+        return Err(format!(
+            "has {} cells but the header has {} columns",
+            record.len(),
+            header.len()
+        ));
+    }
+
+    // Built as JSON so `Job`'s own deserialization checks the enum fields
+    let mut object = Map::new();
+    for field in ["title", "company", "location", "description", "url"] {
+        object.insert(field.to_string(), Value::String(String::new()));
+    }
+    for (column, cell) in header.iter().zip(record) {
+        let cell = cell.trim();
+        if cell.is_empty() {
+            continue;
+        }
+        let value = match column.as_str() {
+            "salary_min" | "salary_max" => {
+                let digits: String = cell.chars().filter(|c| *c != ',' && *c != '_').collect();
+                let amount: i64 = digits
+                    .parse()
+                    .map_err(|_| format!("{} {:?} is not a whole number", column, cell))?;
+                Value::from(amount)
+            }
+            "featured" | "visa_sponsorship" => match cell.to_lowercase().as_str() {
+                "true" | "yes" | "1" => Value::Bool(true),
+                "false" | "no" | "0" => Value::Bool(false),
+                _ => return Err(format!("{} {:?} is not true or false", column, cell)),
+            },
+            "tags" | "countries" => Value::from(
+                cell.split(';')
+                    .map(str::trim)
+                    .filter(|item| !item.is_empty())
+                    .collect::<Vec<_>>(),
+            ),
+            "salary_period" | "contract_type" => Value::String(cell.to_lowercase()),
+            _ => Value::String(cell.to_string()),
+        };
+        object.insert(column.clone(), value);
+    }
+    let job: Job = serde_json::from_value(Value::Object(object)).map_err(|e| e.to_string())?;
+
+    if job.title.is_empty() {
+        return Err("title is empty".to_string());
+    }
+    if !(job.url.starts_with("http://") || job.url.starts_with("https://")) {
+        return Err(format!("url {:?} is not an http(s) URL", job.url));
+    }
+    if let (Some(min), Some(max)) = (job.salary_min, job.salary_max) {
+        if min > max {
+            return Err(format!("salary_min {} is above salary_max {}", min, max));
+        }
+    }
+    for (column, date) in [
+        ("posted_date", &job.posted_date),
+        ("deadline", &job.deadline),
+    ] {
+        if let Some(date) = date {
+            if parse_timestamp(date).is_none() {
+                return Err(format!(
+                    "{} {:?} is not an RFC 3339 date-time",
+                    column, date
+                ));
+            }
+        }
+    }
+    Ok(job)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jobs_from_csv_reports_bad_rows() {
+        let csv = "Title,URL,salary_min,salary_max,contract_type,tags\n\
+                   Rust Developer,https://example.com/1,\"100,000\",120000,Full-Time,rust; backend\n\
+                   ,,,,,\n\
+                   Go Developer,not a url,,,,\n\
+                   Python Developer,https://example.com/3,lots,,,\n\
+                   Java Developer,https://example.com/4,,,permanent,\n";
+        let imported = jobs_from_csv(csv).unwrap();
+
+        assert_eq!(imported.jobs.len(), 1);
+        let job = &imported.jobs[0];
+        assert_eq!(job.salary_min, Some(100_000));
+        assert_eq!(job.contract_type, Some(common::ContractType::FullTime));
+        assert_eq!(job.tags, ["rust", "backend"]);
+        assert_eq!(imported.skipped, 1);
+        let lines: Vec<usize> = imported.errors.iter().map(|e| e.line).collect();
+        assert_eq!(lines, [4, 5, 6]);
+
+        assert!(jobs_from_csv("title,salary\n").is_err());
+        assert!(jobs_from_csv("title,company\n").is_err());
+    }
+}
@@ -0,0 +1,178 @@
+//! Aggregate overview of the index for GET /dashboard
+//!
+//! Tallying companies and tags reads every stored document, so the result is
+//! kept for a short while and recomputed once it's older than that, rather
+//! than on every request.
+
+use crate::index::{JobFields, company_key};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ops::Bound;
+use std::time::{Duration, Instant};
+use tantivy::{
+    Searcher,
+    collector::{Count, DocSetCollector},
+    query::{AllQuery, RangeQuery},
+};
+use utoipa::ToSchema;
+
+/// How many companies and tags the dashboard lists
+pub const TOP_N: usize = 5;
+
+/// How far back "recently scraped" reaches
+pub const RECENT_SECS: i64 = 7 * 24 * 60 * 60;
+
+/// A company or tag and how many jobs have it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct NameCount {
+    pub name: String,
+    pub count: usize,
+}
+
+/// Overview of the indexed jobs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Dashboard {
+    pub total_jobs: u64,
+    /// Jobs listing a minimum salary
+    pub jobs_with_salary: usize,
+    /// Companies with the most jobs, most first; names as first indexed
+    pub top_companies: Vec<NameCount>,
+    /// Most used tags, lowercased, most first
+    pub top_tags: Vec<NameCount>,
+    /// Median of the listed minimum salaries, in their own currencies and
+    /// periods; None when no job lists one
+    pub median_salary: Option<i64>,
+    /// Jobs scraped in the last 7 days
+    pub scraped_last_7_days: usize,
+    /// When these numbers were computed, as unix seconds
+    pub computed_at: i64,
+}
+
+impl Dashboard {
+    /// Computes the overview from the index as of `now` (unix seconds)
+    pub fn compute(searcher: &Searcher, fields: &JobFields, now: i64) -> tantivy::Result<Self> {
+        let with_salary =
+            RangeQuery::new_i64_bounds(fields.salary_min, Bound::Unbounded, Bound::Unbounded);
+        let mut salaries = Vec::new();
+        for doc_address in searcher.search(&with_salary, &DocSetCollector)? {
+            let segment_reader = searcher.segment_reader(doc_address.segment_ord);
+            let column = segment_reader.fast_fields().i64(fields.salary_min)?;
+            salaries.push(column.get_val(doc_address.doc_id));
+        }
+
+        let recent = RangeQuery::new_i64_bounds(
+            fields.scraped_at,
+            Bound::Included(now - RECENT_SECS),
+            Bound::Unbounded,
+        );
+        let scraped_last_7_days = searcher.search(&recent, &Count)?;
+
+        // Keyed by the normalized name, with the first spelling seen
+        let mut companies: HashMap<String, NameCount> = HashMap::new();
+        let mut tags: HashMap<String, usize> = HashMap::new();
+        // In index order, so the spelling kept doesn't depend on hashing
+        let mut doc_addresses: Vec<_> = searcher
+            .search(&AllQuery, &DocSetCollector)?
+            .into_iter()
+            .collect();
+        doc_addresses.sort();
+        for doc_address in doc_addresses {
+            let doc = searcher.doc(doc_address)?;
+            if let Some(company) = doc.get_first(fields.company).and_then(|v| v.as_text()) {
+                let key = company_key(company);
+                if !key.is_empty() {
+                    companies
+                        .entry(key)
+                        .or_insert_with(|| NameCount {
+                            name: company.trim().to_string(),
+                            count: 0,
+                        })
+                        .count += 1;
+                }
+            }
+            for tag in doc.get_all(fields.tag).filter_map(|v| v.as_text()) {
+                *tags.entry(tag.to_string()).or_default() += 1;
+            }
+        }
+        let tags = tags
+            .into_iter()
+            .map(|(name, count)| NameCount { name, count });
+
+        Ok(Dashboard {
+            total_jobs: searcher.num_docs(),
+            jobs_with_salary: salaries.len(),
+            top_companies: top(companies.into_values()),
+            top_tags: top(tags),
+            median_salary: median(&mut salaries),
+            scraped_last_7_days,
+            computed_at: now,
+        })
+    }
+}
+
+/// The `TOP_N` largest counts, ties broken by name so the order is stable
+fn top(counts: impl Iterator<Item = NameCount>) -> Vec<NameCount> {
+    let mut counts: Vec<NameCount> = counts.collect();
+    counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+    counts.truncate(TOP_N);
+    counts
+}
+
+/// Middle value, or the mean of the two middle values for an even count
+fn median(values: &mut [i64]) -> Option<i64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_unstable();
+    let middle = values.len() / 2;
+    if values.len() % 2 == 1 {
+        Some(values[middle])
+    } else {
+        Some((values[middle - 1] + values[middle]) / 2)
+    }
+}
+
+/// The last computed dashboard, reused until it's `ttl` old
+pub struct DashboardCache {
+    ttl: Duration,
+    cached: Option<(Instant, Dashboard)>,
+}
+
+impl DashboardCache {
+    pub fn new(ttl: Duration) -> Self {
+        DashboardCache { ttl, cached: None }
+    }
+
+    /// The cached dashboard, if computed less than `ttl` before `now`
+    pub fn get(&self, now: Instant) -> Option<&Dashboard> {
+        self.cached
+            .as_ref()
+            .filter(|(computed, _)| now.duration_since(*computed) < self.ttl)
+            .map(|(_, dashboard)| dashboard)
+    }
+
+    pub fn insert(&mut self, dashboard: Dashboard, now: Instant) {
+        self.cached = Some((now, dashboard));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_median_and_top() {
+        assert_eq!(median(&mut []), None);
+        assert_eq!(median(&mut [300, 100, 200]), Some(200));
+        assert_eq!(median(&mut [400, 100, 200, 300]), Some(250));
+
+        let counts = [("b", 2), ("a", 2), ("c", 5), ("d", 1), ("e", 1), ("f", 1)]
+            .into_iter()
+            .map(|(name, count)| NameCount {
+                name: name.to_string(),
+                count,
+            });
+        let names: Vec<String> = top(counts).into_iter().map(|c| c.name).collect();
+        assert_eq!(names, ["c", "a", "b", "d", "e"]);
+    }
+}
@@ -0,0 +1,273 @@
+//! Facets over the whole result set
+//!
+//! The response only carries one page of results, but a salary slider has to
+//! be bounded by every match. The bounds are collected from the `salary_min`
+//! fast field while the query runs over all of its matches. Counts per
+//! company and location come from their facet fields the same way.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::ops::Bound;
+use tantivy::{
+    DocId, Score, Searcher, SegmentOrdinal, SegmentReader,
+    collector::{Collector, FacetCollector, FacetCounts, SegmentCollector},
+    fastfield::Column,
+    query::{BooleanQuery, Occur, Query, RangeQuery},
+    schema::{Facet, Field},
+};
+use utoipa::ToSchema;
+
+/// Lowest and highest listed minimum salary among the matches
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct SalaryBounds {
+    pub min: i64,
+    pub max: i64,
+}
+
+impl SalaryBounds {
+    /// Converts bounds in US dollars into a currency worth `usd_rate` dollars
+    /// a unit. Rounded outwards, so a salary filter set to the bounds (and
+    /// converted back) still keeps every match.
+    pub fn in_currency(self, usd_rate: f64) -> SalaryBounds {
+        SalaryBounds {
+            min: (self.min as f64 / usd_rate).floor() as i64,
+            max: (self.max as f64 / usd_rate).ceil() as i64,
+        }
+    }
+}
+
+/// Matches per company and per location, each the most common ones only
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct FieldFacets {
+    pub company: BTreeMap<String, u64>,
+    pub location: BTreeMap<String, u64>,
+}
+
+/// Smallest and largest value of an i64 fast field over the collected docs.
+/// A doc without a value reads as 0, so the query has to leave those out.
+pub struct MinMaxCollector {
+    field: Field,
+}
+
+impl Collector for MinMaxCollector {
+    type Fruit = Option<(i64, i64)>;
+    type Child = MinMaxSegmentCollector;
+
+    fn for_segment(
+        &self,
+        _segment_local_id: SegmentOrdinal,
+        segment: &SegmentReader,
+    ) -> tantivy::Result<Self::Child> {
+        Ok(MinMaxSegmentCollector {
+            column: segment.fast_fields().i64(self.field)?,
+            bounds: None,
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(&self, fruits: Vec<Option<(i64, i64)>>) -> tantivy::Result<Self::Fruit> {
+        Ok(fruits.into_iter().flatten().reduce(widen))
+    }
+}
+
+pub struct MinMaxSegmentCollector {
+    column: std::sync::Arc<dyn Column<i64>>,
+    bounds: Option<(i64, i64)>,
+}
+
+impl SegmentCollector for MinMaxSegmentCollector {
+    type Fruit = Option<(i64, i64)>;
+
+    fn collect(&mut self, doc: DocId, _score: Score) {
+        let value = self.column.get_val(doc);
+        self.bounds = Some(
+            self.bounds
+                .map_or((value, value), |b| widen(b, (value, value))),
+        );
+    }
+
+    fn harvest(self) -> Self::Fruit {
+        self.bounds
+    }
+}
+
+/// The smallest range covering both `a` and `b`
+fn widen(a: (i64, i64), b: (i64, i64)) -> (i64, i64) {
+    (a.0.min(b.0), a.1.max(b.1))
+}
+
+/// The matches of `query` that have a value in the i64 field `field`
+fn with_value(query: &dyn Query, field: Field) -> BooleanQuery {
+    BooleanQuery::new(vec![
+        (Occur::Must, query.box_clone()),
+        (
+            Occur::Must,
+            Box::new(RangeQuery::new_i64_bounds(
+                field,
+                Bound::Unbounded,
+                Bound::Unbounded,
+            )),
+        ),
+    ])
+}
+
+/// Salary bounds over every job `query` matches that lists a salary; None
+/// when none of them does
+pub fn salary_bounds(
+    searcher: &Searcher,
+    query: &dyn Query,
+    salary_field: Field,
+) -> tantivy::Result<Option<SalaryBounds>> {
+    let with_salary = with_value(query, salary_field);
+    let bounds = searcher.search(
+        &with_salary,
+        &MinMaxCollector {
+            field: salary_field,
+        },
+    )?;
+    Ok(bounds.map(|(min, max)| SalaryBounds { min, max }))
+}
+
+/// Counts every job `query` matches by company key and by location, keeping
+/// the `limit` most common values of each. Jobs without a company or
+/// location aren't counted under it.
+pub fn field_facets(
+    searcher: &Searcher,
+    query: &dyn Query,
+    company_field: Field,
+    location_field: Field,
+    limit: usize,
+) -> tantivy::Result<FieldFacets> {
+    let collector = |field: Field| {
+        let mut collector = FacetCollector::for_field(field);
+        collector.add_facet(Facet::root());
+        collector
+    };
+    let (companies, locations) = searcher.search(
+        query,
+        &(collector(company_field), collector(location_field)),
+    )?;
+    let top = |counts: &FacetCounts| -> BTreeMap<String, u64> {
+        counts
+            .top_k(Facet::root(), limit)
+            .into_iter()
+            .filter_map(|(facet, count)| {
+                let name = facet.to_path().first()?.to_string();
+                Some((name, count))
+            })
+            .collect()
+    };
+    Ok(FieldFacets {
+        company: top(&companies),
+        location: top(&locations),
+    })
+}
+
+/// One bar of a salary histogram: jobs whose `salary_min` is at least
+/// `from` and below `to`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct SalaryBucket {
+    pub from: i64,
+    pub to: i64,
+    pub count: u64,
+}
+
+/// Counts the collected docs per `width`-wide range of an i64 fast field,
+/// keyed by the start of each range. A doc without a value reads as 0, so
+/// the query has to leave those out.
+pub struct HistogramCollector {
+    field: Field,
+    width: i64,
+}
+
+impl Collector for HistogramCollector {
+    type Fruit = BTreeMap<i64, u64>;
+    type Child = HistogramSegmentCollector;
+
+    fn for_segment(
+        &self,
+        _segment_local_id: SegmentOrdinal,
+        segment: &SegmentReader,
+    ) -> tantivy::Result<Self::Child> {
+        Ok(HistogramSegmentCollector {
+            column: segment.fast_fields().i64(self.field)?,
+            width: self.width,
+            counts: BTreeMap::new(),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(&self, fruits: Vec<BTreeMap<i64, u64>>) -> tantivy::Result<Self::Fruit> {
+        let mut merged = BTreeMap::new();
+        for (start, count) in fruits.into_iter().flatten() {
+            *merged.entry(start).or_default() += count;
+        }
+        Ok(merged)
+    }
+}
+
+pub struct HistogramSegmentCollector {
+    column: std::sync::Arc<dyn Column<i64>>,
+    width: i64,
+    counts: BTreeMap<i64, u64>,
+}
+
+impl SegmentCollector for HistogramSegmentCollector {
+    type Fruit = BTreeMap<i64, u64>;
+
+    fn collect(&mut self, doc: DocId, _score: Score) {
+        let value = self.column.get_val(doc);
+        let start = value.div_euclid(self.width) * self.width;
+        *self.counts.entry(start).or_default() += 1;
+    }
+
+    fn harvest(self) -> Self::Fruit {
+        self.counts
+    }
+}
+
+/// Histogram of the i64 fast field `salary_field` (the dollar `salary_min`)
+/// in `width`-wide buckets over every job `query` matches that has a value, from the lowest bucket with a job to the
+/// highest, empty ones in between included. None when that would take more
+/// than `max_buckets` buckets.
+pub fn salary_histogram(
+    searcher: &Searcher,
+    query: &dyn Query,
+    salary_field: Field,
+    width: i64,
+    max_buckets: usize,
+) -> tantivy::Result<Option<Vec<SalaryBucket>>> {
+    let with_salary = with_value(query, salary_field);
+    let counts = searcher.search(
+        &with_salary,
+        &HistogramCollector {
+            field: salary_field,
+            width,
+        },
+    )?;
+    let (Some(&first), Some(&last)) = (counts.keys().next(), counts.keys().next_back()) else {
+        return Ok(Some(Vec::new()));
+    };
+    let buckets = (last - first) / width + 1;
+    if buckets > max_buckets as i64 {
+        return Ok(None);
+    }
+    Ok(Some(
+        (0..buckets)
+            .map(|i| {
+                let from = first + i * width;
+                SalaryBucket {
+                    from,
+                    to: from + width,
+                    count: counts.get(&from).copied().unwrap_or(0),
+                }
+            })
+            .collect(),
+    ))
+}
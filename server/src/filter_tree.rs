@@ -0,0 +1,307 @@
+//! Nested boolean filters
+//!
+//! Flat query parameters can't say "(rust OR go) AND senior AND NOT
+//! contract". The `POST /search` body can, as a tree of `and` / `or` / `not`
+//! nodes over leaf conditions, compiled into one Tantivy `BooleanQuery` that
+//! every hit must match:
+//!
+//! ```json
+//! {"and": [
+//!     {"or": [{"field": "title", "term": "rust"}, {"field": "title", "term": "go"}]},
+//!     {"field": "title", "term": "senior"},
+//!     {"not": {"field": "contract_type", "term": "contract"}},
+//!     {"field": "salary_min", "gte": 100000}
+//! ]}
+//! ```
+
+use crate::filters::Filters;
+use crate::index::JobFields;
+use common::{ContractType, parse_country_code};
+use serde::Deserialize;
+use std::ops::Bound;
+use tantivy::query::{AllQuery, BooleanQuery, Occur, PhraseQuery, Query, RangeQuery, TermQuery};
+use tantivy::schema::{Field, IndexRecordOption};
+use tantivy::{Index, Term};
+use utoipa::ToSchema;
+
+/// Deepest nesting accepted; a single leaf is depth 1
+pub const MAX_FILTER_DEPTH: usize = 4;
+
+/// Most nodes (groups and leaves together) accepted in one tree
+pub const MAX_FILTER_NODES: usize = 64;
+
+/// One node of a filter tree, told apart by its keys. Each kind rejects
+/// keys it doesn't know, so a misspelt or mixed-up node is an error rather
+/// than a looser match.
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+#[serde(untagged)]
+pub enum FilterNode {
+    And(AndNode),
+    Or(OrNode),
+    Not(NotNode),
+    Term(TermNode),
+    Range(RangeNode),
+}
+
+/// Matches when every child does
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct AndNode {
+    pub and: Vec<FilterNode>,
+}
+
+/// Matches when any child does
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct OrNode {
+    pub or: Vec<FilterNode>,
+}
+
+/// Matches when the child doesn't
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct NotNode {
+    pub not: Box<FilterNode>,
+}
+
+/// Text fields (`title`, `company`, `description`) contain the words, in
+/// order; `contract_type` or `country` equal the value
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct TermNode {
+    pub field: String,
+    pub term: String,
+}
+
+/// A salary field (`salary_min`, `salary_max`) lies within the bounds
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct RangeNode {
+    pub field: String,
+    pub gte: Option<i64>,
+    pub lte: Option<i64>,
+}
+
+impl FilterNode {
+    /// Nesting depth of the tree below and including this node
+    fn depth(&self) -> usize {
+        match self {
+            FilterNode::And(AndNode { and: children })
+            | FilterNode::Or(OrNode { or: children }) => {
+                1 + children.iter().map(FilterNode::depth).max().unwrap_or(0)
+            }
+            FilterNode::Not(NotNode { not }) => 1 + not.depth(),
+            FilterNode::Term(_) | FilterNode::Range(_) => 1,
+        }
+    }
+
+    /// Number of nodes in the tree
+    fn size(&self) -> usize {
+        match self {
+            FilterNode::And(AndNode { and: children })
+            | FilterNode::Or(OrNode { or: children }) => {
+                1 + children.iter().map(FilterNode::size).sum::<usize>()
+            }
+            FilterNode::Not(NotNode { not }) => 1 + not.size(),
+            FilterNode::Term(_) | FilterNode::Range(_) => 1,
+        }
+    }
+}
+
+/// Checks a tree's size and compiles it into a query. Text leaves are
+/// analyzed with their field's tokenizer, as the query parser would.
+pub fn compile(
+    node: &FilterNode,
+    index: &Index,
+    fields: &JobFields,
+) -> Result<Box<dyn Query>, String> {
+    if node.depth() > MAX_FILTER_DEPTH {
+        return Err(format!(
+            "filter is nested {} levels deep; at most {} are allowed",
+            node.depth(),
+            MAX_FILTER_DEPTH
+        ));
+    }
+    if node.size() > MAX_FILTER_NODES {
+        return Err(format!(
+            "filter has {} conditions and groups; at most {} are allowed",
+            node.size(),
+            MAX_FILTER_NODES
+        ));
+    }
+    compile_node(node, index, fields)
+}
+
+fn compile_node(
+    node: &FilterNode,
+    index: &Index,
+    fields: &JobFields,
+) -> Result<Box<dyn Query>, String> {
+    match node {
+        FilterNode::And(AndNode { and: children }) => {
+            group(children, Occur::Must, "and", index, fields)
+        }
+        FilterNode::Or(OrNode { or: children }) => {
+            group(children, Occur::Should, "or", index, fields)
+        }
+        FilterNode::Not(NotNode { not }) => {
+            // A query of only exclusions matches nothing, so exclude from everything
+            let all: Box<dyn Query> = Box::new(AllQuery);
+            Ok(Box::new(BooleanQuery::new(vec![
+                (Occur::Must, all),
+                (Occur::MustNot, compile_node(not, index, fields)?),
+            ])))
+        }
+        FilterNode::Term(TermNode { field, term }) => term_query(field, term, index, fields),
+        FilterNode::Range(RangeNode { field, gte, lte }) => {
+            let field_handle = match field.as_str() {
+                "salary_min" => fields.salary_min,
+                "salary_max" => fields.salary_max,
+                _ => {
+                    return Err(format!(
+                        "unknown range field {:?}: expected salary_min or salary_max",
+                        field
+                    ));
+                }
+            };
+            if gte.is_none() && lte.is_none() {
+                return Err(format!("range on {} needs gte or lte", field));
+            }
+            let bound = |value: &Option<i64>| value.map_or(Bound::Unbounded, Bound::Included);
+            Ok(Box::new(RangeQuery::new_i64_bounds(
+                field_handle,
+                bound(gte),
+                bound(lte),
+            )))
+        }
+    }
+}
+
+/// Combines compiled children with `occur`; an empty group is an error
+fn group(
+    children: &[FilterNode],
+    occur: Occur,
+    name: &str,
+    index: &Index,
+    fields: &JobFields,
+) -> Result<Box<dyn Query>, String> {
+    if children.is_empty() {
+        return Err(format!("`{}` needs at least one condition", name));
+    }
+    let clauses = children
+        .iter()
+        .map(|child| Ok((occur, compile_node(child, index, fields)?)))
+        .collect::<Result<Vec<_>, String>>()?;
+    Ok(Box::new(BooleanQuery::new(clauses)))
+}
+
+/// Query for a `{"field": ..., "term": ...}` leaf
+fn term_query(
+    field: &str,
+    term: &str,
+    index: &Index,
+    fields: &JobFields,
+) -> Result<Box<dyn Query>, String> {
+    let text_field = match field {
+        "title" => fields.title,
+        "company" => fields.company,
+        "description" => fields.description,
+        "location" => fields.location,
+        "contract_type" | "country" => {
+            // Same matching as the flat parameters, worldwide jobs included for country
+            let filters = match field {
+                "contract_type" => Filters {
+                    contract_type: Some(term.parse::<ContractType>()?),
+                    ..Filters::default()
+                },
+                _ => Filters {
+                    country: Some(parse_country_code(term)?),
+                    ..Filters::default()
+                },
+            };
+            return Ok(Box::new(BooleanQuery::new(filters.clauses(fields))));
+        }
+        _ => {
+            return Err(format!(
+                "unknown term field {:?}: expected title, company, description, location, contract_type or country",
+                field
+            ));
+        }
+    };
+    text_query(text_field, field, term, index)
+}
+
+/// Matches the words of `text` in order in a text field
+fn text_query(
+    field: Field,
+    name: &str,
+    text: &str,
+    index: &Index,
+) -> Result<Box<dyn Query>, String> {
+    let analyzer = index
+        .tokenizer_for_field(field)
+        .map_err(|e| format!("cannot analyze {}: {}", name, e))?;
+    let mut terms = Vec::new();
+    analyzer
+        .token_stream(text)
+        .process(&mut |token| terms.push(Term::from_field_text(field, &token.text)));
+    match terms.len() {
+        0 => Err(format!(
+            "term {:?} for {} has no searchable words",
+            text, name
+        )),
+        1 => Ok(Box::new(TermQuery::new(
+            terms.remove(0),
+            IndexRecordOption::Basic,
+        ))),
+        _ => Ok(Box::new(PhraseQuery::new(terms))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_depth_and_size_are_limited() {
+        let leaf = || {
+            FilterNode::Term(TermNode {
+                field: "title".to_string(),
+                term: "rust".to_string(),
+            })
+        };
+        let mut deep = leaf();
+        for _ in 0..MAX_FILTER_DEPTH {
+            deep = FilterNode::Not(NotNode {
+                not: Box::new(deep),
+            });
+        }
+        assert_eq!(deep.depth(), MAX_FILTER_DEPTH + 1);
+
+        let wide = FilterNode::Or(OrNode {
+            or: (0..MAX_FILTER_NODES).map(|_| leaf()).collect(),
+        });
+        assert_eq!(wide.size(), MAX_FILTER_NODES + 1);
+        assert_eq!(wide.depth(), 2);
+    }
+
+    #[test]
+    fn test_filter_nodes_reject_unknown_keys() {
+        let parse = |json: &str| serde_json::from_str::<FilterNode>(json);
+
+        let node = parse(r#"{"not": {"field": "salary_min", "gte": 100000}}"#).unwrap();
+        assert!(
+            matches!(&node, FilterNode::Not(NotNode { not }) if matches!(**not, FilterNode::Range(_)))
+        );
+
+        for json in [
+            // A misspelt bound, which would otherwise leave an open range
+            r#"{"field": "salary_min", "gte": 100000, "lt": 150000}"#,
+            r#"{"field": "title", "term": "rust", "gte": 5}"#,
+            r#"{"and": [], "or": []}"#,
+            r#"{"not": {"field": "title", "term": "go"}, "note": "no go"}"#,
+        ] {
+            assert!(parse(json).is_err(), "{} should be rejected", json);
+        }
+    }
+}
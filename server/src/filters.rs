@@ -0,0 +1,405 @@
+//! Structured search filters
+//!
+//! Filters narrow the keyword query down with exact conditions on indexed
+//! fields. Each one becomes a `Must` clause next to the keyword query.
+
+use crate::index::{JobFields, company_key};
+use common::{
+    ContractType, MAX_UTC_OFFSET, MIN_UTC_OFFSET, WORLDWIDE, parse_timestamp, to_usd, usd_rate,
+};
+use std::ops::Bound;
+use tantivy::Term;
+use tantivy::query::{BooleanQuery, EmptyQuery, Occur, PhraseQuery, Query, RangeQuery, TermQuery};
+use tantivy::schema::IndexRecordOption;
+
+/// Requested salary bounds: at least, at most (either may be open)
+pub type SalaryRange = (Option<i64>, Option<i64>);
+
+/// Filters requested alongside the keywords
+#[derive(Debug, Default)]
+pub struct Filters {
+    /// Searcher's UTC offset in hours; keeps jobs whose band includes it
+    pub timezone: Option<i32>,
+    /// Minimum salary bounds, from a `salary_bucket` label
+    pub salary: Option<(Bound<i64>, Bound<i64>)>,
+    /// Requested salary range; keeps jobs whose own range overlaps it
+    pub salary_range: Option<SalaryRange>,
+    /// The salary range is in US dollars and is compared with each job's
+    /// range converted to dollars, rather than with the listed figures
+    pub salary_in_usd: bool,
+    /// Keeps jobs dated after this unix time (undated jobs are left out)
+    pub posted_after: Option<i64>,
+    /// Keeps jobs scraped, or else added to the index, after this unix time
+    pub scraped_after: Option<i64>,
+    /// Drops jobs whose application deadline is before this unix time
+    /// (jobs without a deadline are kept)
+    pub expired_before: Option<i64>,
+    /// Keeps jobs with this contract type (unclassified jobs are left out)
+    pub contract_type: Option<ContractType>,
+    /// Keeps jobs that do (true) or explicitly don't (false) sponsor visas;
+    /// jobs that don't say are left out either way
+    pub visa: Option<bool>,
+    /// Keeps jobs that can be done from this ISO country code, worldwide ones included
+    pub country: Option<String>,
+    /// Keeps jobs whose location text contains these words in order,
+    /// ignoring case and punctuation ("europe" matches "Europe Only")
+    pub location: Option<String>,
+    /// Keeps only this company's jobs (exact name, ignoring case)
+    pub company: Option<String>,
+    /// Nested and/or/not filter from a `POST /search` body, already compiled
+    pub expression: Option<Box<dyn Query>>,
+}
+
+impl Filters {
+    /// True when no filter is set
+    pub fn is_empty(&self) -> bool {
+        self.timezone.is_none()
+            && self.salary.is_none()
+            && self.salary_range.is_none()
+            && self.posted_after.is_none()
+            && self.scraped_after.is_none()
+            && self.expired_before.is_none()
+            && self.contract_type.is_none()
+            && self.visa.is_none()
+            && self.country.is_none()
+            && self.location.is_none()
+            && self.company.is_none()
+            && self.expression.is_none()
+    }
+
+    /// Query clauses that every result must match
+    pub fn clauses(&self, fields: &JobFields) -> Vec<(Occur, Box<dyn Query>)> {
+        let mut clauses: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+
+        if let Some(offset) = self.timezone {
+            // Band overlaps the offset: starts at or before it and ends at or after it.
+            // Worldwide jobs span every offset, so they always match.
+            let offset = i64::from(offset);
+            clauses.push((
+                Occur::Must,
+                Box::new(RangeQuery::new_i64_bounds(
+                    fields.timezone_min,
+                    Bound::Unbounded,
+                    Bound::Included(offset),
+                )),
+            ));
+            clauses.push((
+                Occur::Must,
+                Box::new(RangeQuery::new_i64_bounds(
+                    fields.timezone_max,
+                    Bound::Included(offset),
+                    Bound::Unbounded,
+                )),
+            ));
+        }
+
+        if let Some((lower, upper)) = self.salary {
+            clauses.push((
+                Occur::Must,
+                Box::new(RangeQuery::new_i64_bounds(fields.salary_min, lower, upper)),
+            ));
+        }
+
+        if let Some((at_least, at_most)) = self.salary_range {
+            // Ranges overlap when the job's top reaches the requested bottom and
+            // its bottom is within the requested top. A job without a maximum
+            // is indexed with its minimum as the top.
+            let (salary_min, salary_max) = if self.salary_in_usd {
+                (fields.salary_min_usd, fields.salary_max_usd)
+            } else {
+                (fields.salary_min, fields.salary_max)
+            };
+            if let Some(at_least) = at_least {
+                clauses.push((
+                    Occur::Must,
+                    Box::new(RangeQuery::new_i64_bounds(
+                        salary_max,
+                        Bound::Included(at_least),
+                        Bound::Unbounded,
+                    )),
+                ));
+            }
+            if let Some(at_most) = at_most {
+                clauses.push((
+                    Occur::Must,
+                    Box::new(RangeQuery::new_i64_bounds(
+                        salary_min,
+                        Bound::Unbounded,
+                        Bound::Included(at_most),
+                    )),
+                ));
+            }
+        }
+
+        if let Some(after) = self.posted_after {
+            clauses.push((
+                Occur::Must,
+                Box::new(RangeQuery::new_i64_bounds(
+                    fields.date,
+                    Bound::Excluded(after),
+                    Bound::Unbounded,
+                )),
+            ));
+        }
+
+        if let Some(after) = self.scraped_after {
+            clauses.push((
+                Occur::Must,
+                Box::new(RangeQuery::new_i64_bounds(
+                    fields.scraped_at,
+                    Bound::Excluded(after),
+                    Bound::Unbounded,
+                )),
+            ));
+        }
+
+        if let Some(now) = self.expired_before {
+            clauses.push((
+                Occur::MustNot,
+                Box::new(RangeQuery::new_i64_bounds(
+                    fields.deadline,
+                    Bound::Unbounded,
+                    Bound::Excluded(now),
+                )),
+            ));
+        }
+
+        if let Some(contract_type) = self.contract_type {
+            clauses.push((
+                Occur::Must,
+                Box::new(TermQuery::new(
+                    Term::from_field_text(fields.contract_type, contract_type.as_str()),
+                    IndexRecordOption::Basic,
+                )),
+            ));
+        }
+
+        if let Some(sponsors) = self.visa {
+            clauses.push((
+                Occur::Must,
+                Box::new(TermQuery::new(
+                    Term::from_field_text(fields.visa, &sponsors.to_string()),
+                    IndexRecordOption::Basic,
+                )),
+            ));
+        }
+
+        if let Some(country) = &self.country {
+            let either: Vec<(Occur, Box<dyn Query>)> = [country.as_str(), WORLDWIDE]
+                .into_iter()
+                .map(|code| {
+                    let query: Box<dyn Query> = Box::new(TermQuery::new(
+                        Term::from_field_text(fields.country, code),
+                        IndexRecordOption::Basic,
+                    ));
+                    (Occur::Should, query)
+                })
+                .collect();
+            clauses.push((Occur::Must, Box::new(BooleanQuery::new(either))));
+        }
+
+        if let Some(location) = &self.location {
+            // Split and lowercased like the default tokenizer the field is indexed with
+            let mut terms: Vec<Term> = location
+                .split(|c: char| !c.is_alphanumeric())
+                .filter(|word| !word.is_empty())
+                .map(|word| Term::from_field_text(fields.location, &word.to_lowercase()))
+                .collect();
+            let query: Box<dyn Query> = match terms.len() {
+                0 => Box::new(EmptyQuery),
+                1 => Box::new(TermQuery::new(terms.remove(0), IndexRecordOption::Basic)),
+                _ => Box::new(PhraseQuery::new(terms)),
+            };
+            clauses.push((Occur::Must, query));
+        }
+
+        if let Some(company) = &self.company {
+            clauses.push((
+                Occur::Must,
+                Box::new(TermQuery::new(
+                    Term::from_field_text(fields.company_exact, &company_key(company)),
+                    IndexRecordOption::Basic,
+                )),
+            ));
+        }
+
+        if let Some(expression) = &self.expression {
+            clauses.push((Occur::Must, expression.box_clone()));
+        }
+
+        clauses
+    }
+}
+
+/// Salary bucket labels for UI filter chips, and the `salary_min` range each covers
+const SALARY_BUCKETS: &[(&str, Bound<i64>, Bound<i64>)] = &[
+    ("lt50k", Bound::Unbounded, Bound::Excluded(50_000)),
+    (
+        "50to100k",
+        Bound::Included(50_000),
+        Bound::Excluded(100_000),
+    ),
+    (
+        "100to150k",
+        Bound::Included(100_000),
+        Bound::Excluded(150_000),
+    ),
+    ("gt150k", Bound::Included(150_000), Bound::Unbounded),
+];
+
+/// Looks up a `salary_bucket` label
+pub fn parse_salary_bucket(label: &str) -> Result<(Bound<i64>, Bound<i64>), String> {
+    SALARY_BUCKETS
+        .iter()
+        .find(|(name, _, _)| *name == label.trim())
+        .map(|(_, lower, upper)| (*lower, *upper))
+        .ok_or_else(|| {
+            let names: Vec<&str> = SALARY_BUCKETS.iter().map(|(name, _, _)| *name).collect();
+            format!(
+                "unknown salary_bucket {:?}: expected one of {}",
+                label,
+                names.join(", ")
+            )
+        })
+}
+
+/// Checks the `salary_min`/`salary_max` parameters, returning None when neither is set
+pub fn parse_salary_range(
+    at_least: Option<i64>,
+    at_most: Option<i64>,
+) -> Result<Option<SalaryRange>, String> {
+    match (at_least, at_most) {
+        (None, None) => Ok(None),
+        (Some(low), Some(high)) if low > high => {
+            Err(format!("salary_min {} is above salary_max {}", low, high))
+        }
+        range => Ok(Some(range)),
+    }
+}
+
+/// Converts a requested salary range given in `currency` to US dollars.
+/// An unsupported currency is an error even without a range.
+pub fn salary_range_in_usd(
+    range: Option<SalaryRange>,
+    currency: &str,
+) -> Result<Option<SalaryRange>, String> {
+    if usd_rate(currency).is_none() {
+        return Err(format!("unsupported currency {:?}", currency));
+    }
+    let convert = |amount: Option<i64>| amount.and_then(|amount| to_usd(amount, Some(currency)));
+    Ok(range.map(|(at_least, at_most)| (convert(at_least), convert(at_most))))
+}
+
+/// Parses a `timezone` parameter such as "+1", "-5" or "UTC+2" into whole hours.
+///
+/// A `+` in a URL query decodes to a space, so "timezone=+1" arrives as " 1".
+pub fn parse_timezone(value: &str) -> Result<i32, String> {
+    let value = value.trim();
+    let offset = value
+        .strip_prefix("UTC")
+        .or_else(|| value.strip_prefix("utc"))
+        .unwrap_or(value)
+        .trim();
+
+    match offset.parse::<i32>() {
+        Ok(hours) if (MIN_UTC_OFFSET..=MAX_UTC_OFFSET).contains(&hours) => Ok(hours),
+        _ => Err(format!(
+            "invalid timezone {:?}: expected a UTC offset in hours from {} to +{}",
+            value, MIN_UTC_OFFSET, MAX_UTC_OFFSET
+        )),
+    }
+}
+
+/// Parses a `posted_after` parameter: an RFC 3339 date-time, or a plain
+/// date such as 2024-05-01 meaning its start in UTC
+pub fn parse_posted_after(value: &str) -> Result<i64, String> {
+    let value = value.trim();
+    parse_timestamp(value)
+        .or_else(|| parse_timestamp(&format!("{}T00:00:00Z", value)))
+        .ok_or_else(|| {
+            format!(
+                "invalid posted_after {:?}: expected a date such as 2024-05-01 or an RFC 3339 date-time",
+                value
+            )
+        })
+}
+
+/// Parses a `posted_within` age such as "24h", "7d" or "2w" into the unix
+/// time that long before `now`
+pub fn parse_posted_within(value: &str, now: i64) -> Result<i64, String> {
+    let value = value.trim();
+    let invalid = || {
+        format!(
+            "invalid posted_within {:?}: expected a number of hours, days or weeks such as 24h, 7d or 2w",
+            value
+        )
+    };
+    let unit_secs = match value.chars().last() {
+        Some('h') => 3_600,
+        Some('d') => 86_400,
+        Some('w') => 7 * 86_400,
+        _ => return Err(invalid()),
+    };
+    let count: i64 = value[..value.len() - 1].parse().map_err(|_| invalid())?;
+    if count < 0 {
+        return Err(invalid());
+    }
+    Ok(now.saturating_sub(count.saturating_mul(unit_secs)))
+}
+
+/// Checks a `company` parameter names a company
+pub fn parse_company(value: &str) -> Result<String, String> {
+    if company_key(value).is_empty() {
+        Err("company must not be blank".to_string())
+    } else {
+        Ok(value.to_string())
+    }
+}
+
+/// Checks a `location` parameter has a word to match
+pub fn parse_location(value: &str) -> Result<String, String> {
+    if value.chars().any(char::is_alphanumeric) {
+        Ok(value.to_string())
+    } else {
+        Err(format!("location {:?} has no words to match", value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_timezone() {
+        assert_eq!(parse_timezone("+1"), Ok(1));
+        assert_eq!(parse_timezone(" 1"), Ok(1));
+        assert_eq!(parse_timezone("-5"), Ok(-5));
+        assert_eq!(parse_timezone("UTC+2"), Ok(2));
+        assert!(parse_timezone("+15").is_err());
+        assert!(parse_timezone("Europe").is_err());
+    }
+
+    #[test]
+    fn test_parse_salary_bucket() {
+        assert_eq!(
+            parse_salary_bucket("50to100k"),
+            Ok((Bound::Included(50_000), Bound::Excluded(100_000)))
+        );
+        let error = parse_salary_bucket("huge").unwrap_err();
+        assert!(
+            error.contains("lt50k, 50to100k, 100to150k, gt150k"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn test_parse_salary_range() {
+        assert_eq!(parse_salary_range(None, None), Ok(None));
+        assert_eq!(
+            parse_salary_range(Some(100_000), None),
+            Ok(Some((Some(100_000), None)))
+        );
+        assert!(parse_salary_range(Some(100_000), Some(50_000)).is_err());
+    }
+}
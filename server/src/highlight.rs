@@ -0,0 +1,191 @@
+//! Highlighting of matched terms in search results
+//!
+//! Tantivy's snippet generator only looks at query terms for the field it
+//! highlights, and its HTML output hardcodes `<b>`. Here the terms from every
+//! queried field are used for each highlighted field, so a word matched in the
+//! description is also marked in the title and company, and the markup comes
+//! from the server configuration.
+//!
+//! Clients that style matches themselves can ask for offsets instead: the
+//! character ranges of matched terms, found by running the field's tokenizer
+//! over the text.
+//!
+//! Each result also says how many of the distinct query terms it contains,
+//! read from the index's postings rather than the stored text.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use tantivy::{
+    DocAddress, DocSet, Score, Searcher, Snippet, SnippetGenerator, Term,
+    query::Query,
+    schema::{Field, IndexRecordOption},
+    tokenizer::TextAnalyzer,
+};
+use utoipa::ToSchema;
+
+/// Builds a snippet generator for `field` from the terms of `query`,
+/// whichever field they were searched in
+pub fn snippet_generator(
+    searcher: &Searcher,
+    query: &dyn Query,
+    field: Field,
+    max_num_chars: usize,
+) -> tantivy::Result<SnippetGenerator> {
+    let mut terms = Vec::new();
+    query.query_terms(&mut |term, _| terms.push(term.clone()));
+
+    // Rarer terms score higher, so the best fragment is the most specific one
+    let mut terms_text: BTreeMap<String, Score> = BTreeMap::new();
+    for term in terms {
+        if let Some(text) = term.as_str() {
+            let doc_freq = searcher.doc_freq(&term)?;
+            let score = 1.0 / (1.0 + doc_freq as Score);
+            let entry = terms_text.entry(text.to_string()).or_insert(0.0);
+            *entry = entry.max(score);
+        }
+    }
+
+    let tokenizer = searcher.index().tokenizer_for_field(field)?;
+    Ok(SnippetGenerator::new(
+        terms_text,
+        tokenizer,
+        field,
+        max_num_chars,
+    ))
+}
+
+/// Renders a snippet as HTML, wrapping each match in `pre_tag`/`post_tag`.
+/// Returns None when nothing in the snippet matched.
+pub fn render_snippet(snippet: &Snippet, pre_tag: &str, post_tag: &str) -> Option<String> {
+    if snippet.is_empty() {
+        return None;
+    }
+
+    let fragment = snippet.fragment();
+    let mut html = String::new();
+    let mut start = 0;
+    for range in snippet.highlighted() {
+        // Ranges are sorted; skip any that overlap one already written
+        if range.start < start {
+            continue;
+        }
+        html.push_str(&escape_html(&fragment[start..range.start]));
+        html.push_str(pre_tag);
+        html.push_str(&escape_html(&fragment[range.clone()]));
+        html.push_str(post_tag);
+        start = range.end;
+    }
+    html.push_str(&escape_html(&fragment[start..]));
+    Some(html)
+}
+
+/// Half-open range `[start, end)` of a matched term, counted in characters
+/// (Unicode scalar values), not bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct MatchRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Finds where the terms of a query occur in one field's text
+pub struct OffsetFinder {
+    terms: BTreeSet<String>,
+    tokenizer: TextAnalyzer,
+}
+
+impl OffsetFinder {
+    /// Collects the terms of `query`, whichever field they were searched in,
+    /// and the tokenizer `field` is indexed with
+    pub fn new(searcher: &Searcher, query: &dyn Query, field: Field) -> tantivy::Result<Self> {
+        let mut terms = BTreeSet::new();
+        query.query_terms(&mut |term, _| {
+            if let Some(text) = term.as_str() {
+                terms.insert(text.to_string());
+            }
+        });
+        let tokenizer = searcher.index().tokenizer_for_field(field)?;
+        Ok(OffsetFinder { terms, tokenizer })
+    }
+
+    /// Character ranges in `text` of the tokens that match a query term, in order
+    pub fn offsets(&self, text: &str) -> Vec<MatchRange> {
+        let mut ranges = Vec::new();
+        let mut stream = self.tokenizer.token_stream(text);
+        while stream.advance() {
+            let token = stream.token();
+            if self.terms.contains(&token.text) {
+                ranges.push((token.offset_from, token.offset_to));
+            }
+        }
+
+        // Tokens carry byte offsets; convert them to character offsets
+        let char_offset = |byte: usize| text[..byte].chars().count();
+        ranges
+            .into_iter()
+            .map(|(from, to)| MatchRange {
+                start: char_offset(from),
+                end: char_offset(to),
+            })
+            .collect()
+    }
+}
+
+/// Counts how many distinct query terms a document contains
+pub struct TermCounter {
+    /// Each distinct term text, with the keyword-field terms it was searched as
+    terms: BTreeMap<String, Vec<Term>>,
+}
+
+impl TermCounter {
+    /// Collects the terms of `query` searched in one of `fields`, so filter
+    /// terms aren't counted. None when there are no such terms.
+    pub fn new(query: &dyn Query, fields: &[Field]) -> Option<Self> {
+        let mut terms: BTreeMap<String, Vec<Term>> = BTreeMap::new();
+        query.query_terms(&mut |term, _| {
+            if let Some(text) = term.as_str().filter(|_| fields.contains(&term.field())) {
+                let field_terms = terms.entry(text.to_string()).or_default();
+                if !field_terms.contains(term) {
+                    field_terms.push(term.clone());
+                }
+            }
+        });
+        (!terms.is_empty()).then_some(TermCounter { terms })
+    }
+
+    /// Number of distinct terms found in any of the document's keyword fields
+    pub fn count(&self, searcher: &Searcher, doc_address: DocAddress) -> usize {
+        let segment_reader = searcher.segment_reader(doc_address.segment_ord);
+        let contains = |term: &Term| {
+            let Ok(inverted_index) = segment_reader.inverted_index(term.field()) else {
+                return false;
+            };
+            match inverted_index.read_postings(term, IndexRecordOption::Basic) {
+                // Postings are sorted, and `seek` can only move forward
+                Ok(Some(mut postings)) => {
+                    postings.doc() <= doc_address.doc_id
+                        && postings.seek(doc_address.doc_id) == doc_address.doc_id
+                }
+                _ => false,
+            }
+        };
+        self.terms
+            .values()
+            .filter(|field_terms| field_terms.iter().any(contains))
+            .count()
+    }
+}
+
+/// Escapes the characters that are significant in HTML text
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
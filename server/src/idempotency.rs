@@ -0,0 +1,89 @@
+//! Idempotency-Key support for write endpoints
+//!
+//! Remembers the response sent for each key so a client retrying a request
+//! (e.g. after a timeout) gets the original result instead of a second write.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Bounded, expiring map from idempotency key to the response first sent for it
+pub struct IdempotencyCache<T> {
+    entries: HashMap<String, (Instant, T)>,
+    ttl: Duration,
+    capacity: usize,
+}
+
+impl<T: Clone> IdempotencyCache<T> {
+    pub fn new(ttl: Duration, capacity: usize) -> Self {
+        IdempotencyCache {
+            entries: HashMap::new(),
+            ttl,
+            capacity,
+        }
+    }
+
+    /// Returns the stored response for `key` if it hasn't expired
+    pub fn get(&mut self, key: &str, now: Instant) -> Option<T> {
+        match self.entries.get(key) {
+            Some((stored_at, value)) if now.duration_since(*stored_at) < self.ttl => {
+                Some(value.clone())
+            }
+            Some(_) => {
+                self.entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Stores the response for `key`, evicting expired entries and then the
+    /// oldest ones if the cache is full
+    pub fn insert(&mut self, key: String, value: T, now: Instant) {
+        let ttl = self.ttl;
+        self.entries
+            .retain(|_, (stored_at, _)| now.duration_since(*stored_at) < ttl);
+
+        while self.entries.len() >= self.capacity {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (stored_at, _))| *stored_at)
+                .map(|(key, _)| key.clone());
+            match oldest {
+                Some(oldest) => self.entries.remove(&oldest),
+                None => break,
+            };
+        }
+
+        self.entries.insert(key, (now, value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_expires_after_ttl() {
+        let start = Instant::now();
+        let mut cache = IdempotencyCache::new(Duration::from_secs(60), 10);
+        cache.insert("key".to_string(), 1, start);
+
+        assert_eq!(cache.get("key", start + Duration::from_secs(30)), Some(1));
+        assert_eq!(cache.get("key", start + Duration::from_secs(61)), None);
+    }
+
+    #[test]
+    fn test_cache_evicts_oldest_when_full() {
+        let start = Instant::now();
+        let mut cache = IdempotencyCache::new(Duration::from_secs(60), 2);
+        cache.insert("a".to_string(), 1, start);
+        cache.insert("b".to_string(), 2, start + Duration::from_secs(1));
+        cache.insert("c".to_string(), 3, start + Duration::from_secs(2));
+
+        let now = start + Duration::from_secs(3);
+        assert_eq!(cache.get("a", now), None);
+        assert_eq!(cache.get("b", now), Some(2));
+        assert_eq!(cache.get("c", now), Some(3));
+    }
+}
@@ -0,0 +1,1310 @@
+//! Tantivy schema and indexing
+//!
+//! Defines how a `Job` maps onto index fields and writes jobs into the index
+//! as upserts keyed by URL and the configured dedup key, so an existing index
+//! is updated in place.
+
+use crate::config::Config;
+use crate::tokenizer::{
+    CJK_TOKENIZER, CjkTokenizer, DESCRIPTION_TOKENIZER, MaxTokensFilter, TECH_TOKENIZER,
+    TechTokenizer,
+};
+use common::{
+    DedupStrategy, Error, Job, TimezoneRange, classify_contract, countries_from_region,
+    detect_visa_sponsorship, format_timestamp, normalize_title, parse_timestamp, to_usd,
+};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tantivy::{
+    DocAddress, Document, Index, IndexWriter, ReloadPolicy, TantivyError, Term,
+    directory::error::LockError,
+    schema::{
+        Cardinality, Facet, FacetOptions, Field, IndexRecordOption, NumericOptions, STORED, STRING,
+        Schema, TextFieldIndexing, TextOptions,
+    },
+    tokenizer::{LowerCaser, NgramTokenizer, RemoveLongFilter, TextAnalyzer},
+};
+
+/// Tokenizer splitting text into lowercase 3-character grams, for substring search
+pub const TRIGRAM_TOKENIZER: &str = "trigram";
+
+/// Characters of a description kept in `description_excerpt`, enough for
+/// the longest snippet a search can ask for
+pub const DESCRIPTION_EXCERPT_CHARS: usize = 1_000;
+
+/// Text fields whose tokenizer can be set with `FIELD_TOKENIZERS`
+pub const CONFIGURABLE_FIELDS: [&str; 3] = ["title", "company", "description"];
+
+/// Tokenizers a text field can be given: Tantivy's built-in ones ("raw"
+/// keeps the whole value as one term) and the ones registered here
+pub const KNOWN_TOKENIZERS: [&str; 8] = [
+    "default",
+    "raw",
+    "whitespace",
+    "en_stem",
+    CJK_TOKENIZER,
+    DESCRIPTION_TOKENIZER,
+    TECH_TOKENIZER,
+    TRIGRAM_TOKENIZER,
+];
+
+/// Directory the server keeps its index in
+pub const INDEX_DIR: &str = "search_index";
+
+/// Heap given to each index writer (50MB)
+pub const WRITER_HEAP_BYTES: usize = 50_000_000;
+
+/// File in the index directory naming the process that last took the writer
+/// lock. Tantivy's own lock file is empty, so this is what says who holds it.
+const WRITER_OWNER_FILE: &str = ".writer-owner";
+
+/// First pause between attempts to take a busy writer lock; it doubles each time
+const LOCK_RETRY_START: Duration = Duration::from_millis(50);
+
+/// Longest pause between attempts to take a busy writer lock
+const LOCK_RETRY_MAX: Duration = Duration::from_secs(2);
+
+/// Options for a searchable text field analyzed with `tokenizer`
+fn text_options(tokenizer: &str) -> TextOptions {
+    TextOptions::default().set_indexing_options(
+        TextFieldIndexing::default()
+            .set_tokenizer(tokenizer)
+            .set_index_option(IndexRecordOption::WithFreqsAndPositions),
+    )
+}
+
+/// The `company_exact` term for a company name: trimmed and lowercased, so
+/// "Acme Corp" and "acme corp" name the same company
+pub fn company_key(company: &str) -> String {
+    company.trim().to_lowercase()
+}
+
+/// Checks `FIELD_TOKENIZERS` names a configurable field and a known tokenizer
+pub fn check_field_tokenizers(config: &Config) -> Result<(), Error> {
+    for (field, tokenizer) in &config.field_tokenizers {
+        if !CONFIGURABLE_FIELDS.contains(&field.as_str()) {
+            return Err(Error::Config(format!(
+                "FIELD_TOKENIZERS: unknown field {:?}, expected one of {}",
+                field,
+                CONFIGURABLE_FIELDS.join(", ")
+            )));
+        }
+        if !KNOWN_TOKENIZERS.contains(&tokenizer.as_str()) {
+            return Err(Error::Config(format!(
+                "FIELD_TOKENIZERS: unknown tokenizer {:?} for {}, expected one of {}",
+                tokenizer,
+                field,
+                KNOWN_TOKENIZERS.join(", ")
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Tokenizer for `field`: the configured one, or else `default`
+fn field_tokenizer<'a>(config: &'a Config, field: &str, default: &'a str) -> &'a str {
+    config
+        .field_tokenizers
+        .get(field)
+        .map_or(default, String::as_str)
+}
+
+/// Builds the Tantivy schema for job indexing
+pub fn build_schema(config: &Config) -> Schema {
+    let mut schema_builder = Schema::builder();
+
+    // Text fields use the default tokenizer unless CJK support is enabled
+    let tokenizer = if config.cjk_tokenizer {
+        CJK_TOKENIZER
+    } else {
+        "default"
+    };
+
+    // Title: searchable and stored (returned in results). Without CJK
+    // support its words keep symbols like the "++" of "C++"
+    let title_tokenizer = if config.cjk_tokenizer {
+        CJK_TOKENIZER
+    } else {
+        TECH_TOKENIZER
+    };
+    schema_builder.add_text_field(
+        "title",
+        text_options(field_tokenizer(config, "title", title_tokenizer)) | STORED,
+    );
+
+    // Normalized title: one term per role ("backend developer"), so
+    // differently worded listings of the same role can be grouped
+    schema_builder.add_text_field("title_normalized", STRING | STORED);
+
+    // Company: searchable and stored
+    schema_builder.add_text_field(
+        "company",
+        text_options(field_tokenizer(config, "company", tokenizer)) | STORED,
+    );
+
+    // Title and company trigrams: let "shop" match inside "Shopify".
+    // Every 3-character window is a term, so these grow the index noticeably.
+    let trigram_options = TextOptions::default().set_indexing_options(
+        TextFieldIndexing::default()
+            .set_tokenizer(TRIGRAM_TOKENIZER)
+            .set_index_option(IndexRecordOption::Basic),
+    );
+    schema_builder.add_text_field("title_ngram", trigram_options.clone());
+    schema_builder.add_text_field("company_ngram", trigram_options);
+
+    // Company name as one lowercase term, for scoping a search to one company
+    schema_builder.add_text_field("company_exact", STRING);
+
+    // Location: the board's region text ("USA Only", "Europe"), stored for
+    // results and tokenized for the `location` filter
+    schema_builder.add_text_field("location", text_options("default") | STORED);
+
+    // Company and location as one-level facets, for counting the matches of
+    // each in `facets`. The company is faceted by its `company_key`
+    // ("/stripe"), so facets group names the way `company=` and the
+    // dashboard do; the location by its text ("/Europe Only").
+    schema_builder.add_facet_field("company_key_facet", FacetOptions::default());
+    schema_builder.add_facet_field("location_facet", FacetOptions::default());
+
+    // Description: searchable, and only stored when asked for (saves space).
+    // Its tokenizer caps how much of it is indexed; the stored text is whole.
+    // A configured tokenizer replaces it, cap included.
+    let description_tokenizer = field_tokenizer(config, "description", DESCRIPTION_TOKENIZER);
+    let description_options = if config.store_descriptions {
+        text_options(description_tokenizer) | STORED
+    } else {
+        text_options(description_tokenizer)
+    };
+    schema_builder.add_text_field("description", description_options);
+
+    // Salary: indexed for filtering, a fast field for sorting, and stored
+    // so results can show it
+    let int_options = NumericOptions::default()
+        .set_indexed()
+        .set_fast(Cardinality::SingleValue);
+    schema_builder.add_i64_field("salary_min", int_options.clone().set_stored());
+
+    // Salary top: the listed maximum, or the minimum when there is none,
+    // so salary ranges can be matched by overlap
+    schema_builder.add_i64_field("salary_max", NumericOptions::default().set_indexed());
+
+    // Salary range converted to US dollars, so filters and the salary
+    // histogram can compare across currencies. Left out when the currency
+    // has no known rate.
+    schema_builder.add_i64_field(
+        "salary_min_usd",
+        NumericOptions::default()
+            .set_indexed()
+            .set_fast(Cardinality::SingleValue),
+    );
+    schema_builder.add_i64_field("salary_max_usd", NumericOptions::default().set_indexed());
+
+    // Date: posted date, or scrape time, as unix seconds (missing reads as 0)
+    schema_builder.add_i64_field("date", int_options.clone());
+
+    // Deadline: last moment to apply, as unix seconds; absent when the
+    // listing gives none, so undated jobs never count as expired
+    schema_builder.add_i64_field("deadline", NumericOptions::default().set_indexed());
+
+    // Scrape time as unix seconds, for counting recently scraped jobs and
+    // for evicting the oldest ones past `MAX_INDEXED_JOBS` (missing reads as 0)
+    schema_builder.add_i64_field("scraped_at", int_options.clone());
+
+    // Featured: 1 for listings the board promotes, 0 otherwise; read at
+    // query time for the optional featured boost
+    schema_builder.add_i64_field("featured", int_options);
+
+    // Timezone band: lowest and highest accepted UTC offset, for range filters
+    let offset_options = NumericOptions::default().set_indexed();
+    schema_builder.add_i64_field("timezone_min", offset_options.clone());
+    schema_builder.add_i64_field("timezone_max", offset_options);
+
+    // Countries: one untokenized ISO code per country ("US", "DE"), or "*"
+    // for worldwide jobs, for the country filter
+    schema_builder.add_text_field("country", STRING);
+
+    // Contract type: untokenized name such as "full-time", for exact filtering
+    schema_builder.add_text_field("contract_type", STRING);
+
+    // Visa sponsorship: "true" or "false" when the listing says, absent otherwise
+    schema_builder.add_text_field("visa", STRING);
+
+    // Tags: one lowercase term per tag, stored so they can be tallied
+    schema_builder.add_text_field("tag", STRING | STORED);
+
+    // URL: untokenized so a job can be replaced by its exact URL
+    schema_builder.add_text_field("url", STRING | STORED);
+
+    // ID: stable hash of the URL, so a single job can be addressed in the API
+    schema_builder.add_text_field("id", STRING | STORED);
+
+    // Dedup key under `DEDUP_STRATEGY`: a re-imported job replaces the one
+    // with the same key, as well as the one with the same URL
+    schema_builder.add_text_field("dedup_key", STRING);
+
+    // The description's start, stored when the description itself isn't, so
+    // results can still show and highlight some of it
+    if !config.store_descriptions {
+        schema_builder.add_text_field("description_excerpt", STORED);
+    }
+
+    // Source: the whole job as JSON, so the index can be exported back to
+    // jobs.json. Only kept alongside stored descriptions, which it duplicates.
+    if config.store_descriptions {
+        schema_builder.add_text_field("source", STORED);
+    }
+
+    schema_builder.build()
+}
+
+/// Handles to the schema fields a `Job` is written into
+pub struct JobFields {
+    pub title: Field,
+    pub title_normalized: Field,
+    pub company: Field,
+    pub title_ngram: Field,
+    pub company_ngram: Field,
+    pub company_exact: Field,
+    pub location: Field,
+    pub company_key_facet: Field,
+    pub location_facet: Field,
+    pub description: Field,
+    pub salary_min: Field,
+    pub salary_max: Field,
+    pub salary_min_usd: Field,
+    pub salary_max_usd: Field,
+    pub date: Field,
+    pub deadline: Field,
+    pub scraped_at: Field,
+    pub featured: Field,
+    pub timezone_min: Field,
+    pub timezone_max: Field,
+    pub country: Field,
+    pub contract_type: Field,
+    pub visa: Field,
+    pub tag: Field,
+    pub url: Field,
+    pub id: Field,
+    pub dedup_key: Field,
+    /// How `dedup_key` is computed; `url` unless set after `new`
+    pub dedup: DedupStrategy,
+    /// Most documents kept; None unless set after `new`
+    pub max_docs: Option<usize>,
+    /// Only present when descriptions are stored
+    pub source: Option<Field>,
+    /// Only present when descriptions aren't stored
+    pub description_excerpt: Option<Field>,
+}
+
+impl JobFields {
+    /// Handles to the fields of `schema`. An index built by an older version
+    /// may lack some; that is an error naming the first one missing.
+    pub fn new(schema: &Schema) -> Result<JobFields, Error> {
+        let field = |name: &str| {
+            schema.get_field(name).ok_or_else(|| {
+                Error::Index(format!(
+                    "the index has no {:?} field, so it was built by an older version; \
+                     restart with --rebuild-index to rebuild {}/ from the job files",
+                    name, INDEX_DIR
+                ))
+            })
+        };
+        Ok(JobFields {
+            title: field("title")?,
+            title_normalized: field("title_normalized")?,
+            company: field("company")?,
+            title_ngram: field("title_ngram")?,
+            company_ngram: field("company_ngram")?,
+            company_exact: field("company_exact")?,
+            location: field("location")?,
+            company_key_facet: field("company_key_facet")?,
+            location_facet: field("location_facet")?,
+            description: field("description")?,
+            salary_min: field("salary_min")?,
+            salary_max: field("salary_max")?,
+            salary_min_usd: field("salary_min_usd")?,
+            salary_max_usd: field("salary_max_usd")?,
+            date: field("date")?,
+            deadline: field("deadline")?,
+            scraped_at: field("scraped_at")?,
+            featured: field("featured")?,
+            timezone_min: field("timezone_min")?,
+            timezone_max: field("timezone_max")?,
+            country: field("country")?,
+            contract_type: field("contract_type")?,
+            visa: field("visa")?,
+            tag: field("tag")?,
+            url: field("url")?,
+            id: field("id")?,
+            dedup_key: field("dedup_key")?,
+            dedup: DedupStrategy::Url,
+            max_docs: None,
+            source: schema.get_field("source"),
+            description_excerpt: schema.get_field("description_excerpt"),
+        })
+    }
+
+    /// Fields of `schema`, deduplicated and capped as `config` says
+    pub fn with_config(schema: &Schema, config: &Config) -> Result<JobFields, Error> {
+        Ok(JobFields {
+            dedup: config.dedup_strategy,
+            max_docs: config.max_indexed_jobs,
+            ..JobFields::new(schema)?
+        })
+    }
+
+    /// Converts a job into an index document
+    pub fn document(&self, job: &Job) -> Document {
+        let mut doc = Document::new();
+        doc.add_text(self.title, &job.title);
+        doc.add_text(self.title_normalized, normalize_title(&job.title));
+        doc.add_text(self.title_ngram, &job.title);
+        // A missing company stays out of company search, filters and facets
+        if let Some(company) = job.known_company() {
+            doc.add_text(self.company, company);
+            doc.add_text(self.company_ngram, company);
+            doc.add_text(self.company_exact, company_key(company));
+            doc.add_facet(
+                self.company_key_facet,
+                Facet::from_path([company_key(company)]),
+            );
+        }
+        if !job.location.is_empty() {
+            doc.add_text(self.location, &job.location);
+            doc.add_facet(self.location_facet, Facet::from_path([job.location.trim()]));
+        }
+        doc.add_text(self.description, &job.description);
+        if let Some(salary) = job.salary_min {
+            doc.add_i64(self.salary_min, salary);
+        }
+        if let Some(salary) = job.salary_max.or(job.salary_min) {
+            doc.add_i64(self.salary_max, salary);
+        }
+        let currency = job.salary_currency.as_deref();
+        if let Some(salary) = job.salary_min.and_then(|s| to_usd(s, currency)) {
+            doc.add_i64(self.salary_min_usd, salary);
+        }
+        let top = job.salary_max.or(job.salary_min);
+        if let Some(salary) = top.and_then(|s| to_usd(s, currency)) {
+            doc.add_i64(self.salary_max_usd, salary);
+        }
+        if let Some(timestamp) = job.timestamp() {
+            doc.add_i64(self.date, timestamp);
+        }
+        if let Some(deadline) = job.deadline.as_deref().and_then(parse_timestamp) {
+            doc.add_i64(self.deadline, deadline);
+        }
+        // A job added without a scrape time (through the API or a CSV) is
+        // seen now, so eviction doesn't take it for the oldest
+        let scraped_at = job.scraped_at.as_deref().and_then(parse_timestamp);
+        doc.add_i64(self.scraped_at, scraped_at.unwrap_or_else(unix_now));
+        doc.add_i64(self.featured, i64::from(job.featured));
+        // Jobs imported without a band get one from their location text
+        let timezone = job
+            .timezone
+            .or_else(|| TimezoneRange::from_region(&job.location));
+        if let Some(timezone) = timezone {
+            doc.add_i64(self.timezone_min, timezone.min_offset.into());
+            doc.add_i64(self.timezone_max, timezone.max_offset.into());
+        }
+        // Likewise the countries
+        let countries = if job.countries.is_empty() {
+            countries_from_region(&job.location)
+        } else {
+            job.countries.clone()
+        };
+        for country in &countries {
+            doc.add_text(self.country, country);
+        }
+        // And the contract type from the title and description
+        let contract_type = job
+            .contract_type
+            .or_else(|| classify_contract(&format!("{} {}", job.title, job.description)));
+        if let Some(contract_type) = contract_type {
+            doc.add_text(self.contract_type, contract_type.as_str());
+        }
+        // And visa sponsorship, the same way
+        let visa_sponsorship = job
+            .visa_sponsorship
+            .or_else(|| detect_visa_sponsorship(&format!("{} {}", job.title, job.description)));
+        if let Some(sponsors) = visa_sponsorship {
+            doc.add_text(self.visa, sponsors.to_string());
+        }
+        for tag in &job.tags {
+            let tag = tag.trim().to_lowercase();
+            if !tag.is_empty() {
+                doc.add_text(self.tag, tag);
+            }
+        }
+        doc.add_text(self.url, &job.url);
+        doc.add_text(self.id, job.id());
+        doc.add_text(self.dedup_key, self.dedup.key(job));
+        if let Some(excerpt) = self.description_excerpt {
+            let text: String = job
+                .description
+                .chars()
+                .take(DESCRIPTION_EXCERPT_CHARS)
+                .collect();
+            doc.add_text(excerpt, text);
+        }
+        if let Some(source) = self.source {
+            // A Job always serializes
+            doc.add_text(source, serde_json::to_string(job).unwrap_or_default());
+        }
+        doc
+    }
+}
+
+/// Registers the custom tokenizers the schema may refer to
+pub fn register_tokenizers(index: &Index, config: &Config) {
+    let cjk = || {
+        TextAnalyzer::from(CjkTokenizer)
+            .filter(RemoveLongFilter::limit(40))
+            .filter(LowerCaser)
+    };
+    index.tokenizers().register(CJK_TOKENIZER, cjk());
+
+    let tech = || {
+        TextAnalyzer::from(TechTokenizer)
+            .filter(RemoveLongFilter::limit(40))
+            .filter(LowerCaser)
+    };
+    index.tokenizers().register(TECH_TOKENIZER, tech());
+
+    // Same words as the title (tech terms or CJK), capped
+    let mut description = if config.cjk_tokenizer { cjk() } else { tech() };
+    if let Some(limit) = config.max_description_tokens {
+        description = description.filter(MaxTokensFilter::limit(limit));
+    }
+    index
+        .tokenizers()
+        .register(DESCRIPTION_TOKENIZER, description);
+
+    let trigram = TextAnalyzer::from(NgramTokenizer::new(3, 3, false)).filter(LowerCaser);
+    index.tokenizers().register(TRIGRAM_TOKENIZER, trigram);
+}
+
+/// Current time in unix seconds
+pub(crate) fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as i64)
+}
+
+/// Wraps a Tantivy failure in the shared error type
+pub fn index_error(e: TantivyError) -> Error {
+    Error::Index(e.to_string())
+}
+
+/// How to take an index's writer. Tantivy allows one writer per index, so a
+/// second process opening one fails until the first lets go.
+#[derive(Debug, Clone, Default)]
+pub struct WriterLock {
+    /// Directory of the index, where the holder is recorded; None in memory
+    pub index_path: Option<PathBuf>,
+    /// How long to keep retrying while another writer holds the lock;
+    /// None fails straight away
+    pub wait: Option<Duration>,
+}
+
+impl WriterLock {
+    /// Opens a writer on `index`, retrying with backoff for up to `wait`
+    /// while the lock is busy. Fails with a message naming the holder, when known.
+    pub fn acquire(&self, index: &Index) -> Result<IndexWriter, Error> {
+        let started = Instant::now();
+        let mut pause = LOCK_RETRY_START;
+        loop {
+            match index.writer(WRITER_HEAP_BYTES) {
+                Ok(writer) => {
+                    self.record_owner();
+                    return Ok(writer);
+                }
+                Err(TantivyError::LockFailure(LockError::LockBusy, _)) => {
+                    let waited = started.elapsed();
+                    match self.wait {
+                        Some(wait) if waited < wait => {
+                            thread::sleep(pause.min(wait - waited));
+                            pause = (pause * 2).min(LOCK_RETRY_MAX);
+                        }
+                        _ => return Err(self.busy_error(waited)),
+                    }
+                }
+                Err(e) => return Err(index_error(e)),
+            }
+        }
+    }
+
+    /// Notes this process as the writer, for whoever finds the lock busy next
+    fn record_owner(&self) {
+        let Some(index_path) = &self.index_path else {
+            return;
+        };
+        let now = unix_now();
+        let owner = format!(
+            "{} (pid {}) since {}",
+            env!("CARGO_PKG_NAME"),
+            std::process::id(),
+            format_timestamp(now)
+        );
+        // Only used in error messages, so failing to write it isn't fatal
+        if let Err(e) = fs::write(index_path.join(WRITER_OWNER_FILE), owner) {
+            eprintln!("⚠️  Couldn't record the index writer: {}", e);
+        }
+    }
+
+    /// Explains a lock that stayed busy, and what to do about it
+    fn busy_error(&self, waited: Duration) -> Error {
+        let location = match &self.index_path {
+            Some(index_path) => format!("the index in {}", index_path.display()),
+            None => "the index".to_string(),
+        };
+        let holder = self
+            .index_path
+            .as_ref()
+            .and_then(|index_path| fs::read_to_string(index_path.join(WRITER_OWNER_FILE)).ok())
+            .map(|owner| owner.trim().to_string())
+            .filter(|owner| !owner.is_empty())
+            .map_or_else(
+                || "an unknown process".to_string(),
+                |owner| describe_owner(&owner),
+            );
+        let advice = match self.wait {
+            Some(_) => format!("still busy after waiting {} ms", waited.as_millis()),
+            None => "stop it, or retry with --wait-for-lock <SECS>".to_string(),
+        };
+        Error::Index(format!(
+            "{} is locked by another writer, {}: {}",
+            location, holder, advice
+        ))
+    }
+}
+
+/// Describes the recorded writer. The record is only overwritten by the next
+/// writer, so it may name a process that has since exited while something
+/// else (an older build, another tool) holds the lock; a dead one is called out.
+fn describe_owner(owner: &str) -> String {
+    let pid = owner
+        .split("(pid ")
+        .nth(1)
+        .and_then(|rest| rest.split(')').next())
+        .and_then(|pid| pid.parse::<u32>().ok());
+    match pid.and_then(process_running) {
+        Some(false) => format!(
+            "last recorded as {}, which is no longer running, so the record is stale",
+            owner
+        ),
+        _ => format!("last recorded as {}", owner),
+    }
+}
+
+/// Whether process `pid` is still running, where that can be told (from /proc)
+#[cfg(target_os = "linux")]
+fn process_running(pid: u32) -> Option<bool> {
+    Some(Path::new("/proc").join(pid.to_string()).exists())
+}
+
+/// Whether process `pid` is still running, where that can be told
+#[cfg(not(target_os = "linux"))]
+fn process_running(_pid: u32) -> Option<bool> {
+    None
+}
+
+/// Creates or opens the search index at `index_path` and upserts all jobs.
+/// Jobs already in an existing index are kept, so it never starts out empty.
+/// Analyzers can't change in place, so when the configured schema differs
+/// from the index's (say a changed `FIELD_TOKENIZERS`) this fails, unless
+/// `config.rebuild_index` is set: the index is then rebuilt from `jobs` plus
+/// the jobs the old index stored whole (with `STORE_DESCRIPTIONS`).
+/// `lock_wait` is how long to wait for another process's writer to finish.
+pub fn create_index(
+    index_path: &Path,
+    jobs: &[Job],
+    config: &Config,
+    lock_wait: Option<Duration>,
+) -> Result<Index, Error> {
+    check_field_tokenizers(config)?;
+    let schema = build_schema(config);
+    let lock = WriterLock {
+        index_path: Some(index_path.to_path_buf()),
+        wait: lock_wait,
+    };
+
+    // Replace an index built with another schema only when asked to
+    let mut kept_jobs = Vec::new();
+    if index_path.join("meta.json").exists() {
+        let existing = Index::open_in_dir(index_path).map_err(index_error)?;
+        if existing.schema() != schema {
+            let changed = changed_fields(&existing.schema(), &schema);
+            if !config.rebuild_index {
+                return Err(Error::Index(format!(
+                    "{} was built with a different schema or analyzers than the current \
+                     settings (changed fields: {}); restart with --rebuild-index \
+                     (or REBUILD_INDEX=1) to rebuild it from the job files. Jobs only in \
+                     the old index are kept if it stored them whole (STORE_DESCRIPTIONS)",
+                    index_path.display(),
+                    changed.join(", ")
+                )));
+            }
+            let writer = lock.acquire(&existing)?;
+            kept_jobs = stored_jobs(&existing)?;
+            drop(writer);
+            drop(existing);
+            println!(
+                "🔁 Index schema or analyzers changed ({}), rebuilding from the loaded jobs \
+                 and {} jobs stored in the old index...",
+                changed.join(", "),
+                kept_jobs.len()
+            );
+            fs::remove_dir_all(index_path).map_err(|e| Error::io(index_path, e))?;
+        }
+    }
+    // The loaded jobs come last, so they win over stored copies
+    kept_jobs.extend_from_slice(jobs);
+
+    // Create directory if needed
+    if !index_path.exists() {
+        fs::create_dir_all(index_path).map_err(|e| Error::io(index_path, e))?;
+    }
+
+    // Create or open index
+    let index = if index_path.join("meta.json").exists() {
+        println!("📂 Opening existing index...");
+        Index::open_in_dir(index_path).map_err(index_error)?
+    } else {
+        println!("📝 Creating new index...");
+        Index::create_in_dir(index_path, schema.clone()).map_err(index_error)?
+    };
+    register_tokenizers(&index, config);
+
+    index_jobs(&index, &kept_jobs, config, &lock)?;
+
+    Ok(index)
+}
+
+/// Names of the fields that differ between two schemas, or are in only one
+fn changed_fields(old: &Schema, new: &Schema) -> Vec<String> {
+    let mut names: Vec<String> = old
+        .fields()
+        .chain(new.fields())
+        .map(|(_, entry)| entry.name())
+        .filter(|name| {
+            let entry = |schema: &Schema| {
+                schema
+                    .get_field(name)
+                    .map(|field| schema.get_field_entry(field).clone())
+            };
+            entry(old) != entry(new)
+        })
+        .map(str::to_string)
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Jobs kept whole in the `source` field of `index`, which only an index
+/// built with `STORE_DESCRIPTIONS` has
+fn stored_jobs(index: &Index) -> Result<Vec<Job>, Error> {
+    let Some(source) = index.schema().get_field("source") else {
+        return Ok(Vec::new());
+    };
+    let reader = index.reader().map_err(index_error)?;
+    let searcher = reader.searcher();
+    let doc_addresses = searcher
+        .search(
+            &tantivy::query::AllQuery,
+            &tantivy::collector::DocSetCollector,
+        )
+        .map_err(index_error)?;
+    let mut jobs = Vec::with_capacity(doc_addresses.len());
+    for doc_address in doc_addresses {
+        let doc = searcher.doc(doc_address).map_err(index_error)?;
+        if let Some(job) = doc
+            .get_first(source)
+            .and_then(|value| value.as_text())
+            .and_then(|json| serde_json::from_str(json).ok())
+        {
+            jobs.push(job);
+        }
+    }
+    Ok(jobs)
+}
+
+/// Adds `jobs` to `index`, replacing documents with the same URL or the same
+/// key under `DEDUP_STRATEGY`, and evicting past `MAX_INDEXED_JOBS`
+pub fn index_jobs(
+    index: &Index,
+    jobs: &[Job],
+    config: &Config,
+    lock: &WriterLock,
+) -> Result<(), Error> {
+    let fields = JobFields::with_config(&index.schema(), config)?;
+    let mut index_writer = lock.acquire(index)?;
+
+    println!("📊 Indexing {} jobs...", jobs.len());
+    upsert_jobs(&mut index_writer, &fields, jobs).map_err(index_error)?;
+    println!("✅ Indexing complete!");
+
+    Ok(())
+}
+
+/// Adds `jobs` to the index, replacing any existing document with the same URL
+/// or dedup key, and commits, then evicts the oldest jobs past the cap.
+/// Within `jobs`, the last of several with the same key wins. Returns the
+/// number of jobs written.
+pub fn upsert_jobs(
+    index_writer: &mut IndexWriter,
+    fields: &JobFields,
+    jobs: &[Job],
+) -> tantivy::Result<usize> {
+    for job in jobs {
+        index_writer.delete_term(Term::from_field_text(fields.url, &job.url));
+        index_writer.delete_term(Term::from_field_text(
+            fields.dedup_key,
+            &fields.dedup.key(job),
+        ));
+        index_writer.add_document(fields.document(job))?;
+    }
+    index_writer.commit()?;
+    evict_oldest(index_writer, fields)?;
+    Ok(jobs.len())
+}
+
+/// Deletes the jobs scraped longest ago until at most `fields.max_docs`
+/// remain, and commits again. Jobs added without a scrape time count as
+/// scraped when indexed; ties go in index order. Returns the number of jobs
+/// evicted.
+pub fn evict_oldest(index_writer: &mut IndexWriter, fields: &JobFields) -> tantivy::Result<usize> {
+    let Some(max_docs) = fields.max_docs else {
+        return Ok(0);
+    };
+    // A reader of its own sees the commit just made
+    let reader = index_writer
+        .index()
+        .reader_builder()
+        .reload_policy(ReloadPolicy::Manual)
+        .try_into()?;
+    let searcher = reader.searcher();
+    let excess = (searcher.num_docs() as usize).saturating_sub(max_docs);
+    if excess == 0 {
+        return Ok(0);
+    }
+
+    let mut docs = Vec::new();
+    for (segment_ord, segment) in searcher.segment_readers().iter().enumerate() {
+        let scraped_at = segment.fast_fields().i64(fields.scraped_at)?;
+        for doc_id in segment.doc_ids_alive() {
+            let address = DocAddress::new(segment_ord as u32, doc_id);
+            docs.push((scraped_at.get_val(doc_id), address));
+        }
+    }
+    docs.sort_unstable();
+    for &(_, address) in &docs[..excess] {
+        let doc = searcher.doc(address)?;
+        if let Some(url) = doc.get_first(fields.url).and_then(|value| value.as_text()) {
+            index_writer.delete_term(Term::from_field_text(fields.url, url));
+        }
+    }
+    index_writer.commit()?;
+    println!(
+        "🧹 Evicted {} oldest jobs to stay within {}",
+        excess, max_docs
+    );
+    Ok(excess)
+}
+
+/// Replaces every document with `jobs` in a single commit, so searches see
+/// the old documents until the new ones are all in. `progress` is called with
+/// the number of jobs indexed so far and the total, about every tenth of
+/// them (at most every 1000) and after the last one. Returns the number of
+/// jobs then evicted past the cap. On failure the staged changes are rolled
+/// back, leaving the index as it was.
+pub fn rebuild_index(
+    index_writer: &mut IndexWriter,
+    fields: &JobFields,
+    jobs: &[Job],
+    mut progress: impl FnMut(usize, usize),
+) -> tantivy::Result<usize> {
+    let mut replace_all = || -> tantivy::Result<()> {
+        index_writer.delete_all_documents()?;
+        let step = (jobs.len() / 10).clamp(1, 1_000);
+        for (i, job) in jobs.iter().enumerate() {
+            index_writer.add_document(fields.document(job))?;
+            let indexed = i + 1;
+            if indexed % step == 0 || indexed == jobs.len() {
+                progress(indexed, jobs.len());
+            }
+        }
+        index_writer.commit()?;
+        Ok(())
+    };
+    if let Err(e) = replace_all() {
+        // The writer is shared: a staged delete-all left behind would empty
+        // the index on the next job write's commit
+        if let Err(rollback_error) = index_writer.rollback() {
+            eprintln!(
+                "⚠️  Couldn't roll back the failed rebuild: {}",
+                rollback_error
+            );
+        }
+        return Err(e);
+    }
+    evict_oldest(index_writer, fields)
+}
+
+/// Replaces the job stored under `id` with `job` in a single commit.
+/// Any other document with the job's dedup key is replaced too, so an edit
+/// that makes it a duplicate of another job can't leave both behind.
+pub fn replace_job(
+    index_writer: &mut IndexWriter,
+    fields: &JobFields,
+    id: &str,
+    job: &Job,
+) -> tantivy::Result<()> {
+    index_writer.delete_term(Term::from_field_text(fields.id, id));
+    index_writer.delete_term(Term::from_field_text(fields.url, &job.url));
+    index_writer.delete_term(Term::from_field_text(
+        fields.dedup_key,
+        &fields.dedup.key(job),
+    ));
+    index_writer.add_document(fields.document(job))?;
+    index_writer.commit()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tantivy::collector::DocSetCollector;
+    use tantivy::query::AllQuery;
+    use tantivy::schema::FieldEntry;
+
+    fn job(title: &str, url: &str) -> Job {
+        Job {
+            title: title.to_string(),
+            company: "Acme".to_string(),
+            location: "Remote".to_string(),
+            url: url.to_string(),
+            ..Job::default()
+        }
+    }
+
+    #[test]
+    fn test_fields_missing_from_an_old_index_are_an_error() {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("title", text_options("default") | STORED);
+        let Err(error) = JobFields::new(&schema_builder.build()) else {
+            panic!("an index without most fields was accepted");
+        };
+        assert!(
+            error.to_string().contains("no \"title_normalized\" field"),
+            "{}",
+            error
+        );
+        assert!(JobFields::new(&build_schema(&Config::default())).is_ok());
+    }
+
+    #[test]
+    fn test_create_index_upserts_into_existing_index() {
+        let dir = std::env::temp_dir().join(format!("index-upsert-{}", std::process::id()));
+        let config = Config::default();
+        let num_docs = |index: &Index| index.reader().unwrap().searcher().num_docs();
+
+        let first = vec![
+            job("Rust Developer", "https://example.com/rust"),
+            job("Go Developer", "https://example.com/go"),
+        ];
+        let index = create_index(&dir, &first, &config, None).unwrap();
+        assert_eq!(num_docs(&index), 2);
+        drop(index);
+
+        // Overlaps on one URL: updated once, the other old job is kept
+        let second = vec![
+            job("Senior Rust Developer", "https://example.com/rust"),
+            job("Designer", "https://example.com/design"),
+        ];
+        let index = create_index(&dir, &second, &config, None).unwrap();
+        let count = num_docs(&index);
+        let searcher = index.reader().unwrap().searcher();
+        let fields = JobFields::new(&index.schema()).unwrap();
+        let rust_docs = searcher
+            .search(
+                &tantivy::query::TermQuery::new(
+                    Term::from_field_text(fields.url, "https://example.com/rust"),
+                    IndexRecordOption::Basic,
+                ),
+                &tantivy::collector::TopDocs::with_limit(10),
+            )
+            .unwrap();
+        drop(index);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(count, 3);
+        assert_eq!(rust_docs.len(), 1);
+        let doc = searcher.doc(rust_docs[0].1).unwrap();
+        assert_eq!(
+            doc.get_first(fields.title).and_then(|v| v.as_text()),
+            Some("Senior Rust Developer")
+        );
+    }
+
+    #[test]
+    fn test_create_index_rebuilds_when_analyzers_change() {
+        let dir = std::env::temp_dir().join(format!("index-analyzers-{}", std::process::id()));
+        let num_docs = |index: &Index| index.reader().unwrap().searcher().num_docs();
+
+        let first = vec![
+            job("Rust Engineer", "https://example.com/rust"),
+            job("Go Developer", "https://example.com/go"),
+        ];
+        let index = create_index(&dir, &first, &Config::default(), None).unwrap();
+        assert_eq!(num_docs(&index), 2);
+        drop(index);
+
+        let stemmed = Config {
+            field_tokenizers: [("title".to_string(), "en_stem".to_string())].into(),
+            ..Config::default()
+        };
+        let second = vec![job("Rust Engineering Lead", "https://example.com/lead")];
+        // A mismatch stops startup instead of discarding the index
+        let Err(error) = create_index(&dir, &second, &stemmed, None) else {
+            panic!("an index with other analyzers was opened");
+        };
+        assert!(error.to_string().contains("--rebuild-index"));
+        assert!(error.to_string().contains("title"));
+        assert_eq!(
+            num_docs(&Index::open_in_dir(&dir).unwrap()),
+            2,
+            "the old index was kept"
+        );
+
+        let rebuild = Config {
+            rebuild_index: true,
+            ..stemmed
+        };
+        let index = create_index(&dir, &second, &rebuild, None).unwrap();
+        let fields = JobFields::new(&index.schema()).unwrap();
+        let reader = index.reader().unwrap();
+        let searcher = reader.searcher();
+        // The old jobs went with the old analyzers; "engineer" now stems alike
+        let matches = searcher
+            .search(
+                &tantivy::query::TermQuery::new(
+                    Term::from_field_text(fields.title, "engin"),
+                    tantivy::schema::IndexRecordOption::Basic,
+                ),
+                &tantivy::collector::Count,
+            )
+            .unwrap();
+        let count = searcher.num_docs();
+        drop(index);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(count, 1);
+        assert_eq!(matches, 1);
+    }
+
+    #[test]
+    fn test_index_with_the_old_description_analyzer_is_detected() {
+        let dir = std::env::temp_dir().join(format!("index-old-analyzer-{}", std::process::id()));
+        // As built before descriptions used the tech word tokenizer
+        let mut builder = Schema::builder();
+        for (_, entry) in build_schema(&Config::default()).fields() {
+            match entry.name() {
+                "description" => builder.add_field(FieldEntry::new_text(
+                    "description".to_string(),
+                    text_options("description"),
+                )),
+                _ => builder.add_field(entry.clone()),
+            };
+        }
+        fs::create_dir_all(&dir).unwrap();
+        drop(Index::create_in_dir(&dir, builder.build()).unwrap());
+
+        let result = create_index(&dir, &[], &Config::default(), None);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let Err(error) = result else {
+            panic!("an index with the old description analyzer was opened");
+        };
+        assert!(error.to_string().contains("changed fields: description"));
+    }
+
+    #[test]
+    fn test_rebuild_keeps_jobs_stored_in_the_old_index() {
+        let dir = std::env::temp_dir().join(format!("index-rebuild-keep-{}", std::process::id()));
+        let stored = Config {
+            store_descriptions: true,
+            ..Config::default()
+        };
+        let pushed = vec![job("Rust Engineer", "https://example.com/pushed")];
+        drop(create_index(&dir, &pushed, &stored, None).unwrap());
+
+        let rebuild = Config {
+            field_tokenizers: [("title".to_string(), "en_stem".to_string())].into(),
+            rebuild_index: true,
+            ..stored
+        };
+        let loaded = vec![job("Go Developer", "https://example.com/loaded")];
+        let index = create_index(&dir, &loaded, &rebuild, None).unwrap();
+        let count = index.reader().unwrap().searcher().num_docs();
+        drop(index);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn test_upserts_replace_by_dedup_key() {
+        let reposted = [
+            job("Rust Developer", "https://a.example/rust"),
+            job("Rust Developer", "https://b.example/rust?ref=feed"),
+        ];
+        let num_docs = |dedup_strategy| {
+            let config = Config {
+                dedup_strategy,
+                ..Config::default()
+            };
+            let index = Index::create_in_ram(build_schema(&config));
+            register_tokenizers(&index, &config);
+            index_jobs(&index, &reposted[..1], &config, &WriterLock::default()).unwrap();
+            index_jobs(&index, &reposted[1..], &config, &WriterLock::default()).unwrap();
+            index.reader().unwrap().searcher().num_docs()
+        };
+
+        assert_eq!(num_docs(DedupStrategy::Url), 2);
+        assert_eq!(num_docs(DedupStrategy::Id), 2);
+        assert_eq!(num_docs(DedupStrategy::TitleCompany), 1);
+        assert_eq!(num_docs(DedupStrategy::Composite), 1);
+    }
+
+    #[test]
+    fn test_adding_past_the_cap_evicts_the_oldest_jobs() {
+        let config = Config {
+            max_indexed_jobs: Some(2),
+            ..Config::default()
+        };
+        let index = Index::create_in_ram(build_schema(&config));
+        register_tokenizers(&index, &config);
+        let scraped = |title: &str, day: u32| Job {
+            scraped_at: Some(format!("2024-05-{:02}T00:00:00Z", day)),
+            ..job(title, &format!("https://example.com/{}", day))
+        };
+
+        let lock = WriterLock::default();
+        index_jobs(
+            &index,
+            &[scraped("Newer", 3), scraped("Oldest", 1)],
+            &config,
+            &lock,
+        )
+        .unwrap();
+        index_jobs(&index, &[scraped("Newest", 4)], &config, &lock).unwrap();
+        // An older job than any kept is evicted straight away
+        index_jobs(&index, &[scraped("Stale", 2)], &config, &lock).unwrap();
+
+        let searcher = index.reader().unwrap().searcher();
+        let fields = JobFields::new(&index.schema()).unwrap();
+        let mut titles: Vec<String> = searcher
+            .search(&AllQuery, &DocSetCollector)
+            .unwrap()
+            .into_iter()
+            .map(|address| {
+                let doc = searcher.doc(address).unwrap();
+                doc.get_first(fields.title)
+                    .unwrap()
+                    .as_text()
+                    .unwrap()
+                    .to_string()
+            })
+            .collect();
+        titles.sort();
+        assert_eq!(titles, ["Newer", "Newest"]);
+    }
+
+    #[test]
+    fn test_jobs_imported_without_scrape_time_survive_the_cap() {
+        let config = Config {
+            max_indexed_jobs: Some(2),
+            ..Config::default()
+        };
+        let index = Index::create_in_ram(build_schema(&config));
+        register_tokenizers(&index, &config);
+        let scraped = |title: &str, day: u32| Job {
+            scraped_at: Some(format!("2024-05-{:02}T00:00:00Z", day)),
+            ..job(title, &format!("https://example.com/{}", day))
+        };
+
+        let lock = WriterLock::default();
+        index_jobs(
+            &index,
+            &[scraped("Older", 1), scraped("Newer", 2)],
+            &config,
+            &lock,
+        )
+        .unwrap();
+        // Like a POST /jobs or CSV import, which carry no scrape time
+        let imported = job("Imported", "https://example.com/imported");
+        assert_eq!(imported.scraped_at, None);
+        index_jobs(&index, &[imported], &config, &lock).unwrap();
+
+        let searcher = index.reader().unwrap().searcher();
+        let fields = JobFields::new(&index.schema()).unwrap();
+        let mut titles: Vec<String> = searcher
+            .search(&AllQuery, &DocSetCollector)
+            .unwrap()
+            .into_iter()
+            .map(|address| {
+                let doc = searcher.doc(address).unwrap();
+                doc.get_first(fields.title)
+                    .unwrap()
+                    .as_text()
+                    .unwrap()
+                    .to_string()
+            })
+            .collect();
+        titles.sort();
+        assert_eq!(titles, ["Imported", "Newer"]);
+    }
+
+    #[test]
+    fn test_busy_writer_lock_names_holder_and_can_be_waited_for() {
+        let dir = std::env::temp_dir().join(format!("index-lock-{}", std::process::id()));
+        let index = create_index(&dir, &[], &Config::default(), None).unwrap();
+        let lock = WriterLock {
+            index_path: Some(dir.clone()),
+            wait: None,
+        };
+        let held = lock.acquire(&index).unwrap();
+
+        // Another writer fails straight away, saying who has the lock
+        let busy = lock.acquire(&index).err().unwrap().to_string();
+
+        // A short wait runs out while the lock is still held
+        let short_wait = WriterLock {
+            wait: Some(Duration::from_millis(100)),
+            ..lock.clone()
+        };
+        let timed_out = short_wait.acquire(&index).err().unwrap().to_string();
+
+        // A longer one gets the writer once the holder lets go
+        let release = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(200));
+            drop(held);
+        });
+        let long_wait = WriterLock {
+            wait: Some(Duration::from_secs(10)),
+            ..lock.clone()
+        };
+        let acquired = long_wait.acquire(&index).map(drop);
+        release.join().unwrap();
+        drop(index);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(busy.contains("locked by another writer"), "{}", busy);
+        assert!(busy.contains(&format!("server (pid {})", std::process::id())));
+        assert!(busy.contains("--wait-for-lock"));
+        assert!(
+            timed_out.contains("still busy after waiting"),
+            "{}",
+            timed_out
+        );
+        assert!(acquired.is_ok());
+    }
+
+    #[test]
+    fn test_writer_owner_that_exited_is_called_stale() {
+        let owner = |pid: u32| format!("server (pid {}) since 2024-05-10T15:30:00Z", pid);
+
+        let alive = describe_owner(&owner(std::process::id()));
+        assert_eq!(
+            alive,
+            format!("last recorded as {}", owner(std::process::id()))
+        );
+        // Above any pid Linux hands out
+        let exited = describe_owner(&owner(u32::MAX));
+        if cfg!(target_os = "linux") {
+            assert!(exited.ends_with("which is no longer running, so the record is stale"));
+        }
+        assert_eq!(describe_owner("garbled"), "last recorded as garbled");
+    }
+
+    #[test]
+    fn test_long_descriptions_are_capped_in_the_index_only() {
+        let config = Config {
+            store_descriptions: true,
+            max_description_tokens: Some(5),
+            ..Config::default()
+        };
+        let index = Index::create_in_ram(build_schema(&config));
+        register_tokenizers(&index, &config);
+        let mut long = job("Rust Developer", "https://example.com/rust");
+        long.description = "one two three four five six seven".to_string();
+        index_jobs(&index, &[long.clone()], &config, &WriterLock::default()).unwrap();
+
+        let searcher = index.reader().unwrap().searcher();
+        let fields = JobFields::new(&index.schema()).unwrap();
+        let doc_freq = |word: &str| {
+            searcher
+                .doc_freq(&Term::from_field_text(fields.description, word))
+                .unwrap()
+        };
+        assert_eq!(doc_freq("five"), 1);
+        assert_eq!(doc_freq("six"), 0);
+
+        // The stored copy keeps every word
+        let (_, doc_address) = searcher
+            .search(
+                &tantivy::query::AllQuery,
+                &tantivy::collector::TopDocs::with_limit(1),
+            )
+            .unwrap()[0];
+        let doc = searcher.doc(doc_address).unwrap();
+        assert_eq!(
+            doc.get_first(fields.description).and_then(|v| v.as_text()),
+            Some(long.description.as_str())
+        );
+    }
+
+    #[test]
+    fn test_field_tokenizers_configure_analysis_per_field() {
+        let config = Config {
+            field_tokenizers: [("company".to_string(), "raw".to_string())].into(),
+            ..Config::default()
+        };
+        check_field_tokenizers(&config).unwrap();
+        let index = Index::create_in_ram(build_schema(&config));
+        register_tokenizers(&index, &config);
+        let fields = JobFields::new(&index.schema()).unwrap();
+        let tokens = |field: Field| {
+            let mut tokens = Vec::new();
+            index
+                .tokenizer_for_field(field)
+                .unwrap()
+                .token_stream("Acme Corp")
+                .process(&mut |token| tokens.push(token.text.clone()));
+            tokens
+        };
+
+        // The keyword tokenizer keeps the company whole
+        assert_eq!(tokens(fields.company), ["Acme Corp"]);
+        assert_eq!(tokens(fields.description), ["acme", "corp"]);
+        assert_eq!(tokens(fields.title), ["acme", "corp"]);
+
+        let bad_tokenizer = Config {
+            field_tokenizers: [("company".to_string(), "snowball".to_string())].into(),
+            ..Config::default()
+        };
+        assert!(matches!(
+            check_field_tokenizers(&bad_tokenizer),
+            Err(Error::Config(_))
+        ));
+        let bad_field = Config {
+            field_tokenizers: [("salary".to_string(), "raw".to_string())].into(),
+            ..Config::default()
+        };
+        assert!(check_field_tokenizers(&bad_field).is_err());
+    }
+}
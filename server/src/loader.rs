@@ -0,0 +1,236 @@
+//! Loads scraped jobs from disk
+//!
+//! Each scraper writes its own JSON file into `data/`, so the server reads
+//! every file matching a pattern and merges them, dropping jobs that appear
+//! in more than one file (by the configured dedup key). A JSON file with a fresh MessagePack copy next to
+//! it is read from the copy, which loads faster.
+
+use common::{DedupStrategy, Error, Job, MSGPACK_EXTENSION, fresh_msgpack_copy, read_jobs_file};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Matches a file name against a pattern where `*` stands for any run of characters
+fn matches_pattern(name: &str, pattern: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return name == pattern;
+    }
+
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if !name.starts_with(first) || name.len() < first.len() + last.len() || !name.ends_with(last) {
+        return false;
+    }
+
+    // Middle parts must appear in order between the fixed prefix and suffix
+    let mut rest = &name[first.len()..name.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(pos) => rest = &rest[pos + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+/// Lists the files matching `pattern` (e.g. "data/*.json"), sorted by name.
+/// Only the file name part of the pattern may contain wildcards.
+fn matching_files(pattern: &str) -> Vec<PathBuf> {
+    let pattern_path = Path::new(pattern);
+    let dir = match pattern_path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let file_pattern = pattern_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("*");
+
+    let mut files: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file())
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| matches_pattern(name, file_pattern))
+            })
+            .collect(),
+        Err(_) => vec![],
+    };
+    files.sort();
+    files
+}
+
+/// Job files to load for `pattern`: the matching files, with each JSON file
+/// swapped for its MessagePack copy when the copy is at least as new. For a
+/// `*.json` pattern, MessagePack files written without a JSON version count too.
+fn job_files(pattern: &str) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = matching_files(pattern)
+        .into_iter()
+        .map(|path| fresh_msgpack_copy(&path).unwrap_or(path))
+        .collect();
+    if let Some(stem_pattern) = pattern.strip_suffix(".json") {
+        let msgpack_pattern = format!("{}.{}", stem_pattern, MSGPACK_EXTENSION);
+        for copy in matching_files(&msgpack_pattern) {
+            if !copy.with_extension("json").exists() {
+                files.push(copy);
+            }
+        }
+        files.sort();
+    }
+    files
+}
+
+/// Merges per-file job lists, keeping the first job seen for each dedup key
+fn merge_jobs(files: Vec<(PathBuf, Vec<Job>)>, dedup: DedupStrategy) -> Vec<Job> {
+    let mut seen_keys = HashSet::new();
+    let mut jobs = Vec::new();
+
+    for (path, file_jobs) in files {
+        let total = file_jobs.len();
+        let mut added = 0;
+        for job in file_jobs {
+            if seen_keys.insert(dedup.key(&job)) {
+                jobs.push(job);
+                added += 1;
+            }
+        }
+        println!(
+            "   {:?}: {} jobs ({} duplicates skipped)",
+            path,
+            total,
+            total - added
+        );
+    }
+
+    jobs
+}
+
+/// Loads and merges all job files matching `pattern`.
+/// Files that aren't a list of jobs (e.g. the scraper's seen-URL sidecar) are skipped.
+pub fn load_jobs(pattern: &str, dedup: DedupStrategy) -> Vec<Job> {
+    let mut files = Vec::new();
+
+    for path in job_files(pattern) {
+        match read_jobs_file(&path) {
+            Ok(jobs) => files.push((path, jobs)),
+            Err(e) => println!("   Skipping {:?}: not a job list ({})", path, e),
+        }
+    }
+
+    merge_jobs(files, dedup)
+}
+
+/// Loads the jobs in the single file at `path` (JSON, or MessagePack by its
+/// extension). Unlike `load_jobs`, a missing or malformed file is an error,
+/// since it was asked for by name.
+pub fn load_jobs_file(path: &Path) -> Result<Vec<Job>, Error> {
+    let jobs = read_jobs_file(path)?;
+    println!("   {:?}: {} jobs", path, jobs.len());
+    Ok(jobs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(title: &str, url: &str) -> Job {
+        Job {
+            title: title.to_string(),
+            company: "Acme".to_string(),
+            location: "Remote".to_string(),
+            description: String::new(),
+            salary_min: None,
+            url: url.to_string(),
+            ..Job::default()
+        }
+    }
+
+    #[test]
+    fn test_matches_pattern() {
+        assert!(matches_pattern("jobs.json", "*.json"));
+        assert!(matches_pattern("remoteok.json", "*.json"));
+        assert!(!matches_pattern("jobs.json.bak", "*.json"));
+        assert!(matches_pattern("jobs.json", "jobs.json"));
+        assert!(matches_pattern("wwr-2024.json", "wwr-*.json"));
+        assert!(!matches_pattern("remoteok.json", "wwr-*.json"));
+    }
+
+    #[test]
+    fn test_load_jobs_merges_and_dedupes_files() {
+        let dir = std::env::temp_dir().join(format!("jobs-loader-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let shared = job("Rust Developer", "https://example.com/shared");
+        let first = vec![
+            shared.clone(),
+            job("Go Developer", "https://example.com/go"),
+        ];
+        let second = vec![shared, job("Designer", "https://example.com/design")];
+        fs::write(
+            dir.join("remoteok.json"),
+            serde_json::to_string(&first).unwrap(),
+        )
+        .unwrap();
+        fs::write(
+            dir.join("weworkremotely.json"),
+            serde_json::to_string(&second).unwrap(),
+        )
+        .unwrap();
+        fs::write(dir.join("seen_jobs.json"), r#"{"entries": {}}"#).unwrap();
+
+        let jobs = load_jobs(dir.join("*.json").to_str().unwrap(), DedupStrategy::Url);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let urls: Vec<&str> = jobs.iter().map(|j| j.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            vec![
+                "https://example.com/shared",
+                "https://example.com/go",
+                "https://example.com/design"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_load_jobs_prefers_fresh_msgpack_copies() {
+        use std::time::{Duration, SystemTime};
+
+        let dir = std::env::temp_dir().join(format!("jobs-msgpack-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let write_json = |name: &str, jobs: &[Job]| {
+            fs::write(dir.join(name), serde_json::to_string(jobs).unwrap()).unwrap();
+        };
+        let write_msgpack = |name: &str, jobs: &[Job]| {
+            fs::write(dir.join(name), common::jobs_to_msgpack(jobs).unwrap()).unwrap();
+        };
+
+        // Fresh copy: read instead of the JSON
+        write_json("fresh.json", &[job("From JSON", "https://example.com/1")]);
+        write_msgpack(
+            "fresh.msgpack",
+            &[job("From MessagePack", "https://example.com/1")],
+        );
+        // Stale copy, older than its JSON: ignored
+        write_msgpack("stale.msgpack", &[job("Stale", "https://example.com/2")]);
+        let an_hour_ago = SystemTime::now() - Duration::from_secs(3_600);
+        fs::File::options()
+            .write(true)
+            .open(dir.join("stale.msgpack"))
+            .unwrap()
+            .set_modified(an_hour_ago)
+            .unwrap();
+        write_json("stale.json", &[job("Current", "https://example.com/2")]);
+        // MessagePack only: still loaded
+        write_msgpack("packed.msgpack", &[job("Packed", "https://example.com/3")]);
+
+        let jobs = load_jobs(dir.join("*.json").to_str().unwrap(), DedupStrategy::Url);
+        fs::remove_dir_all(&dir).unwrap();
+
+        let titles: Vec<&str> = jobs.iter().map(|j| j.title.as_str()).collect();
+        assert_eq!(titles, vec!["From MessagePack", "Packed", "Current"]);
+    }
+}
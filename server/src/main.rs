@@ -3,259 +3,6088 @@
 //! Provides full-text search over scraped job listings using Tantivy
 //! and exposes a REST API using Axum.
 
+mod completion;
+mod config;
+mod csv_import;
+mod dashboard;
+mod facets;
+mod filter_tree;
+mod filters;
+mod highlight;
+mod idempotency;
+mod index;
+mod loader;
+mod querylog;
+mod ranking;
+mod saved;
+mod sort;
+mod spelling;
+mod tokenizer;
+mod vocabulary;
+
 use axum::{
     Json, Router,
-    extract::{Query, State},
-    routing::get,
+    body::StreamBody,
+    extract::{Path, Query, State},
+    http::{HeaderMap, HeaderValue, StatusCode, header},
+    response::{
+        IntoResponse, Response,
+        sse::{Event, Sse},
+    },
+    routing::{get, post, put},
+};
+use clap::Parser;
+use common::{Error, Job, parse_timestamp, usd_rate};
+use completion::Completion;
+use config::{Config, QueryFallback, SnippetField};
+use dashboard::{Dashboard, DashboardCache, NameCount};
+use facets::{FieldFacets, SalaryBounds, SalaryBucket};
+use filter_tree::FilterNode;
+use filters::Filters;
+use futures_util::stream;
+use highlight::{MatchRange, OffsetFinder, TermCounter};
+use idempotency::IdempotencyCache;
+use index::{
+    DESCRIPTION_EXCERPT_CHARS, INDEX_DIR, JobFields, WriterLock, create_index, index_error,
+    rebuild_index, replace_job, unix_now, upsert_jobs,
 };
-use common::Job;
+use querylog::{QueryLog, Suggestion};
+use ranking::{Bm25, Bm25Params, FeaturedBoost, FieldBoosts, Freshness, Profile, RecencyBoost};
+use saved::{SavedSearch, SavedSearchStore};
 use serde::{Deserialize, Serialize};
-use std::fs;
-use std::path::Path;
-use std::sync::Arc;
+use std::borrow::Cow;
+use std::io;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use subtle::ConstantTimeEq;
 use tantivy::{
-    Index, IndexReader, ReloadPolicy,
-    collector::TopDocs,
-    query::QueryParser,
-    schema::{INDEXED, IntOptions, STORED, Schema, TEXT},
+    DocAddress, Index, IndexReader, IndexWriter, ReloadPolicy, Searcher, SnippetGenerator, Term,
+    collector::{Count, DocSetCollector, TopDocs},
+    query::{
+        AllQuery, BooleanQuery, BoostQuery, FuzzyTermQuery, Occur, Query as TantivyQuery,
+        QueryParser, QueryParserError, RegexQuery, TermQuery,
+    },
+    schema::{Field, IndexRecordOption, NamedFieldDocument, Schema},
 };
+use tower_http::compression::{
+    CompressionLayer, Predicate,
+    predicate::{DefaultPredicate, NotForContentType, SizeAbove},
+};
+use utoipa::{IntoParams, OpenApi, ToSchema};
+use vocabulary::Vocabulary;
 
 /// Search result returned by the API
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 struct SearchResult {
     title: String,
+    /// The title reduced to the role, e.g. "backend developer", for grouping
+    title_normalized: String,
     company: String,
+    /// Region text as the board gave it, e.g. "Europe Only"; empty when unknown
+    location: String,
+    /// The listing, to read the whole job and apply
+    url: String,
+    /// Lowest listed yearly salary
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    salary_min: Option<i64>,
     score: f32,
+    /// Start of the job description, at most 300 characters
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    /// Fields with the query terms marked up, only present where a term matched
+    highlights: Highlights,
+    /// HTML context for the result: the first highlight in the configured
+    /// field order, else the first of those fields with any text. Not set
+    /// with `highlights=offsets`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    snippet: Option<String>,
+    /// Where the query terms matched, with `highlights=offsets` (instead of markup)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    highlight_offsets: Option<HighlightOffsets>,
+    /// How many distinct query terms the job contains, e.g. to show "matched
+    /// 2 of 3 terms"; left out when the search has no keywords
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    matched_terms: Option<usize>,
+    /// Age badge from the posted (or else scraped) date
+    freshness: Freshness,
+}
+
+/// HTML-escaped field text with matched terms wrapped in the configured tags
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema)]
+struct Highlights {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    company: Option<String>,
+    /// Best matching excerpt, from the stored description or its first 1000 characters
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+}
+
+/// Character ranges of matched terms within the returned `title`, `company`
+/// and `description`; a field is left out when nothing in it matched.
+/// Prefix wildcard and fuzzy matches have no ranges, so a job found only
+/// through them has none.
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema)]
+struct HighlightOffsets {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<Vec<MatchRange>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    company: Option<Vec<MatchRange>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<Vec<MatchRange>>,
 }
 
 /// API response wrapper
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 struct SearchResponse {
     query: String,
+    /// Results in this response, at most the page size
     total_results: usize,
+    /// Jobs matching the query and filters across all pages, before a
+    /// `max_per_company` cap
+    total_hits: usize,
     results: Vec<SearchResult>,
+    /// How the query was interpreted, filters included; only with `debug=true`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    parsed_query: Option<String>,
+    /// Where this page sits among all matches; only with `limit` or `offset`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pagination: Option<Pagination>,
+    /// Lowest and highest `salary_min` among all matches, not just this
+    /// page; left out when no match lists a salary. With `currency`, every
+    /// salary is converted and the bounds are in that currency.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    salary_range: Option<SalaryBounds>,
+    /// Matches per company and location, the most common 20 of each; only
+    /// with `facets=true`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    facets: Option<FieldFacets>,
+    /// "Did you mean" rewrites of a query that found nothing, with unknown
+    /// words replaced by the closest indexed ones; left out when empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    suggestions: Vec<String>,
+}
+
+/// Page math for a search run with `limit`/`offset`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+struct Pagination {
+    /// 1-based page the offset falls on
+    page: usize,
+    per_page: usize,
+    /// Pages that can be requested, those starting at most `MAX_OFFSET`
+    /// in; 0 when nothing matched
+    total_pages: usize,
+    /// Jobs matching the query and filters, before any `max_per_company` cap
+    total_hits: usize,
+    /// Whether a next page exists and can be requested
+    has_next: bool,
+}
+
+impl Pagination {
+    fn new(offset: usize, per_page: usize, total_hits: usize) -> Pagination {
+        // Matches past MAX_OFFSET exist but no page can start there
+        let reachable = total_hits.min(MAX_OFFSET + 1);
+        let next = offset + per_page;
+        Pagination {
+            page: offset / per_page + 1,
+            per_page,
+            total_pages: reachable.div_ceil(per_page),
+            total_hits,
+            has_next: next < reachable,
+        }
+    }
+}
+
+/// Response for the count endpoint
+#[derive(Debug, Serialize, ToSchema)]
+struct CountResponse {
+    query: String,
+    count: usize,
+}
+
+/// Response for the salary histogram endpoint
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+struct SalaryHistogramResponse {
+    query: String,
+    /// Width of each bucket
+    bucket: i64,
+    /// Currency of the buckets: every salary is converted to US dollars,
+    /// so listings in different currencies can share a chart
+    currency: String,
+    /// Matching jobs with a salary in a known currency, the sum of the bucket counts
+    total: u64,
+    /// Buckets from the lowest to the highest `salary_min` in dollars, empty ones included
+    buckets: Vec<SalaryBucket>,
+}
+
+/// Response for the health endpoint
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+struct HealthResponse {
+    /// "ok"; from /ready, "degraded" when a probe search fails
+    status: String,
+    /// Jobs currently searchable; 0 before anything has been indexed
+    num_docs: u64,
+    /// Why the probe search failed; only when degraded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Response for the CSV import endpoint
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+struct CsvImportResponse {
+    /// Rows read into jobs
+    parsed: usize,
+    /// Rows whose cells are all empty
+    skipped: usize,
+    /// Rows that couldn't be imported; see `errors`
+    errored: usize,
+    /// Jobs added or replaced
+    indexed: usize,
+    errors: Vec<csv_import::RowError>,
+}
+
+/// Response for the config reload endpoint
+#[derive(Debug, Clone, Serialize)]
+struct ReloadConfigResponse {
+    /// Terms that now have synonyms
+    synonyms: usize,
+    /// Stopwords now in effect
+    stopwords: usize,
+}
+
+/// Response for the job import endpoint
+#[derive(Debug, Clone, Serialize, ToSchema)]
+struct ImportResponse {
+    /// Number of jobs added or replaced
+    indexed: usize,
 }
 
 /// Query parameters for search endpoint
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 struct SearchParams {
+    /// Search keywords, matched against job title and description
     q: Option<String>,
+    /// Boost recent jobs: a job this many days old gets half the boost of a new one
+    recency_half_life_days: Option<f64>,
+    /// Only jobs that can be worked from this UTC offset in hours (e.g. +1, -5),
+    /// including worldwide ones
+    timezone: Option<String>,
+    /// Salary band: `lt50k`, `50to100k`, `100to150k` or `gt150k`
+    salary_bucket: Option<String>,
+    /// Only jobs whose salary range reaches at least this much (also `min_salary`)
+    #[serde(alias = "min_salary")]
+    salary_min: Option<i64>,
+    /// Only jobs whose salary range starts at or below this much (also `max_salary`)
+    #[serde(alias = "max_salary")]
+    salary_max: Option<i64>,
+    /// Currency of `salary_min`/`salary_max` (ISO code such as EUR). When set,
+    /// both sides are converted to USD so jobs in any currency compare fairly.
+    currency: Option<String>,
+    /// Response body: `json` (default) or `ndjson`, one result per line
+    format: Option<String>,
+    /// Match keywords anywhere inside title and company words ("shop" finds "Shopify")
+    substring: Option<bool>,
+    /// Words allowed between the words of a quoted phrase, so `"machine learning"`
+    /// with `slop=1` also finds "machine and learning" (default 0: exact phrase, at most 10)
+    slop: Option<u32>,
+    /// Edits (0 to 2) a keyword may be away from a word and still match, so
+    /// "develper" finds "developer"; words under 6 letters allow at most 1,
+    /// under 3 none. Exact matches still rank higher. Default 0.
+    fuzziness: Option<u8>,
+    /// Order by fields instead of relevance: comma-separated `salary` or `date`
+    /// keys with `_asc` or `_desc`, e.g. `salary_desc,date_desc`; `relevance`
+    /// keeps the ranking by score, e.g. to override a profile's sort
+    sort: Option<String>,
+    /// Return at most this many jobs from any one company
+    max_per_company: Option<usize>,
+    /// Jobs per page (default 10, at most 100); adds `pagination` to the response
+    limit: Option<usize>,
+    /// Matches to skip before this page (default 0, at most 1000); adds `pagination`
+    offset: Option<usize>,
+    /// 1-based page number, an alternative to `offset` counted in `limit`-sized
+    /// pages; adds `pagination`
+    page: Option<usize>,
+    /// Include the parsed Tantivy query in the response as `parsed_query`
+    debug: Option<bool>,
+    /// Count every match per company and per location, in `facets`
+    facets: Option<bool>,
+    /// Only jobs with this contract type: full-time, part-time or contract
+    contract_type: Option<String>,
+    /// Leave out jobs whose application deadline has passed; jobs without
+    /// a deadline are kept
+    exclude_expired: Option<bool>,
+    /// Only jobs posted (or else scraped) after this date, e.g. 2024-05-01 or
+    /// an RFC 3339 date-time; undated jobs are left out
+    posted_after: Option<String>,
+    /// Only jobs posted (or else scraped) within this long, e.g. 24h, 7d or 2w
+    posted_within: Option<String>,
+    /// `true`: only jobs offering visa sponsorship; `false`: only jobs saying
+    /// they don't. Jobs that don't mention it are left out either way.
+    visa: Option<bool>,
+    /// Only jobs that can be done from this country (ISO code such as US or DE),
+    /// including worldwide ones
+    country: Option<String>,
+    /// Only jobs whose location text contains these words, e.g. "USA Only" or "Europe"
+    location: Option<String>,
+    /// Only this company's jobs: its whole name, ignoring case
+    company: Option<String>,
+    /// `html` (default) marks matches up in `highlights`; `offsets` returns
+    /// their character ranges in `highlight_offsets` instead. Either way only
+    /// words matching a query term exactly are reported: words found by a
+    /// prefix wildcard (`dev*`) or by `fuzziness` aren't.
+    highlights: Option<String>,
+    /// Length of the description excerpt in `highlights` and `snippet`, in
+    /// characters (default 150, clamped to 20-1000)
+    snippet_chars: Option<usize>,
+    /// Rank jobs the board features higher (off by default)
+    boost_featured: Option<bool>,
+    /// Weight of keyword matches in the title (default 2, or `BOOST_TITLE`)
+    boost_title: Option<f32>,
+    /// Weight of keyword matches in the description (default 1)
+    boost_description: Option<f32>,
+    /// Weight of keyword matches in the company name (default 0: not searched)
+    boost_company: Option<f32>,
+    /// Ranking preset: `relevance`, `recent` or `comp`. Fills in `sort`,
+    /// `boost_title` and `recency_half_life_days` where they aren't given.
+    profile: Option<String>,
+}
+
+/// Body of POST /search
+#[derive(Debug, Deserialize, ToSchema)]
+struct SearchRequest {
+    /// Same parameters as GET /search
+    #[serde(flatten)]
+    #[schema(value_type = Object)]
+    params: SearchParams,
+    /// Conditions every result must meet, combined with `and`, `or` and `not`
+    filter: Option<FilterNode>,
+}
+
+/// Body of POST /saved-searches
+#[derive(Debug, Deserialize, ToSchema)]
+struct CreateSavedSearch {
+    name: String,
+    /// Same parameters as GET /search
+    #[schema(value_type = Object)]
+    params: SearchParams,
+}
+
+/// Query parameters for running a saved search
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct SavedResultsParams {
+    /// Only jobs scraped (or added) after the previous run of this search
+    new_only: Option<bool>,
+}
+
+/// Query parameters for the new jobs endpoint
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct NewJobsParams {
+    /// RFC 3339 date-time; only jobs scraped (or added) after it are returned
+    since: String,
+    /// Most jobs returned, newest first (default and maximum 500)
+    limit: Option<usize>,
+}
+
+/// Query parameters of the salary histogram endpoint besides the search ones
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct SalaryHistogramParams {
+    /// Width of each salary bucket (default 10000, at least 1000)
+    bucket: Option<i64>,
+}
+
+/// Query parameters for the query suggestion endpoint
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+struct SuggestParams {
+    /// Start of the query being typed
+    prefix: String,
+    /// Most suggestions returned (default 5, at most 20)
+    limit: Option<usize>,
+}
+
+/// Query parameters for the stored document endpoint
+#[derive(Debug, Deserialize)]
+struct AdminDocParams {
+    /// URL of the job to look up
+    url: String,
+}
+
+/// Reads the structured filters out of the search parameters
+fn parse_filters(params: &SearchParams) -> Result<Filters, String> {
+    let mut salary_range = filters::parse_salary_range(params.salary_min, params.salary_max)?;
+    let salary_in_usd = params.currency.is_some();
+    if let Some(currency) = &params.currency {
+        salary_range = filters::salary_range_in_usd(salary_range, currency)?;
+    }
+    let posted_after = params
+        .posted_after
+        .as_deref()
+        .map(filters::parse_posted_after)
+        .transpose()?;
+    let posted_within = params
+        .posted_within
+        .as_deref()
+        .map(|age| filters::parse_posted_within(age, unix_now()))
+        .transpose()?;
+    Ok(Filters {
+        timezone: params
+            .timezone
+            .as_deref()
+            .map(filters::parse_timezone)
+            .transpose()?,
+        salary: params
+            .salary_bucket
+            .as_deref()
+            .map(filters::parse_salary_bucket)
+            .transpose()?,
+        salary_range,
+        salary_in_usd,
+        // The later of the two cutoffs, when both are given
+        posted_after: posted_after.max(posted_within),
+        scraped_after: None,
+        expired_before: params.exclude_expired.unwrap_or(false).then(unix_now),
+        contract_type: params
+            .contract_type
+            .as_deref()
+            .map(str::parse)
+            .transpose()?,
+        visa: params.visa,
+        country: params
+            .country
+            .as_deref()
+            .map(common::parse_country_code)
+            .transpose()?,
+        location: params
+            .location
+            .as_deref()
+            .map(filters::parse_location)
+            .transpose()?,
+        // Replaced by the path in /companies/{company}/jobs
+        company: params
+            .company
+            .as_deref()
+            .map(filters::parse_company)
+            .transpose()?,
+        // Set from the body by POST /search
+        expression: None,
+    })
+}
+
+/// OpenAPI description of the public API, served at GET /openapi.json
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Job Search Engine API"),
+    paths(
+        search_handler,
+        search_post_handler,
+        count_handler,
+        salary_histogram_handler,
+        company_jobs_handler,
+        health_handler,
+        ready_handler,
+        dashboard_handler,
+        suggest_handler,
+        new_jobs_handler,
+        import_handler,
+        import_csv_handler,
+        update_job_handler,
+        create_saved_search_handler,
+        list_saved_searches_handler,
+        saved_search_results_handler
+    ),
+    components(schemas(
+        SearchResponse,
+        SearchResult,
+        Highlights,
+        HighlightOffsets,
+        MatchRange,
+        Freshness,
+        Pagination,
+        SalaryBounds,
+        FieldFacets,
+        SearchRequest,
+        FilterNode,
+        filter_tree::AndNode,
+        filter_tree::OrNode,
+        filter_tree::NotNode,
+        filter_tree::TermNode,
+        filter_tree::RangeNode,
+        CountResponse,
+        SalaryHistogramResponse,
+        SalaryBucket,
+        HealthResponse,
+        Dashboard,
+        NameCount,
+        SuggestResponse,
+        Suggestion,
+        Completion,
+        ImportResponse,
+        CsvImportResponse,
+        csv_import::RowError,
+        CreateSavedSearch,
+        SavedSearch
+    ))
+)]
+struct ApiDoc;
+
+/// Most idempotency keys remembered at once
+const IDEMPOTENCY_CAPACITY: usize = 10_000;
+
+/// Highlighted titles and company names are returned whole, up to this length
+const HIGHLIGHT_FULL_FIELD_CHARS: usize = 1_000;
+
+/// Length of the highlighted description excerpt, unless `snippet_chars` says otherwise
+const HIGHLIGHT_EXCERPT_CHARS: usize = 150;
+
+/// Range `snippet_chars` is clamped to
+const SNIPPET_CHARS_RANGE: std::ops::RangeInclusive<usize> = 20..=DESCRIPTION_EXCERPT_CHARS;
+
+/// Responses smaller than this many bytes are sent uncompressed
+const COMPRESSION_MIN_BYTES: u16 = 1_024;
+
+/// Score multiplier for featured jobs with `boost_featured=true`
+const FEATURED_BOOST: f32 = 1.5;
+
+/// Most results returned by a search
+const MAX_RESULTS: usize = 10;
+
+/// Largest `limit` a search accepts
+const MAX_PAGE_SIZE: usize = 100;
+
+/// Largest `slop` a search accepts
+const MAX_SLOP: u32 = 10;
+
+/// Largest `fuzziness` a search accepts (Tantivy's Levenshtein automata go up to 2)
+const MAX_FUZZINESS: u8 = 2;
+
+/// Largest `offset` a search accepts; deeper pages cost a collection of every hit before them
+const MAX_OFFSET: usize = 1_000;
+
+/// Most jobs returned by one /jobs/new request
+const MAX_NEW_JOBS: usize = 500;
+
+/// Suggestions returned by GET /suggest when no limit is given
+const DEFAULT_SUGGESTIONS: usize = 5;
+
+/// Most suggestions returned by GET /suggest
+const MAX_SUGGESTIONS: usize = 20;
+
+/// Companies and locations counted in `facets`, the most common first
+const MAX_FACET_VALUES: usize = 20;
+
+/// Salary histogram bucket width when none is given
+const DEFAULT_SALARY_BUCKET: i64 = 10_000;
+
+/// Narrowest salary histogram bucket
+const MIN_SALARY_BUCKET: i64 = 1_000;
+
+/// Most buckets a salary histogram returns
+const MAX_SALARY_BUCKETS: usize = 500;
+
+/// Most "did you mean" rewrites of a query that found nothing
+const MAX_SPELLING_SUGGESTIONS: usize = 3;
+
+/// Hits collected before capping results per company, so the page can
+/// still be filled with other companies' jobs
+const DIVERSITY_CANDIDATES: usize = 100;
+
+/// Longest description returned in a search result
+const MAX_DESCRIPTION_CHARS: usize = 300;
+
+/// Cuts `text` to at most `max_chars` characters, marking the cut with "…"
+fn truncate_chars(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    // Leave room for the ellipsis
+    let end = text
+        .char_indices()
+        .nth(max_chars.saturating_sub(1))
+        .map_or(text.len(), |(end, _)| end);
+    format!("{}…", text[..end].trim_end())
+}
+
+/// Picks a result's snippet: the first field in `priority` with a highlight,
+/// or when nothing matched, the first `max_chars` of the first one with text
+/// (escaped). `texts` holds title, company and description.
+fn snippet(
+    priority: &[SnippetField],
+    highlights: &Highlights,
+    texts: [Option<&String>; 3],
+    max_chars: usize,
+) -> Option<String> {
+    let highlighted = priority.iter().find_map(|field| match field {
+        SnippetField::Title => highlights.title.clone(),
+        SnippetField::Company => highlights.company.clone(),
+        SnippetField::Description => highlights.description.clone(),
+    });
+    highlighted.or_else(|| {
+        priority.iter().find_map(|field| {
+            let text = match field {
+                SnippetField::Title => texts[0],
+                SnippetField::Company => texts[1],
+                SnippetField::Description => texts[2],
+            }?;
+            let text = text.trim();
+            (!text.is_empty()).then(|| highlight::escape_html(&truncate_chars(text, max_chars)))
+        })
+    })
 }
 
 /// Shared application state
 struct AppState {
     index_reader: IndexReader,
+    index_writer: Mutex<IndexWriter>,
+    job_fields: JobFields,
     query_parser: QueryParser,
     schema: Schema,
+    idempotency: Mutex<IdempotencyCache<ImportResponse>>,
+    saved_searches: Mutex<SavedSearchStore>,
+    dashboard: Mutex<DashboardCache>,
+    /// How often each query was searched, for GET /suggest
+    query_log: Mutex<QueryLog>,
+    /// Query synonyms and stopwords, swapped whole by POST /admin/reload-config
+    vocabulary: RwLock<Arc<Vocabulary>>,
+    config: Config,
+}
+
+impl AppState {
+    /// Creates the reader, writer and query parser for an already-built index,
+    /// taking the writer as `lock` says
+    fn new(index: &Index, config: &Config, lock: &WriterLock) -> Result<AppState, Error> {
+        let schema = index.schema();
+
+        // Create index reader
+        let index_reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::OnCommit)
+            .try_into()
+            .map_err(index_error)?;
+
+        // Create the query parser for the configured field weights
+        let job_fields = JobFields::with_config(&schema, config)?;
+        let query_parser = boosted_parser(index, &job_fields, config.field_boosts);
+
+        Ok(AppState {
+            index_reader,
+            index_writer: Mutex::new(lock.acquire(index)?),
+            job_fields,
+            query_parser,
+            schema,
+            idempotency: Mutex::new(IdempotencyCache::new(
+                Duration::from_secs(config.idempotency_ttl_secs),
+                IDEMPOTENCY_CAPACITY,
+            )),
+            saved_searches: Mutex::new(SavedSearchStore::load(std::path::Path::new(
+                &config.saved_searches_path,
+            ))),
+            dashboard: Mutex::new(DashboardCache::new(Duration::from_secs(
+                config.dashboard_cache_secs,
+            ))),
+            query_log: Mutex::new(
+                config
+                    .query_log_path
+                    .as_deref()
+                    .map(|path| QueryLog::load(std::path::Path::new(path)))
+                    .unwrap_or_default(),
+            ),
+            vocabulary: RwLock::new(Arc::new(load_vocabulary(config).unwrap_or_else(|e| {
+                eprintln!("⚠️  No synonyms or stopwords: {}", e);
+                Vocabulary::default()
+            }))),
+            config: config.clone(),
+        })
+    }
+
+    /// The synonyms and stopwords currently in effect
+    fn vocabulary(&self) -> Arc<Vocabulary> {
+        self.vocabulary.read().unwrap().clone()
+    }
+}
+
+/// Reads the synonyms and stopwords files named in the config
+fn load_vocabulary(config: &Config) -> Result<Vocabulary, Error> {
+    Vocabulary::load(
+        std::path::Path::new(&config.synonyms_path),
+        std::path::Path::new(&config.stopwords_path),
+    )
+}
+
+/// Shortest prefix accepted before a trailing `*`, so "d*" can't expand to half the dictionary
+const MIN_WILDCARD_PREFIX: usize = 3;
+
+/// Most wildcard terms expanded per query; further ones are searched as plain words
+const MAX_WILDCARD_TERMS: usize = 3;
+
+/// Rewrites a search the query parser rejected so it parses as plain words,
+/// or None when `fallback` is off or nothing searchable is left
+fn fallback_query_text(query_str: &str, fallback: QueryFallback) -> Option<String> {
+    let words: Vec<String> = match fallback {
+        QueryFallback::Off => return None,
+        // Inside quotes only `"` is special, so "frontend:" is just a word
+        QueryFallback::Escape => query_str
+            .split_whitespace()
+            .map(|word| word.replace('"', ""))
+            .filter(|word| !word.is_empty())
+            .map(|word| format!("\"{}\"", word))
+            .collect(),
+        QueryFallback::Strip => query_str
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(str::to_string)
+            .collect(),
+    };
+    (!words.is_empty()).then(|| words.join(" "))
+}
+
+/// Parses free text against the default fields. When the parser rejects it
+/// (a stray `:` or unbalanced bracket), retries once with the configured
+/// fallback before giving up.
+fn parse_text(
+    state: &AppState,
+    parser: &QueryParser,
+    text: &str,
+) -> Result<Box<dyn TantivyQuery>, QueryParserError> {
+    let err = match parser.parse_query(text) {
+        Ok(query) => return Ok(query),
+        Err(err) => err,
+    };
+    let Some(fallback) = fallback_query_text(text, state.config.query_fallback) else {
+        return Err(err);
+    };
+    eprintln!(
+        "⚠️  Query {:?} didn't parse ({:?}), retrying as {:?}",
+        text, err, fallback
+    );
+    parser.parse_query(&fallback)
+}
+
+/// The 400 for a query the parser rejected, with its reason
+fn invalid_query(query: &str, err: QueryParserError) -> (StatusCode, String) {
+    (
+        StatusCode::BAD_REQUEST,
+        format!("invalid query {:?}: {}", query, err),
+    )
+}
+
+/// Checks the `slop` parameter, 0 when it is left out
+fn parse_slop(params: &SearchParams) -> Result<u32, String> {
+    match params.slop {
+        Some(slop) if slop > MAX_SLOP => Err(format!("slop must be at most {}", MAX_SLOP)),
+        slop => Ok(slop.unwrap_or(0)),
+    }
+}
+
+/// Checks the `fuzziness` parameter, 0 when it is left out
+fn parse_fuzziness(params: &SearchParams) -> Result<u8, String> {
+    match params.fuzziness {
+        Some(fuzziness) if fuzziness > MAX_FUZZINESS => {
+            Err(format!("fuzziness must be at most {}", MAX_FUZZINESS))
+        }
+        fuzziness => Ok(fuzziness.unwrap_or(0)),
+    }
+}
+
+/// Typo-tolerant matches for the plain words of a query: each word, as
+/// each field analyzes it, matches terms within `fuzziness` edits in the
+/// title and description. Their score is constant, so an exact match, which
+/// also scores as a keyword, ranks above a near one.
+fn fuzzy_clauses(
+    state: &AppState,
+    words: &[String],
+    boosts: FieldBoosts,
+    fuzziness: u8,
+) -> Vec<(Occur, Box<dyn TantivyQuery>)> {
+    let mut clauses: Vec<(Occur, Box<dyn TantivyQuery>)> = Vec::new();
+    if fuzziness == 0 {
+        return clauses;
+    }
+    let searcher = state.index_reader.searcher();
+    let fields = &state.job_fields;
+    for (field, boost) in [
+        (fields.title, boosts.title),
+        (fields.description, boosts.description),
+    ] {
+        if boost == 0.0 {
+            continue;
+        }
+        let Ok(analyzer) = searcher.index().tokenizer_for_field(field) else {
+            continue;
+        };
+        for word in words {
+            if !word.chars().all(char::is_alphanumeric) {
+                continue;
+            }
+            let mut tokens = Vec::new();
+            analyzer
+                .token_stream(word)
+                .process(&mut |token| tokens.push(token.text.clone()));
+            for token in tokens {
+                let distance = fuzziness.min(spelling::max_edits(token.chars().count()));
+                if distance == 0 {
+                    continue;
+                }
+                let query: Box<dyn TantivyQuery> = Box::new(FuzzyTermQuery::new(
+                    Term::from_field_text(field, &token),
+                    distance,
+                    true,
+                ));
+                if boost == 1.0 {
+                    clauses.push((Occur::Should, query));
+                } else {
+                    clauses.push((Occur::Should, Box::new(BoostQuery::new(query, boost))));
+                }
+            }
+        }
+    }
+    clauses
+}
+
+/// Fields a query can name with `field:value`
+const SCOPED_FIELDS: [&str; 3] = ["title", "company", "description"];
+
+/// Checks the `field:` prefixes of a query and normalizes them: names are
+/// lowercased and a space after a known one's colon dropped, so
+/// `Title: "senior rust"` searches the title. A prefix naming any other
+/// field is an error rather than text, so a typo like `titel:rust` isn't
+/// silently searched as words. A colon followed by a space or `//` after an
+/// unknown name ("frontend: react", a URL) is left as text.
+fn with_scoped_fields(query_str: &str) -> Result<Cow<'_, str>, QueryParserError> {
+    if !query_str.contains(':') {
+        return Ok(Cow::Borrowed(query_str));
+    }
+    let chars: Vec<char> = query_str.chars().collect();
+    let word_start = |i: usize| {
+        let boundary = |c: char| c.is_whitespace() || c == '(';
+        i == 0
+            || boundary(chars[i - 1])
+            || (matches!(chars[i - 1], '+' | '-') && (i == 1 || boundary(chars[i - 2])))
+    };
+
+    let mut rewritten = String::with_capacity(query_str.len());
+    let mut in_phrase = false;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '"' {
+            in_phrase = !in_phrase;
+        }
+        if in_phrase || !c.is_ascii_alphabetic() || !word_start(i) {
+            rewritten.push(c);
+            i += 1;
+            continue;
+        }
+        let end = i + chars[i..]
+            .iter()
+            .take_while(|c| c.is_ascii_alphanumeric() || **c == '_')
+            .count();
+        if chars.get(end) != Some(&':') {
+            rewritten.extend(&chars[i..end]);
+            i = end;
+            continue;
+        }
+        let name = chars[i..end].iter().collect::<String>().to_lowercase();
+        let value = chars[end + 1..]
+            .iter()
+            .position(|c| !c.is_whitespace())
+            .map(|skip| end + 1 + skip);
+        if SCOPED_FIELDS.contains(&name.as_str()) {
+            if let Some(value) = value {
+                rewritten.push_str(&name);
+                rewritten.push(':');
+                i = value;
+                continue;
+            }
+        } else if value == Some(end + 1) && !chars[end + 1..].starts_with(&['/', '/']) {
+            return Err(QueryParserError::SyntaxError(format!(
+                "unknown field {:?}, a search can name {}",
+                name,
+                SCOPED_FIELDS.map(|field| format!("{}:", field)).join(", ")
+            )));
+        }
+        rewritten.extend(&chars[i..=end]);
+        i = end + 1;
+    }
+    Ok(Cow::Owned(rewritten))
+}
+
+/// Rewrites `NOT word` into the parser's `-word`. The parser reads
+/// `rust AND NOT go` as "rust" and a clause that only excludes, which
+/// matches nothing; `+rust -go` is what was meant. Queries with OR or
+/// parentheses are left as typed.
+fn with_not_as_exclusion(query_str: &str) -> Cow<'_, str> {
+    // Whitespace-separated parts, a quoted phrase being one part
+    let mut parts: Vec<String> = Vec::new();
+    let mut part = String::new();
+    let mut in_phrase = false;
+    for c in query_str.chars() {
+        if c.is_whitespace() && !in_phrase {
+            if !part.is_empty() {
+                parts.push(std::mem::take(&mut part));
+            }
+            continue;
+        }
+        if c == '"' {
+            in_phrase = !in_phrase;
+        }
+        part.push(c);
+    }
+    if !part.is_empty() {
+        parts.push(part);
+    }
+    if !parts.iter().any(|part| part == "NOT")
+        || parts
+            .iter()
+            .any(|part| part == "OR" || part.contains(['(', ')']))
+    {
+        return Cow::Borrowed(query_str);
+    }
+
+    let conjunction = parts.iter().any(|part| part == "AND");
+    let mut rewritten: Vec<String> = Vec::with_capacity(parts.len());
+    let mut negate = false;
+    for part in parts {
+        match part.as_str() {
+            "AND" => {}
+            "NOT" if !negate => negate = true,
+            _ if negate => {
+                rewritten.push(format!("-{}", part));
+                negate = false;
+            }
+            _ if conjunction && !part.starts_with(['+', '-']) => {
+                rewritten.push(format!("+{}", part));
+            }
+            _ => rewritten.push(part),
+        }
+    }
+    if negate {
+        rewritten.push("NOT".to_string());
+    }
+    Cow::Owned(rewritten.join(" "))
+}
+
+/// Gives every quoted phrase in `query_str` that doesn't set its own `~N`
+/// the slop `slop`, in the query parser's `"..."~N` syntax
+fn with_phrase_slop(query_str: &str, slop: u32) -> Cow<'_, str> {
+    if slop == 0 || !query_str.contains('"') {
+        return Cow::Borrowed(query_str);
+    }
+    let mut rewritten = String::with_capacity(query_str.len() + 8);
+    let mut in_phrase = false;
+    let mut chars = query_str.chars().peekable();
+    while let Some(c) = chars.next() {
+        rewritten.push(c);
+        if c != '"' {
+            continue;
+        }
+        if in_phrase && chars.peek() != Some(&'~') {
+            rewritten.push_str(&format!("~{}", slop));
+        }
+        in_phrase = !in_phrase;
+    }
+    Cow::Owned(rewritten)
+}
+
+/// Checks the `boost_*` parameters, filling in the configured weight of each field left out
+fn parse_field_boosts(params: &SearchParams, defaults: FieldBoosts) -> Result<FieldBoosts, String> {
+    let boost = |name: &str, value: Option<f32>, default: f32| match value {
+        None => Ok(default),
+        Some(weight) if weight.is_finite() && weight >= 0.0 => Ok(weight),
+        Some(weight) => Err(format!(
+            "{} must be a finite number of at least 0, got {}",
+            name, weight
+        )),
+    };
+    Ok(FieldBoosts {
+        title: boost("boost_title", params.boost_title, defaults.title)?,
+        description: boost(
+            "boost_description",
+            params.boost_description,
+            defaults.description,
+        )?,
+        company: boost("boost_company", params.boost_company, defaults.company)?,
+    })
+}
+
+/// Parser for keywords weighted by `boosts`: the shared one for the
+/// configured weights, otherwise one built for this search
+fn keyword_parser(state: &AppState, boosts: FieldBoosts) -> Cow<'_, QueryParser> {
+    if boosts == state.config.field_boosts {
+        return Cow::Borrowed(&state.query_parser);
+    }
+    Cow::Owned(boosted_parser(
+        state.index_reader.searcher().index(),
+        &state.job_fields,
+        boosts,
+    ))
+}
+
+/// Query parser searching title and description, and company names when
+/// they have a weight, with each field's matches weighted by `boosts`
+fn boosted_parser(index: &Index, fields: &JobFields, boosts: FieldBoosts) -> QueryParser {
+    let mut default_fields = vec![fields.title, fields.description];
+    if boosts.company > 0.0 {
+        default_fields.push(fields.company);
+    }
+    let mut parser = QueryParser::for_index(index, default_fields);
+    parser.set_field_boost(fields.title, boosts.title);
+    parser.set_field_boost(fields.description, boosts.description);
+    parser.set_field_boost(fields.company, boosts.company);
+    parser
 }
 
-/// Builds the Tantivy schema for job indexing
-fn build_schema() -> Schema {
-    let mut schema_builder = Schema::builder();
+/// Builds the Tantivy query for a search string.
+///
+/// Words ending in `*` (e.g. "develop*") become prefix matches against the
+/// default fields; everything else goes through the regular query parser,
+/// after synonyms are expanded and stopwords dropped.
+/// All parts are ORed together, like the parser's own default, with each
+/// field's matches weighted by `boosts`, quoted phrases match with up to
+/// `slop` words in between, and plain words also match within `fuzziness`
+/// edits. An empty string matches every job.
+fn build_query(
+    state: &AppState,
+    query_str: &str,
+    boosts: FieldBoosts,
+    slop: u32,
+    fuzziness: u8,
+) -> Result<Box<dyn TantivyQuery>, QueryParserError> {
+    if query_str.is_empty() {
+        return Ok(Box::new(AllQuery));
+    }
+    let query_str = with_scoped_fields(query_str)?;
+    let query_str = with_not_as_exclusion(&query_str);
+    let query_str = with_phrase_slop(&query_str, slop);
 
-    // Title: searchable and stored (returned in results)
-    schema_builder.add_text_field("title", TEXT | STORED);
+    let mut prefixes = Vec::new();
+    let mut words = Vec::new();
 
-    // Company: searchable and stored
-    schema_builder.add_text_field("company", TEXT | STORED);
+    for word in query_str.split_whitespace() {
+        match word.strip_suffix('*') {
+            Some(prefix)
+                if prefix.chars().count() >= MIN_WILDCARD_PREFIX
+                    && prefix.chars().all(char::is_alphanumeric)
+                    && prefixes.len() < MAX_WILDCARD_TERMS =>
+            {
+                prefixes.push(prefix.to_lowercase());
+            }
+            Some(prefix) => words.push(prefix),
+            None => words.push(word),
+        }
+    }
 
-    // Description: searchable but not stored (saves space)
-    schema_builder.add_text_field("description", TEXT);
+    let words = state.vocabulary().rewrite(&words);
+    let text_query = parse_text(state, &keyword_parser(state, boosts), &words.join(" "))?;
+    let fuzzy = fuzzy_clauses(state, &words, boosts, fuzziness);
+    if prefixes.is_empty() && fuzzy.is_empty() {
+        return Ok(text_query);
+    }
 
-    // Salary: indexed for filtering, but as i64 field
-    let int_options = IntOptions::default().set_indexed();
-    schema_builder.add_i64_field("salary_min", int_options);
+    let fields = &state.job_fields;
+    let mut clauses: Vec<(Occur, Box<dyn TantivyQuery>)> = Vec::new();
+    if !words.is_empty() {
+        clauses.push((Occur::Should, text_query));
+    }
+    for prefix in &prefixes {
+        // The prefix is alphanumeric only, so it is safe to embed in a regex
+        let pattern = format!("{}.*", prefix);
+        // Same fields and weights as the keywords: the company only when boosted
+        for (field, boost) in [
+            (fields.title, boosts.title),
+            (fields.description, boosts.description),
+            (fields.company, boosts.company),
+        ] {
+            if field == fields.company && boosts.company <= 0.0 {
+                continue;
+            }
+            let regex_query: Box<dyn TantivyQuery> = Box::new(
+                RegexQuery::from_pattern(&pattern, field)
+                    .map_err(|_| QueryParserError::SyntaxError(pattern.clone()))?,
+            );
+            if boost == 1.0 {
+                clauses.push((Occur::Should, regex_query));
+            } else {
+                clauses.push((Occur::Should, Box::new(BoostQuery::new(regex_query, boost))));
+            }
+        }
+    }
+    clauses.extend(fuzzy);
 
-    schema_builder.build()
+    Ok(Box::new(BooleanQuery::new(clauses)))
 }
 
-/// Creates or opens the search index and indexes all jobs
-fn create_index(jobs: &[Job]) -> tantivy::Result<Index> {
-    let schema = build_schema();
-    let index_path = Path::new("search_index");
+/// Builds a substring query: a word matches when all of its trigrams appear
+/// in the title or the company, so "shop" finds "Shopify". Words are ORed;
+/// ones too short to have a trigram go through the regular query parser.
+fn build_substring_query(
+    state: &AppState,
+    query_str: &str,
+    boosts: FieldBoosts,
+) -> Result<Box<dyn TantivyQuery>, QueryParserError> {
+    let fields = &state.job_fields;
+    let analyzer = state
+        .index_reader
+        .searcher()
+        .index()
+        .tokenizer_for_field(fields.title_ngram)
+        .map_err(|_| QueryParserError::UnknownTokenizer {
+            field: "title_ngram".to_string(),
+            tokenizer: index::TRIGRAM_TOKENIZER.to_string(),
+        })?;
+
+    let mut clauses: Vec<(Occur, Box<dyn TantivyQuery>)> = Vec::new();
+    let mut short_words = Vec::new();
+    for word in query_str.split_whitespace() {
+        let mut grams = Vec::new();
+        analyzer
+            .token_stream(word)
+            .process(&mut |token| grams.push(token.text.clone()));
+        if grams.is_empty() {
+            short_words.push(word);
+            continue;
+        }
+
+        let field_clauses: Vec<(Occur, Box<dyn TantivyQuery>)> =
+            [fields.title_ngram, fields.company_ngram]
+                .into_iter()
+                .map(|field| {
+                    let all_grams: Vec<(Occur, Box<dyn TantivyQuery>)> = grams
+                        .iter()
+                        .map(|gram| {
+                            let term = Term::from_field_text(field, gram);
+                            let query: Box<dyn TantivyQuery> =
+                                Box::new(TermQuery::new(term, IndexRecordOption::Basic));
+                            (Occur::Must, query)
+                        })
+                        .collect();
+                    let query: Box<dyn TantivyQuery> = Box::new(BooleanQuery::new(all_grams));
+                    (Occur::Should, query)
+                })
+                .collect();
+        clauses.push((Occur::Should, Box::new(BooleanQuery::new(field_clauses))));
+    }
 
-    // Create directory if needed
-    if !index_path.exists() {
-        fs::create_dir_all(index_path)?;
+    if !short_words.is_empty() {
+        let parser = keyword_parser(state, boosts);
+        clauses.push((
+            Occur::Should,
+            parse_text(state, &parser, &short_words.join(" "))?,
+        ));
     }
+    Ok(Box::new(BooleanQuery::new(clauses)))
+}
 
-    // Create or open index
-    let index = if index_path.join("meta.json").exists() {
-        println!("📂 Opening existing index...");
-        Index::open_in_dir(index_path)?
+/// Combines the keyword query with the filters, which every hit must match
+fn search_query(
+    state: &AppState,
+    query_str: &str,
+    substring: bool,
+    filters: &Filters,
+    boosts: FieldBoosts,
+    slop: u32,
+    fuzziness: u8,
+) -> Result<Box<dyn TantivyQuery>, QueryParserError> {
+    let text_query = if substring && !query_str.is_empty() {
+        build_substring_query(state, query_str, boosts)?
     } else {
-        println!("📝 Creating new index...");
-        Index::create_in_dir(index_path, schema.clone())?
+        build_query(state, query_str, boosts, slop, fuzziness)?
     };
+    if filters.is_empty() {
+        return Ok(text_query);
+    }
+
+    let mut clauses = vec![(Occur::Must, text_query)];
+    clauses.extend(filters.clauses(&state.job_fields));
+    Ok(Box::new(BooleanQuery::new(clauses)))
+}
+
+/// Per-field highlighters for title, company and description, where highlightable
+enum Highlighting {
+    /// Markup around matches, in `highlights`
+    Html([Option<SnippetGenerator>; 3]),
+    /// Character ranges of matches, in `highlight_offsets`
+    Offsets([Option<OffsetFinder>; 3]),
+}
+
+/// A search that has run, with its hits' documents not loaded yet
+struct SearchHits {
+    query: String,
+    searcher: Searcher,
+    hits: Vec<(f32, DocAddress)>,
+    /// Matches across all pages
+    total_hits: usize,
+    /// How matches in title, company and description are reported
+    highlighting: Highlighting,
+    /// Counts each hit's matched query terms, when there are any
+    term_counter: Option<TermCounter>,
+    /// Debug form of the executed query, when asked for
+    parsed_query: Option<String>,
+    /// Page math, when the search was paged
+    pagination: Option<Pagination>,
+    /// Salary bounds over every match
+    salary_range: Option<SalaryBounds>,
+    /// Company and location counts over every match, when asked for
+    facets: Option<FieldFacets>,
+    /// Length of description excerpts and unhighlighted snippets
+    excerpt_chars: usize,
+    /// Corrected queries, when nothing matched
+    suggestions: Vec<String>,
+}
+
+impl SearchHits {
+    fn empty(state: &AppState, query: String, pagination: Option<Pagination>) -> SearchHits {
+        SearchHits {
+            query,
+            searcher: state.index_reader.searcher(),
+            hits: vec![],
+            total_hits: 0,
+            highlighting: Highlighting::Html([None, None, None]),
+            term_counter: None,
+            parsed_query: None,
+            pagination,
+            salary_range: None,
+            facets: None,
+            excerpt_chars: HIGHLIGHT_EXCERPT_CHARS,
+            suggestions: Vec::new(),
+        }
+    }
+
+    /// Loads one hit's stored fields into a result
+    fn load(&self, state: &AppState, score: f32, doc_address: DocAddress) -> Option<SearchResult> {
+        let retrieved_doc = self.searcher.doc(doc_address).ok()?;
+        let fields = &state.job_fields;
+
+        let title = retrieved_doc
+            .get_first(fields.title)
+            .and_then(|v| v.as_text())
+            .unwrap_or("Unknown")
+            .to_string();
+
+        let title_normalized = retrieved_doc
+            .get_first(fields.title_normalized)
+            .and_then(|v| v.as_text())
+            .unwrap_or_default()
+            .to_string();
 
-    // Get field handles
-    let title_field = index.schema().get_field("title").unwrap();
-    let company_field = index.schema().get_field("company").unwrap();
-    let description_field = index.schema().get_field("description").unwrap();
-    let salary_field = index.schema().get_field("salary_min").unwrap();
+        let company = retrieved_doc
+            .get_first(fields.company)
+            .and_then(|v| v.as_text())
+            .unwrap_or("Unknown")
+            .to_string();
 
-    // Create index writer with 50MB heap
-    let mut index_writer = index.writer(50_000_000)?;
+        let text = |field: Field| {
+            retrieved_doc
+                .get_first(field)
+                .and_then(|v| v.as_text())
+                .unwrap_or_default()
+                .to_string()
+        };
+        let location = text(fields.location);
+        let url = text(fields.url);
+        let salary_min = retrieved_doc
+            .get_first(fields.salary_min)
+            .and_then(|v| v.as_i64());
+
+        // The whole description when it is stored, or else its excerpt
+        let description_text = [Some(fields.description), fields.description_excerpt]
+            .into_iter()
+            .flatten()
+            .find_map(|field| retrieved_doc.get_first(field).and_then(|v| v.as_text()));
+        let description = description_text.map(|text| truncate_chars(text, MAX_DESCRIPTION_CHARS));
 
-    // Clear existing documents (for fresh re-indexing)
-    index_writer.delete_all_documents()?;
+        // Dates are only indexed, so read them from the fast field
+        let timestamp = self
+            .searcher
+            .segment_reader(doc_address.segment_ord)
+            .fast_fields()
+            .i64(fields.date)
+            .map_or(0, |dates| dates.get_val(doc_address.doc_id));
+        let freshness = Freshness::from_timestamp(timestamp, unix_now());
 
-    println!("📊 Indexing {} jobs...", jobs.len());
+        let (highlights, highlight_offsets, snippet) = match &self.highlighting {
+            Highlighting::Html(generators) => {
+                let highlight = |index: usize, text: Option<&str>| {
+                    let generator = generators[index].as_ref()?;
+                    highlight::render_snippet(
+                        &generator.snippet(text?),
+                        &state.config.highlight_pre_tag,
+                        &state.config.highlight_post_tag,
+                    )
+                };
+                let highlights = Highlights {
+                    title: highlight(0, Some(&title)),
+                    company: highlight(1, Some(&company)),
+                    description: highlight(2, description_text),
+                };
+                let snippet = snippet(
+                    &state.config.snippet_fields,
+                    &highlights,
+                    [Some(&title), Some(&company), description.as_ref()],
+                    self.excerpt_chars,
+                );
+                (highlights, None, snippet)
+            }
+            Highlighting::Offsets(finders) => {
+                let offsets = |index: usize, text: Option<&str>| {
+                    let ranges = finders[index].as_ref()?.offsets(text?);
+                    (!ranges.is_empty()).then_some(ranges)
+                };
+                let offsets = HighlightOffsets {
+                    title: offsets(0, Some(&title)),
+                    company: offsets(1, Some(&company)),
+                    description: offsets(2, description.as_deref()),
+                };
+                (Highlights::default(), Some(offsets), None)
+            }
+        };
+
+        Some(SearchResult {
+            title,
+            title_normalized,
+            company,
+            location,
+            url,
+            salary_min,
+            score,
+            description,
+            highlights,
+            snippet,
+            highlight_offsets,
+            matched_terms: self
+                .term_counter
+                .as_ref()
+                .map(|counter| counter.count(&self.searcher, doc_address)),
+            freshness,
+        })
+    }
 
-    // Index each job
-    for job in jobs {
-        let mut doc = tantivy::Document::new();
-        doc.add_text(title_field, &job.title);
-        doc.add_text(company_field, &job.company);
-        doc.add_text(description_field, &job.description);
-        if let Some(salary) = job.salary_min {
-            doc.add_i64(salary_field, salary);
+    /// Loads every hit into a complete response
+    fn into_response(self, state: &AppState) -> SearchResponse {
+        let results: Vec<SearchResult> = self
+            .hits
+            .iter()
+            .filter_map(|&(score, doc_address)| self.load(state, score, doc_address))
+            .collect();
+        SearchResponse {
+            query: self.query,
+            total_results: results.len(),
+            total_hits: self.total_hits,
+            results,
+            parsed_query: self.parsed_query,
+            pagination: self.pagination,
+            salary_range: self.salary_range,
+            facets: self.facets,
+            suggestions: self.suggestions,
         }
-        index_writer.add_document(doc)?;
     }
+}
+
+/// Runs `search` on the blocking pool, so a slow query doesn't hold up an
+/// async worker, and answers 503 once it has run for `SEARCH_TIMEOUT_MS`.
+/// Tantivy can't interrupt a search, so a timed-out one still finishes in
+/// the background; only the request stops waiting for it.
+async fn timed_search<T, F>(state: &Arc<AppState>, search: F) -> Result<T, (StatusCode, String)>
+where
+    F: FnOnce(&AppState) -> Result<T, (StatusCode, String)> + Send + 'static,
+    T: Send + 'static,
+{
+    let task = {
+        let state = state.clone();
+        tokio::task::spawn_blocking(move || search(&state))
+    };
+    let joined = match state.config.search_timeout_ms {
+        Some(ms) => tokio::time::timeout(Duration::from_millis(ms), task)
+            .await
+            .map_err(|_| {
+                (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    format!(
+                        "Search timed out after {} ms; try a more specific query",
+                        ms
+                    ),
+                )
+            })?,
+        None => task.await,
+    };
+    joined.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Search failed: {}", e),
+        )
+    })?
+}
 
-    // Commit changes
-    index_writer.commit()?;
-    println!("✅ Indexing complete!");
+/// Runs an index write on the blocking pool: committing flushes segments to
+/// disk, and the writer lock is held meanwhile, so neither should stall an
+/// async worker
+async fn blocking_write<T, F>(state: &Arc<AppState>, write: F) -> Result<T, (StatusCode, String)>
+where
+    F: FnOnce(&AppState) -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let state = state.clone();
+    tokio::task::spawn_blocking(move || write(&state))
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Indexing failed: {}", e),
+            )
+        })
+}
 
-    Ok(index)
+/// Fills in the settings of the request's `profile` that it doesn't set itself
+fn apply_profile(mut params: SearchParams) -> Result<SearchParams, String> {
+    let Some(profile) = params.profile.as_deref() else {
+        return Ok(params);
+    };
+    let preset = profile.parse::<Profile>()?.preset();
+    params.sort = params.sort.or(preset.sort.map(str::to_string));
+    params.boost_title = params.boost_title.or(preset.boost_title);
+    params.recency_half_life_days = params
+        .recency_half_life_days
+        .or(preset.recency_half_life_days);
+    Ok(params)
 }
 
-/// Handler for GET /search?q=<keywords>
-async fn search_handler(
-    State(state): State<Arc<AppState>>,
-    Query(params): Query<SearchParams>,
-) -> Json<SearchResponse> {
+/// Runs a search without loading documents, with filters already parsed from
+/// `params` (and possibly extended). Invalid parameters and a query the parser
+/// rejects fail with a 400 (see `invalid_query`), and an index that can't be
+/// searched with a 500.
+fn run_filtered_search(
+    state: &AppState,
+    params: SearchParams,
+    filters: Filters,
+) -> Result<SearchHits, (StatusCode, String)> {
+    let params = apply_profile(params).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    let sort_keys = params
+        .sort
+        .as_deref()
+        .map(sort::parse_sort)
+        .transpose()
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?
+        .unwrap_or_default();
+    let offsets = match params.highlights.as_deref() {
+        None | Some("html") => false,
+        Some("offsets") => true,
+        Some(other) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("unknown highlights {:?}: expected html or offsets", other),
+            ));
+        }
+    };
+    if params.max_per_company == Some(0) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "max_per_company must be at least 1".to_string(),
+        ));
+    }
+    let per_page = params.limit.unwrap_or(MAX_RESULTS);
+    if !(1..=MAX_PAGE_SIZE).contains(&per_page) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("limit must be between 1 and {}", MAX_PAGE_SIZE),
+        ));
+    }
+    let offset = match (params.page, params.offset) {
+        (Some(_), Some(_)) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "give either page or offset, not both".to_string(),
+            ));
+        }
+        (Some(0), None) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "page must be at least 1".to_string(),
+            ));
+        }
+        (Some(page), None) => (page - 1).saturating_mul(per_page),
+        (None, offset) => offset.unwrap_or(0),
+    };
+    if offset > MAX_OFFSET {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("offset must be at most {}", MAX_OFFSET),
+        ));
+    }
+    let boosts = parse_field_boosts(&params, state.config.field_boosts)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    let slop = parse_slop(&params).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    let fuzziness = parse_fuzziness(&params).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    let paged = params.limit.is_some() || params.offset.is_some() || params.page.is_some();
+    let no_hits = paged.then(|| Pagination::new(offset, per_page, 0));
     let query_str = params.q.unwrap_or_default();
 
-    if query_str.is_empty() {
-        return Json(SearchResponse {
-            query: query_str,
-            total_results: 0,
-            results: vec![],
-        });
+    // An empty query browses all (filtered) jobs; with no filters this has to
+    // be enabled, otherwise it matches nothing
+    let browse_all = query_str.is_empty();
+    if browse_all && filters.is_empty() && !state.config.allow_match_all {
+        return Ok(SearchHits::empty(state, query_str, no_hits));
     }
 
     // Parse the query
-    let query = match state.query_parser.parse_query(&query_str) {
+    let substring = params.substring.unwrap_or(false);
+    let query = match search_query(
+        state, &query_str, substring, &filters, boosts, slop, fuzziness,
+    ) {
         Ok(q) => q,
-        Err(_) => {
-            return Json(SearchResponse {
-                query: query_str,
-                total_results: 0,
-                results: vec![],
-            });
-        }
+        Err(err) => return Err(invalid_query(&query_str, err)),
     };
-
-    // Get field handles for retrieving stored fields
-    let title_field = state.schema.get_field("title").unwrap();
-    let company_field = state.schema.get_field("company").unwrap();
+    let parsed_query = params
+        .debug
+        .unwrap_or(false)
+        .then(|| format!("{:?}", query));
 
     // Search the index
     let searcher = state.index_reader.searcher();
-    let top_docs = match searcher.search(&query, &TopDocs::with_limit(10)) {
-        Ok(docs) => docs,
-        Err(_) => {
-            return Json(SearchResponse {
-                query: query_str,
-                total_results: 0,
-                results: vec![],
+    // Collect every hit up to the end of the page, then drop the ones before it
+    let limit = if params.max_per_company.is_some() {
+        DIVERSITY_CANDIDATES.max(offset + per_page)
+    } else {
+        offset + per_page
+    };
+    // Every collector is paired with a `Count`, so the total number of
+    // matches comes out of the same pass as the page
+    let top_docs = if !sort_keys.is_empty() {
+        searcher
+            .search(
+                &query,
+                &(Count, sort::sorted_by(&sort_keys, limit, &state.job_fields)),
+            )
+            .map(|(count, docs)| {
+                let docs = docs
+                    .into_iter()
+                    .map(|(_, doc_address)| (1.0, doc_address))
+                    .collect();
+                (count, docs)
+            })
+    } else if browse_all {
+        // Nothing to rank by relevance, so show the best paid jobs first
+        searcher
+            .search(
+                &query,
+                &(
+                    Count,
+                    TopDocs::with_limit(limit)
+                        .order_by_fast_field::<i64>(state.job_fields.salary_min),
+                ),
+            )
+            .map(|(count, docs)| {
+                let docs = docs
+                    .into_iter()
+                    .map(|(_, doc_address)| (1.0, doc_address))
+                    .collect();
+                (count, docs)
+            })
+    } else {
+        // Tantivy already scores with the default parameters
+        let bm25_params = Bm25Params {
+            k1: state.config.bm25_k1,
+            b: state.config.bm25_b,
+        };
+        let bm25 = if bm25_params == Bm25Params::default() {
+            None
+        } else {
+            Bm25::new(&searcher, &*query, bm25_params).ok()
+        };
+        let recency = params
+            .recency_half_life_days
+            .filter(|days| days.is_finite() && *days > 0.0)
+            .map(|half_life_days| RecencyBoost {
+                date_field: state.job_fields.date,
+                now: unix_now(),
+                half_life_secs: half_life_days * 86_400.0,
             });
+        let featured = params
+            .boost_featured
+            .unwrap_or(false)
+            .then_some(FeaturedBoost {
+                featured_field: state.job_fields.featured,
+                factor: FEATURED_BOOST,
+            });
+        if bm25.is_none() && recency.is_none() && featured.is_none() {
+            searcher.search(&query, &(Count, TopDocs::with_limit(limit)))
+        } else {
+            searcher.search(
+                &query,
+                &(Count, ranking::adjusted(limit, bm25, recency, featured)),
+            )
         }
     };
+    let (total_hits, mut hits) = top_docs.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to search: {}", e),
+        )
+    })?;
 
-    // Collect results
-    let mut results = Vec::new();
-    for (score, doc_address) in top_docs {
-        if let Ok(retrieved_doc) = searcher.doc(doc_address) {
-            let title = retrieved_doc
-                .get_first(title_field)
-                .and_then(|v| v.as_text())
-                .unwrap_or("Unknown")
-                .to_string();
+    // Diversify: keep each company's best hits, compared by stored name
+    if let Some(max_per_company) = params.max_per_company {
+        hits = ranking::cap_per_key(hits, max_per_company, |doc_address| {
+            searcher
+                .doc(doc_address)
+                .ok()
+                .and_then(|doc| {
+                    doc.get_first(state.job_fields.company)
+                        .and_then(|value| value.as_text())
+                        .map(|company| company.trim().to_lowercase())
+                })
+                .unwrap_or_default()
+        });
+    }
+    hits.drain(..offset.min(hits.len()));
+    hits.truncate(per_page);
 
-            let company = retrieved_doc
-                .get_first(company_field)
-                .and_then(|v| v.as_text())
-                .unwrap_or("Unknown")
-                .to_string();
+    let pagination = paged.then(|| Pagination::new(offset, per_page, total_hits));
+    // In the requested currency, from the dollar figures the filter compares
+    let currency_rate = params.currency.as_deref().and_then(usd_rate);
+    let salary_field = match currency_rate {
+        Some(_) => state.job_fields.salary_min_usd,
+        None => state.job_fields.salary_min,
+    };
+    let salary_range = facets::salary_bounds(&searcher, &*query, salary_field)
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to compute the salary range: {}", e),
+            )
+        })?
+        .map(|bounds| currency_rate.map_or(bounds, |rate| bounds.in_currency(rate)));
+    let facets = if params.facets.unwrap_or(false) {
+        let fields = &state.job_fields;
+        let counts = facets::field_facets(
+            &searcher,
+            &*query,
+            fields.company_key_facet,
+            fields.location_facet,
+            MAX_FACET_VALUES,
+        )
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to count facets: {}", e),
+            )
+        })?;
+        Some(counts)
+    } else {
+        None
+    };
 
-            results.push(SearchResult {
-                title,
-                company,
-                score,
-            });
-        }
-    }
+    // Nothing found: offer the query with unknown words respelled after the
+    // closest words in the searched fields
+    let suggestions = if total_hits == 0 && !browse_all && !substring {
+        spelling::corrections(
+            &searcher,
+            &[state.job_fields.title, state.job_fields.description],
+            &query_str,
+            MAX_SPELLING_SUGGESTIONS,
+        )
+        .unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+
+    // One snippet generator (or offset finder) per highlighted field, all fed
+    // the same query terms. Descriptions are highlighted in the stored text,
+    // whole or excerpt, and titles and company names are always stored.
+    let fields = &state.job_fields;
+    let excerpt_chars = params
+        .snippet_chars
+        .map_or(HIGHLIGHT_EXCERPT_CHARS, |chars| {
+            chars.clamp(*SNIPPET_CHARS_RANGE.start(), *SNIPPET_CHARS_RANGE.end())
+        });
+    let highlighted = [
+        (fields.title, HIGHLIGHT_FULL_FIELD_CHARS),
+        (fields.company, HIGHLIGHT_FULL_FIELD_CHARS),
+        (fields.description, excerpt_chars),
+    ];
+    let highlighting = if offsets {
+        Highlighting::Offsets(
+            highlighted.map(|(field, _)| OffsetFinder::new(&searcher, &*query, field).ok()),
+        )
+    } else {
+        Highlighting::Html(highlighted.map(|(field, max_num_chars)| {
+            highlight::snippet_generator(&searcher, &*query, field, max_num_chars).ok()
+        }))
+    };
+
+    let term_counter =
+        TermCounter::new(&*query, &[fields.title, fields.company, fields.description]);
 
-    Json(SearchResponse {
+    Ok(SearchHits {
         query: query_str,
-        total_results: results.len(),
-        results,
+        searcher,
+        hits,
+        total_hits,
+        highlighting,
+        term_counter,
+        parsed_query,
+        pagination,
+        salary_range,
+        facets,
+        excerpt_chars,
+        suggestions,
     })
 }
 
-/// Handler for GET / (root)
-async fn root_handler() -> &'static str {
-    "🔍 Job Search Engine API\n\nEndpoints:\n  GET /search?q=<keywords> - Search for jobs\n\nExample:\n  curl 'http://127.0.0.1:3000/search?q=rust developer'"
+/// Body formats the search endpoint can produce
+enum ResponseFormat {
+    Json,
+    Ndjson,
 }
 
-#[tokio::main]
-async fn main() {
-    println!("🚀 Starting Job Search Engine Server...\n");
+/// Streams results as newline-delimited JSON, loading each document only
+/// when the client is ready for its line
+fn ndjson_response(state: Arc<AppState>, search: SearchHits) -> Response {
+    let hits = search.hits.clone();
+    let lines = stream::iter(hits.into_iter().filter_map(move |(score, doc_address)| {
+        let result = search.load(&state, score, doc_address)?;
+        Some(serde_json::to_vec(&result).map(|mut line| {
+            line.push(b'\n');
+            line
+        }))
+    }));
 
-    // Load jobs from JSON file
-    let jobs_path = Path::new("data/jobs.json");
+    (
+        [(header::CONTENT_TYPE, "application/x-ndjson")],
+        StreamBody::new(lines),
+    )
+        .into_response()
+}
 
-    let jobs: Vec<Job> = if jobs_path.exists() {
-        println!("📂 Loading jobs from {:?}", jobs_path);
-        let content = fs::read_to_string(jobs_path).expect("Failed to read jobs.json");
-        serde_json::from_str(&content).expect("Failed to parse jobs.json")
-    } else {
-        println!("⚠️  No jobs.json found. Run the scraper first!");
-        println!("   cargo run -p scraper");
-        vec![]
-    };
+/// Handler for GET /search?q=<keywords>
+///
+/// With `format=ndjson` the results are streamed one JSON object per line.
+#[utoipa::path(
+    get,
+    path = "/search",
+    params(SearchParams),
+    responses(
+        (status = 200, description = "Jobs matching the query", body = SearchResponse),
+        (status = 400, description = "Invalid filter parameter, or a query the parser rejects"),
+        (status = 500, description = "The index couldn't be searched"),
+        (status = 503, description = "The search ran past SEARCH_TIMEOUT_MS")
+    )
+)]
+async fn search_handler(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<SearchParams>,
+) -> Response {
+    search_response(state, params, None).await
+}
 
-    println!("📊 Loaded {} jobs\n", jobs.len());
+/// Handler for POST /search
+///
+/// Takes the GET /search parameters as a JSON body, plus an optional `filter`
+/// tree of `and` / `or` / `not` groups over field conditions, up to
+/// `MAX_FILTER_DEPTH` levels deep.
+#[utoipa::path(
+    post,
+    path = "/search",
+    request_body = SearchRequest,
+    responses(
+        (status = 200, description = "Jobs matching the query and filter", body = SearchResponse),
+        (status = 400, description = "Invalid parameter or filter, or a query the parser rejects"),
+        (status = 500, description = "The index couldn't be searched"),
+        (status = 503, description = "The search ran past SEARCH_TIMEOUT_MS")
+    )
+)]
+async fn search_post_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<SearchRequest>,
+) -> Response {
+    search_response(state, request.params, request.filter).await
+}
 
-    // Create search index
-    let index = create_index(&jobs).expect("Failed to create search index");
-    let schema = index.schema();
+/// Runs a search for GET or POST /search and renders it in the requested format
+async fn search_response(
+    state: Arc<AppState>,
+    params: SearchParams,
+    filter: Option<FilterNode>,
+) -> Response {
+    let format = match params.format.as_deref() {
+        None | Some("json") => ResponseFormat::Json,
+        Some("ndjson") => ResponseFormat::Ndjson,
+        Some(other) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                format!("unknown format {:?}: expected json or ndjson", other),
+            )
+                .into_response();
+        }
+    };
 
-    // Create index reader
-    let reader = index
-        .reader_builder()
-        .reload_policy(ReloadPolicy::OnCommit)
-        .try_into()
-        .expect("Failed to create index reader");
+    let mut filters = match parse_filters(&params) {
+        Ok(filters) => filters,
+        Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+    };
+    if let Some(filter) = filter {
+        let searcher = state.index_reader.searcher();
+        match filter_tree::compile(&filter, searcher.index(), &state.job_fields) {
+            Ok(expression) => filters.expression = Some(expression),
+            Err(e) => return (StatusCode::BAD_REQUEST, e).into_response(),
+        }
+    }
 
-    // Create query parser for title and description fields
-    let title_field = schema.get_field("title").unwrap();
-    let description_field = schema.get_field("description").unwrap();
-    let query_parser = QueryParser::for_index(&index, vec![title_field, description_field]);
+    // Later pages repeat a search already counted
+    let first_page = params.offset.unwrap_or(0) == 0 && params.page.unwrap_or(1) <= 1;
+    let logged_query = params.q.clone().filter(|_| first_page);
+    let search = match timed_search(&state, move |state| {
+        run_filtered_search(state, params, filters)
+    })
+    .await
+    {
+        Ok(search) => search,
+        Err(e) => return e.into_response(),
+    };
+    if let Some(query) = logged_query {
+        log_query(&state, &query);
+    }
 
-    // Create shared state
-    let state = Arc::new(AppState {
-        index_reader: reader,
-        query_parser,
-        schema,
-    });
+    match format {
+        ResponseFormat::Json => Json(search.into_response(&state)).into_response(),
+        ResponseFormat::Ndjson => ndjson_response(state, search),
+    }
+}
 
-    // Build router
-    let app = Router::new()
+/// Handler for GET /companies/{company}/jobs?q=<keywords>
+///
+/// Same search as /search, scoped to one company's jobs. The company is
+/// matched by its whole name, ignoring case ("Acme%20Corp" finds "Acme Corp"
+/// but not "Acme"). Without `q`, every job of the company is returned.
+#[utoipa::path(
+    get,
+    path = "/companies/{company}/jobs",
+    params(("company" = String, Path, description = "Company name, URL-encoded"), SearchParams),
+    responses(
+        (status = 200, description = "The company's jobs matching the query", body = SearchResponse),
+        (status = 400, description = "Invalid filter parameter, or a query the parser rejects"),
+        (status = 500, description = "The index couldn't be searched"),
+        (status = 503, description = "The search ran past SEARCH_TIMEOUT_MS")
+    )
+)]
+async fn company_jobs_handler(
+    State(state): State<Arc<AppState>>,
+    Path(company): Path<String>,
+    Query(params): Query<SearchParams>,
+) -> Result<Json<SearchResponse>, (StatusCode, String)> {
+    let mut filters = parse_filters(&params).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    filters.company = Some(company);
+    let search = timed_search(&state, |state| run_filtered_search(state, params, filters)).await?;
+    Ok(Json(search.into_response(&state)))
+}
+
+/// Handler for GET /search/count?q=<keywords>
+///
+/// Same matching as /search, but only counts hits instead of loading documents.
+#[utoipa::path(
+    get,
+    path = "/search/count",
+    params(SearchParams),
+    responses(
+        (status = 200, description = "Number of jobs matching the query", body = CountResponse),
+        (status = 400, description = "Invalid filter parameter, or a query the parser rejects"),
+        (status = 500, description = "The index couldn't be read"),
+        (status = 503, description = "The search ran past SEARCH_TIMEOUT_MS")
+    )
+)]
+async fn count_handler(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<SearchParams>,
+) -> Result<Json<CountResponse>, (StatusCode, String)> {
+    let filters = parse_filters(&params).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    let boosts = parse_field_boosts(&params, state.config.field_boosts)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    let slop = parse_slop(&params).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    let fuzziness = parse_fuzziness(&params).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    let query_str = params.q.unwrap_or_default();
+
+    let count = if query_str.is_empty() && filters.is_empty() && !state.config.allow_match_all {
+        0
+    } else {
+        let query_str = query_str.clone();
+        let substring = params.substring.unwrap_or(false);
+        timed_search(&state, move |state| {
+            let query = search_query(
+                state, &query_str, substring, &filters, boosts, slop, fuzziness,
+            )
+            .map_err(|err| invalid_query(&query_str, err))?;
+            state
+                .index_reader
+                .searcher()
+                .search(&query, &Count)
+                .map_err(|e| {
+                    (
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        format!("Failed to count matches: {}", e),
+                    )
+                })
+        })
+        .await?
+    };
+
+    Ok(Json(CountResponse {
+        query: query_str,
+        count,
+    }))
+}
+
+/// Handler for GET /aggregations/salary?q=<keywords>&bucket=<width>
+///
+/// Same matching as /search, but counts the matches per `salary_min` range
+/// for charting the pay for a query.
+#[utoipa::path(
+    get,
+    path = "/aggregations/salary",
+    params(SearchParams, SalaryHistogramParams),
+    responses(
+        (status = 200, description = "Matching jobs per salary bucket", body = SalaryHistogramResponse),
+        (status = 400, description = "Invalid filter parameter, a query the parser rejects, or a bucket too narrow for the salaries"),
+        (status = 500, description = "The index couldn't be read"),
+        (status = 503, description = "The search ran past SEARCH_TIMEOUT_MS")
+    )
+)]
+async fn salary_histogram_handler(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<SearchParams>,
+    Query(histogram): Query<SalaryHistogramParams>,
+) -> Result<Json<SalaryHistogramResponse>, (StatusCode, String)> {
+    let width = histogram.bucket.unwrap_or(DEFAULT_SALARY_BUCKET);
+    if width < MIN_SALARY_BUCKET {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("bucket must be at least {}", MIN_SALARY_BUCKET),
+        ));
+    }
+    let filters = parse_filters(&params).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    let boosts = parse_field_boosts(&params, state.config.field_boosts)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    let slop = parse_slop(&params).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    let fuzziness = parse_fuzziness(&params).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    let query_str = params.q.unwrap_or_default();
+
+    let buckets = if query_str.is_empty() && filters.is_empty() && !state.config.allow_match_all {
+        Some(Vec::new())
+    } else {
+        let query_str = query_str.clone();
+        let substring = params.substring.unwrap_or(false);
+        timed_search(&state, move |state| {
+            let query = search_query(
+                state, &query_str, substring, &filters, boosts, slop, fuzziness,
+            )
+            .map_err(|err| invalid_query(&query_str, err))?;
+            facets::salary_histogram(
+                &state.index_reader.searcher(),
+                &*query,
+                state.job_fields.salary_min_usd,
+                width,
+                MAX_SALARY_BUCKETS,
+            )
+            .map_err(|e| {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to count salaries: {}", e),
+                )
+            })
+        })
+        .await?
+    };
+    let Some(buckets) = buckets else {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "bucket {} splits the salaries into more than {} buckets",
+                width, MAX_SALARY_BUCKETS
+            ),
+        ));
+    };
+
+    Ok(Json(SalaryHistogramResponse {
+        query: query_str,
+        bucket: width,
+        currency: "USD".to_string(),
+        total: buckets.iter().map(|bucket| bucket.count).sum(),
+        buckets,
+    }))
+}
+
+/// Handler for GET /health
+///
+/// Answers as long as the server is up, including with an empty index.
+#[utoipa::path(
+    get,
+    path = "/health",
+    responses((status = 200, description = "Server is up", body = HealthResponse))
+)]
+async fn health_handler(State(state): State<Arc<AppState>>) -> Json<HealthResponse> {
+    Json(HealthResponse {
+        status: "ok".to_string(),
+        num_docs: state.index_reader.searcher().num_docs(),
+        error: None,
+    })
+}
+
+/// Handler for GET /ready
+///
+/// Readiness: a match-all search for one hit has to run, and has to find a
+/// job when the index has any. /health only counts documents, which a stale
+/// or corrupt reader still does.
+#[utoipa::path(
+    get,
+    path = "/ready",
+    responses(
+        (status = 200, description = "The index answers searches", body = HealthResponse),
+        (status = 503, description = "Searching the index fails", body = HealthResponse)
+    )
+)]
+async fn ready_handler(State(state): State<Arc<AppState>>) -> (StatusCode, Json<HealthResponse>) {
+    let searcher = state.index_reader.searcher();
+    let probe = searcher
+        .search(&AllQuery, &TopDocs::with_limit(1))
+        .map(|hits| hits.len());
+    let response = readiness(searcher.num_docs(), probe);
+    if let Some(error) = &response.error {
+        eprintln!("⚠️  Readiness probe failed: {}", error);
+        return (StatusCode::SERVICE_UNAVAILABLE, Json(response));
+    }
+    (StatusCode::OK, Json(response))
+}
+
+/// Readiness verdict from the document count and a match-all search for one hit
+fn readiness(num_docs: u64, probe: tantivy::Result<usize>) -> HealthResponse {
+    let error = match probe {
+        Ok(0) if num_docs > 0 => Some(format!("no hits from {} documents", num_docs)),
+        Ok(_) => None,
+        Err(e) => Some(e.to_string()),
+    };
+    HealthResponse {
+        status: if error.is_some() { "degraded" } else { "ok" }.to_string(),
+        num_docs,
+        error,
+    }
+}
+
+/// Handler for GET /dashboard
+///
+/// Overview of the whole index in one call. Computing it reads every stored
+/// document, so it runs on the blocking pool and the result is reused for
+/// `DASHBOARD_CACHE_SECS`.
+#[utoipa::path(
+    get,
+    path = "/dashboard",
+    responses(
+        (status = 200, description = "Aggregate numbers for the indexed jobs", body = Dashboard),
+        (status = 500, description = "Reading the index failed")
+    )
+)]
+async fn dashboard_handler(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Dashboard>, (StatusCode, String)> {
+    if let Some(dashboard) = state.dashboard.lock().unwrap().get(Instant::now()) {
+        return Ok(Json(dashboard.clone()));
+    }
+    // Reads every document, so it runs on the blocking pool
+    let computing = state.clone();
+    let dashboard = tokio::task::spawn_blocking(move || {
+        let searcher = computing.index_reader.searcher();
+        Dashboard::compute(&searcher, &computing.job_fields, unix_now()).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())
+    .and_then(|computed| computed)
+    .map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to compute dashboard: {}", e),
+        )
+    })?;
+    state
+        .dashboard
+        .lock()
+        .unwrap()
+        .insert(dashboard.clone(), Instant::now());
+    Ok(Json(dashboard))
+}
+
+/// Counts a search for GET /suggest. The log is only written out by
+/// `save_query_log_regularly`, so searches never wait on the disk.
+fn log_query(state: &AppState, query: &str) {
+    let mut log = state.query_log.lock().unwrap();
+    log.record(query, unix_now(), state.config.query_log_capacity);
+}
+
+/// Writes the query log to `path` every `SAVE_INTERVAL` while searches are
+/// being counted
+async fn save_query_log_regularly(state: Arc<AppState>, path: PathBuf) {
+    let mut interval = tokio::time::interval(querylog::SAVE_INTERVAL);
+    loop {
+        interval.tick().await;
+        if let Err(e) = save_query_log(&state, &path).await {
+            eprintln!("⚠️  Failed to save query log: {}", e);
+        }
+    }
+}
+
+/// Writes the searches counted since the last save to `path`. Only taking
+/// the JSON holds the log's lock; the file is written on the blocking pool.
+async fn save_query_log(state: &AppState, path: &std::path::Path) -> Result<(), Error> {
+    let Some(json) = state.query_log.lock().unwrap().unsaved_json()? else {
+        return Ok(());
+    };
+    let file = path.to_path_buf();
+    tokio::task::spawn_blocking(move || std::fs::write(file, json))
+        .await
+        .map_err(io::Error::from)
+        .and_then(|written| written)
+        .map_err(|e| Error::io(path, e))
+}
+
+/// Response of the query suggestion endpoint
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+struct SuggestResponse {
+    prefix: String,
+    /// Past queries starting with `prefix`, most searched first
+    suggestions: Vec<Suggestion>,
+    /// Normalized job titles and tags in the index starting with `prefix`,
+    /// those on the most jobs first
+    completions: Vec<Completion>,
+}
+
+/// Handler for GET /suggest?prefix=<p>
+///
+/// Completes a partly typed query from the queries searched most often,
+/// and from the titles and tags of indexed jobs
+#[utoipa::path(
+    get,
+    path = "/suggest",
+    params(SuggestParams),
+    responses(
+        (status = 200, description = "Popular past queries, titles and tags with the prefix", body = SuggestResponse)
+    )
+)]
+async fn suggest_handler(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<SuggestParams>,
+) -> Json<SuggestResponse> {
+    let limit = params
+        .limit
+        .unwrap_or(DEFAULT_SUGGESTIONS)
+        .clamp(1, MAX_SUGGESTIONS);
+    let suggestions = state
+        .query_log
+        .lock()
+        .unwrap()
+        .suggest(&params.prefix, limit);
+    let fields = &state.job_fields;
+    let completions = completion::complete(
+        &state.index_reader.searcher(),
+        &[fields.title_normalized, fields.tag],
+        &params.prefix,
+        limit,
+    )
+    .unwrap_or_else(|e| {
+        eprintln!("⚠️  Failed to read completions: {}", e);
+        Vec::new()
+    });
+    Json(SuggestResponse {
+        prefix: params.prefix,
+        suggestions,
+        completions,
+    })
+}
+
+/// Handler for GET /jobs/new?since=<rfc3339>
+///
+/// Whole job records scraped after `since`, most recently scraped first, for
+/// clients polling for updates. Jobs imported without a scrape time count
+/// as scraped when they were indexed. The posted date doesn't matter: a
+/// board can list a job days after it was posted.
+/// Needs `STORE_DESCRIPTIONS`, which keeps the whole job in the index.
+#[utoipa::path(
+    get,
+    path = "/jobs/new",
+    params(NewJobsParams),
+    responses(
+        (status = 200, description = "JSON array of jobs, same shape as data/jobs.json", body = String, content_type = "application/json"),
+        (status = 400, description = "`since` is not an RFC 3339 date-time"),
+        (status = 409, description = "Descriptions aren't stored, so whole jobs aren't available")
+    )
+)]
+async fn new_jobs_handler(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<NewJobsParams>,
+) -> Result<Json<Vec<Job>>, (StatusCode, String)> {
+    let since = parse_timestamp(&params.since).ok_or_else(|| {
+        (
+            StatusCode::BAD_REQUEST,
+            format!(
+                "invalid since {:?}: expected an RFC 3339 date-time such as 2024-05-01T00:00:00Z",
+                params.since
+            ),
+        )
+    })?;
+    let Some(source) = state.job_fields.source else {
+        return Err((
+            StatusCode::CONFLICT,
+            "New jobs need STORE_DESCRIPTIONS, so whole jobs are kept in the index".to_string(),
+        ));
+    };
+    let internal_error = |message: String| (StatusCode::INTERNAL_SERVER_ERROR, message);
+
+    let filters = Filters {
+        scraped_after: Some(since),
+        ..Filters::default()
+    };
+    let query = BooleanQuery::new(filters.clauses(&state.job_fields));
+    let limit = params.limit.unwrap_or(MAX_NEW_JOBS).clamp(1, MAX_NEW_JOBS);
+    let searcher = state.index_reader.searcher();
+    let hits = searcher
+        .search(
+            &query,
+            &TopDocs::with_limit(limit).order_by_fast_field::<i64>(state.job_fields.scraped_at),
+        )
+        .map_err(|e| internal_error(format!("Search failed: {}", e)))?;
+
+    let mut jobs = Vec::with_capacity(hits.len());
+    for (_, doc_address) in hits {
+        let doc = searcher
+            .doc(doc_address)
+            .map_err(|e| internal_error(format!("Failed to read document: {}", e)))?;
+        // Documents indexed before the source field existed have no record
+        let Some(json) = doc.get_first(source).and_then(|value| value.as_text()) else {
+            continue;
+        };
+        let job = serde_json::from_str(json)
+            .map_err(|e| internal_error(format!("Stored job is not valid JSON: {}", e)))?;
+        jobs.push(job);
+    }
+    Ok(Json(jobs))
+}
+
+/// Handler for POST /jobs
+///
+/// Adds or replaces (by URL) a batch of jobs. With an `Idempotency-Key` header,
+/// a retried request returns the first response without indexing again.
+#[utoipa::path(
+    post,
+    path = "/jobs",
+    request_body(content = String, description = "JSON array of jobs, same shape as data/jobs.json", content_type = "application/json"),
+    params(("Idempotency-Key" = Option<String>, Header, description = "Makes retries of the same import safe")),
+    responses(
+        (status = 200, description = "Jobs indexed", body = ImportResponse),
+        (status = 500, description = "Indexing failed")
+    )
+)]
+async fn import_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(jobs): Json<Vec<Job>>,
+) -> Response {
+    let key = headers
+        .get("idempotency-key")
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+
+    let written = blocking_write(&state, move |state| {
+        // Held for the whole import so concurrent retries of one key can't both write
+        let mut idempotency = state.idempotency.lock().unwrap();
+        if let Some(key) = &key {
+            if let Some(previous) = idempotency.get(key, Instant::now()) {
+                let mut response = Json(previous).into_response();
+                response
+                    .headers_mut()
+                    .insert("idempotent-replayed", HeaderValue::from_static("true"));
+                return response;
+            }
+        }
+
+        let result = {
+            let mut index_writer = state.index_writer.lock().unwrap();
+            upsert_jobs(&mut index_writer, &state.job_fields, &jobs)
+        };
+        let indexed = match result.and_then(|indexed| {
+            state.index_reader.reload()?;
+            Ok(indexed)
+        }) {
+            Ok(indexed) => indexed,
+            Err(e) => {
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to index jobs: {}", e),
+                )
+                    .into_response();
+            }
+        };
+
+        let response = ImportResponse { indexed };
+        if let Some(key) = key {
+            idempotency.insert(key, response.clone(), Instant::now());
+        }
+        Json(response).into_response()
+    })
+    .await;
+    written.unwrap_or_else(IntoResponse::into_response)
+}
+
+/// Handler for POST /jobs/csv
+///
+/// Adds or replaces (by URL) the jobs in a CSV file whose header row names
+/// `Job` fields. Rows that fail their checks are reported and left out; the
+/// rest are indexed.
+#[utoipa::path(
+    post,
+    path = "/jobs/csv",
+    request_body(content = String, description = "CSV with a header row of Job fields; `title` and `url` are required", content_type = "text/csv"),
+    responses(
+        (status = 200, description = "Valid rows indexed; bad ones listed", body = CsvImportResponse),
+        (status = 400, description = "Unusable header or broken quoting"),
+        (status = 415, description = "Body is not text/csv"),
+        (status = 500, description = "Indexing failed")
+    )
+)]
+async fn import_csv_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    body: String,
+) -> Result<Json<CsvImportResponse>, (StatusCode, String)> {
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("text/csv");
+    if !content_type.trim().to_lowercase().starts_with("text/csv") {
+        return Err((
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            format!("expected text/csv, got {}", content_type),
+        ));
+    }
+
+    let imported = csv_import::jobs_from_csv(&body).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    let (imported, result) = blocking_write(&state, move |state| {
+        let result = {
+            let mut index_writer = state.index_writer.lock().unwrap();
+            upsert_jobs(&mut index_writer, &state.job_fields, &imported.jobs)
+        }
+        .and_then(|indexed| {
+            state.index_reader.reload()?;
+            Ok(indexed)
+        });
+        (imported, result)
+    })
+    .await?;
+    let indexed = result.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to index jobs: {}", e),
+        )
+    })?;
+
+    Ok(Json(CsvImportResponse {
+        parsed: imported.jobs.len(),
+        skipped: imported.skipped,
+        errored: imported.errors.len(),
+        indexed,
+        errors: imported.errors,
+    }))
+}
+
+/// Handler for PUT /jobs/{id}
+///
+/// Replaces one job, found by its id, and returns the stored record. The
+/// id is the hash of the job's URL, so the body must keep that URL.
+#[utoipa::path(
+    put,
+    path = "/jobs/{id}",
+    params(("id" = String, Path, description = "Job id (hash of its URL)")),
+    request_body(content = String, description = "The updated job, same shape as an entry in data/jobs.json", content_type = "application/json"),
+    responses(
+        (status = 200, description = "Job replaced; the updated record"),
+        (status = 400, description = "The body's URL doesn't hash to this id"),
+        (status = 404, description = "No job with this id"),
+        (status = 500, description = "Indexing failed")
+    )
+)]
+async fn update_job_handler(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(job): Json<Job>,
+) -> Response {
+    let written = blocking_write(&state, move |state| {
+        // Held from the existence check through the commit, so the job
+        // can't be replaced or removed in between
+        let mut index_writer = state.index_writer.lock().unwrap();
+
+        let id_query = TermQuery::new(
+            Term::from_field_text(state.job_fields.id, &id),
+            IndexRecordOption::Basic,
+        );
+        let exists = state
+            .index_reader
+            .searcher()
+            .search(&id_query, &Count)
+            .map(|count| count > 0);
+        match exists {
+            Ok(true) => {}
+            Ok(false) => {
+                return (StatusCode::NOT_FOUND, format!("No job with id {}", id)).into_response();
+            }
+            Err(e) => {
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to look up job: {}", e),
+                )
+                    .into_response();
+            }
+        }
+        if job.id() != id {
+            return (
+                StatusCode::BAD_REQUEST,
+                format!(
+                    "The job's url {:?} has id {}, not {}; a job's url can't change, \
+                     so add a job with a new url through POST /jobs",
+                    job.url,
+                    job.id(),
+                    id
+                ),
+            )
+                .into_response();
+        }
+
+        let result = replace_job(&mut index_writer, &state.job_fields, &id, &job)
+            .and_then(|()| state.index_reader.reload());
+        if let Err(e) = result {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to index job: {}", e),
+            )
+                .into_response();
+        }
+
+        Json(job).into_response()
+    })
+    .await;
+    written.unwrap_or_else(IntoResponse::into_response)
+}
+
+/// Writes the saved searches to their file
+fn save_searches(state: &AppState, store: &SavedSearchStore) -> Result<(), (StatusCode, String)> {
+    store
+        .save(std::path::Path::new(&state.config.saved_searches_path))
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to save searches: {}", e),
+            )
+        })
+}
+
+/// Handler for POST /saved-searches
+///
+/// Stores a named search. Its parameters are checked the same way /search checks them.
+#[utoipa::path(
+    post,
+    path = "/saved-searches",
+    request_body = CreateSavedSearch,
+    responses(
+        (status = 201, description = "Search saved", body = SavedSearch),
+        (status = 400, description = "Invalid search parameter"),
+        (status = 500, description = "Saving failed")
+    )
+)]
+async fn create_saved_search_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<CreateSavedSearch>,
+) -> Result<(StatusCode, Json<SavedSearch>), (StatusCode, String)> {
+    parse_filters(&request.params).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    apply_profile(request.params.clone()).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    if let Some(sort) = &request.params.sort {
+        sort::parse_sort(sort).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    }
+
+    let mut store = state.saved_searches.lock().unwrap();
+    let saved = store.create(request.name, request.params, unix_now());
+    save_searches(&state, &store)?;
+    Ok((StatusCode::CREATED, Json(saved)))
+}
+
+/// Handler for GET /saved-searches
+#[utoipa::path(
+    get,
+    path = "/saved-searches",
+    responses((status = 200, description = "All saved searches", body = [SavedSearch]))
+)]
+async fn list_saved_searches_handler(State(state): State<Arc<AppState>>) -> Json<Vec<SavedSearch>> {
+    Json(state.saved_searches.lock().unwrap().list().to_vec())
+}
+
+/// Handler for GET /saved-searches/{id}/results
+///
+/// Runs a saved search and records the run once it has succeeded. With
+/// `new_only=true`, only jobs scraped or added to the index after the
+/// previous run are returned (all of them on the first run), however long
+/// ago they were posted.
+#[utoipa::path(
+    get,
+    path = "/saved-searches/{id}/results",
+    params(("id" = u64, Path, description = "Saved search id"), SavedResultsParams),
+    responses(
+        (status = 200, description = "Jobs matching the saved search", body = SearchResponse),
+        (status = 404, description = "No saved search with this id"),
+        (status = 500, description = "Saving the run time failed")
+    )
+)]
+async fn saved_search_results_handler(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<u64>,
+    Query(options): Query<SavedResultsParams>,
+) -> Result<Json<SearchResponse>, (StatusCode, String)> {
+    let not_found = || {
+        (
+            StatusCode::NOT_FOUND,
+            format!("No saved search with id {}", id),
+        )
+    };
+    // Jobs scraped while the search runs are new to the next run
+    let now = unix_now();
+    let (params, last_run) = {
+        let store = state.saved_searches.lock().unwrap();
+        let saved = store.get(id).ok_or_else(not_found)?;
+        (saved.params.clone(), saved.last_run)
+    };
+
+    let mut filters = parse_filters(&params).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    if options.new_only.unwrap_or(false) {
+        filters.scraped_after = last_run;
+    }
+    let search = timed_search(&state, |state| run_filtered_search(state, params, filters)).await?;
+
+    // Only a run that returned results counts as a run
+    {
+        let mut store = state.saved_searches.lock().unwrap();
+        store.mark_run(id, now).ok_or_else(not_found)?;
+        save_searches(&state, &store)?;
+    }
+    Ok(Json(search.into_response(&state)))
+}
+
+/// Checks the `Authorization: Bearer <token>` header against `ADMIN_TOKEN`.
+/// Without a configured token the admin endpoints are disabled.
+fn require_admin(state: &AppState, headers: &HeaderMap) -> Result<(), (StatusCode, String)> {
+    let Some(expected) = &state.config.admin_token else {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Admin endpoints are disabled; set ADMIN_TOKEN to enable them".to_string(),
+        ));
+    };
+    let token = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    // Compared in constant time, so response timing doesn't reveal how much
+    // of a guessed token is right
+    let matches =
+        token.is_some_and(|token| bool::from(token.as_bytes().ct_eq(expected.as_bytes())));
+    if !matches {
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            "Missing or invalid admin token".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Handler for GET /admin/export
+///
+/// Returns every indexed job as a JSON array in the jobs.json format, for
+/// backups or moving to another server. Needs `STORE_DESCRIPTIONS`, which
+/// keeps the whole job in the index.
+async fn admin_export_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<Job>>, (StatusCode, String)> {
+    require_admin(&state, &headers)?;
+
+    let Some(source) = state.job_fields.source else {
+        return Err((
+            StatusCode::CONFLICT,
+            "Export needs STORE_DESCRIPTIONS, so whole jobs are kept in the index".to_string(),
+        ));
+    };
+    let internal_error = |message: String| (StatusCode::INTERNAL_SERVER_ERROR, message);
+
+    let searcher = state.index_reader.searcher();
+    let mut doc_addresses: Vec<DocAddress> = searcher
+        .search(&AllQuery, &DocSetCollector)
+        .map_err(|e| internal_error(format!("Failed to list documents: {}", e)))?
+        .into_iter()
+        .collect();
+    doc_addresses.sort();
+
+    let mut jobs = Vec::with_capacity(doc_addresses.len());
+    for doc_address in doc_addresses {
+        let doc = searcher
+            .doc(doc_address)
+            .map_err(|e| internal_error(format!("Failed to read document: {}", e)))?;
+        // Documents indexed before the source field existed can't be exported
+        let Some(json) = doc.get_first(source).and_then(|value| value.as_text()) else {
+            continue;
+        };
+        let job = serde_json::from_str(json)
+            .map_err(|e| internal_error(format!("Stored job is not valid JSON: {}", e)))?;
+        jobs.push(job);
+    }
+    Ok(Json(jobs))
+}
+
+/// Handler for POST /admin/reload-config
+///
+/// Re-reads the synonyms and stopwords files, so edits apply to the next
+/// searches without a restart. If a file can't be read the current ones stay.
+async fn admin_reload_config_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<ReloadConfigResponse>, (StatusCode, String)> {
+    require_admin(&state, &headers)?;
+
+    let vocabulary = load_vocabulary(&state.config).map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to reload config: {}", e),
+        )
+    })?;
+    let response = ReloadConfigResponse {
+        synonyms: vocabulary.synonym_count(),
+        stopwords: vocabulary.stopword_count(),
+    };
+    *state.vocabulary.write().unwrap() = Arc::new(vocabulary);
+    Ok(Json(response))
+}
+
+/// Handler for POST /admin/reindex
+///
+/// Rebuilds the index from the job files (`JOBS_GLOB`), streaming progress
+/// as Server-Sent Events: `progress` events reading `indexed X of Y`, then a
+/// `done` event with a summary (or an `error` event), after which the
+/// stream closes. Searches answer from the old documents until the rebuild
+/// commits. With no jobs found, the index is left as it is.
+async fn admin_reindex_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    require_admin(&state, &headers)?;
+
+    let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+    tokio::task::spawn_blocking(move || {
+        let started = Instant::now();
+        let jobs = loader::load_jobs(&state.config.jobs_glob, state.config.dedup_strategy);
+        let event = if jobs.is_empty() {
+            Event::default().event("error").data(format!(
+                "No jobs found in {}; keeping the current index",
+                state.config.jobs_glob
+            ))
+        } else {
+            let mut index_writer = state.index_writer.lock().unwrap();
+            let result = rebuild_index(
+                &mut index_writer,
+                &state.job_fields,
+                &jobs,
+                |indexed, total| {
+                    let progress = format!("indexed {} of {}", indexed, total);
+                    // The client hanging up doesn't stop the rebuild
+                    let _ = sender.send(Event::default().event("progress").data(progress));
+                },
+            );
+            // Searches right after "done" see the rebuilt index
+            let result = result.and_then(|evicted| {
+                state.index_reader.reload()?;
+                Ok(evicted)
+            });
+            match result {
+                Ok(evicted) => Event::default().event("done").data(format!(
+                    "indexed {} jobs in {} ms, {} evicted",
+                    jobs.len(),
+                    started.elapsed().as_millis(),
+                    evicted
+                )),
+                Err(e) => Event::default()
+                    .event("error")
+                    .data(format!("Reindex failed: {}", e)),
+            }
+        };
+        let _ = sender.send(event);
+    });
+
+    // Ends once the rebuild task is done and drops the sender
+    let events = stream::unfold(receiver, |mut receiver| async move {
+        let event = receiver.recv().await?;
+        Some((Ok::<_, std::convert::Infallible>(event), receiver))
+    });
+    Ok(Sse::new(events).into_response())
+}
+
+/// Handler for GET /admin/doc?url=<url>
+///
+/// Returns every stored field of the job indexed under `url`, exactly as the
+/// index holds them, for debugging field mapping.
+async fn admin_doc_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(params): Query<AdminDocParams>,
+) -> Result<Json<NamedFieldDocument>, (StatusCode, String)> {
+    require_admin(&state, &headers)?;
+
+    let url_query = TermQuery::new(
+        Term::from_field_text(state.job_fields.url, &params.url),
+        IndexRecordOption::Basic,
+    );
+    let searcher = state.index_reader.searcher();
+    let internal_error = |e: tantivy::TantivyError| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to read document: {}", e),
+        )
+    };
+    let hits = searcher
+        .search(&url_query, &TopDocs::with_limit(1))
+        .map_err(internal_error)?;
+    let Some(&(_, doc_address)) = hits.first() else {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("No job with url {}", params.url),
+        ));
+    };
+    let doc = searcher.doc(doc_address).map_err(internal_error)?;
+    Ok(Json(state.schema.to_named_doc(&doc)))
+}
+
+/// Handler for GET /openapi.json
+async fn openapi_handler() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+/// Handler for GET / (root)
+async fn root_handler() -> &'static str {
+    "🔍 Job Search Engine API\n\nEndpoints:\n  GET  /search?q=<keywords>       - Search for jobs\n  POST /search                    - Search with a nested and/or/not filter in the body\n  GET  /search/count?q=<keywords> - Count matching jobs\n  GET  /aggregations/salary?q=<keywords>&bucket=10000 - Matching jobs per salary range\n  GET  /companies/<company>/jobs?q=<keywords> - Search one company's jobs\n  GET  /health                    - Liveness and number of indexed jobs\n  GET  /ready                     - Readiness: checks the index answers searches\n  GET  /dashboard                 - Totals, top companies and tags, median salary\n  GET  /suggest?prefix=<text>     - Past queries, titles and tags starting with the text\n  POST /jobs                      - Add or replace jobs (JSON array)\n  POST /jobs/csv                  - Add or replace jobs from a CSV file\n  GET  /jobs/new?since=<rfc3339>   - Jobs added since a date-time, newest first\n  PUT  /jobs/<id>                 - Replace one job\n  POST /saved-searches            - Save a named search\n  GET  /saved-searches            - List saved searches\n  GET  /saved-searches/<id>/results - Run a saved search (new_only=true for jobs since the last run)\n  GET  /admin/doc?url=<url>       - Stored fields of one job (needs ADMIN_TOKEN)\n  GET  /admin/export              - All jobs as jobs.json (needs ADMIN_TOKEN)\n  POST /admin/reload-config       - Re-read synonyms and stopwords (needs ADMIN_TOKEN)\n  POST /admin/reindex             - Rebuild the index from JOBS_GLOB, streaming progress (needs ADMIN_TOKEN)\n  GET  /openapi.json              - OpenAPI spec\n\nExample:\n  curl 'http://127.0.0.1:3000/search?q=rust developer'"
+}
+
+/// Command-line options for the server
+#[derive(Debug, Parser)]
+#[command(about = "Indexes scraped jobs and serves the search API")]
+struct Args {
+    /// Index this job file instead of the files matching JOBS_GLOB
+    #[arg(long, value_name = "PATH")]
+    jobs: Option<PathBuf>,
+
+    /// Build the index and exit without serving, e.g. to validate a dataset in CI
+    #[arg(long)]
+    reindex_only: bool,
+
+    /// When another process is writing the index, retry for up to this many
+    /// seconds instead of failing straight away
+    #[arg(long, value_name = "SECS")]
+    wait_for_lock: Option<u64>,
+
+    /// Rebuild an index whose schema or analyzers no longer match the
+    /// settings, instead of refusing to start (same as REBUILD_INDEX=1)
+    #[arg(long)]
+    rebuild_index: bool,
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+
+    println!("🚀 Starting Job Search Engine Server...\n");
+
+    if let Err(e) = run(&args).await {
+        eprintln!("❌ {}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Loads the jobs from `jobs_path`, or else every file matching `JOBS_GLOB`,
+/// and builds (or updates) the index at `index_path`
+fn build_index(
+    jobs_path: Option<&std::path::Path>,
+    index_path: &std::path::Path,
+    config: &Config,
+    lock_wait: Option<Duration>,
+) -> Result<Index, Error> {
+    let jobs = match jobs_path {
+        Some(path) => {
+            println!("📂 Loading jobs from {:?}", path);
+            loader::load_jobs_file(path)?
+        }
+        None => {
+            // Load and merge all job files
+            println!("📂 Loading jobs from {}", config.jobs_glob);
+            loader::load_jobs(&config.jobs_glob, config.dedup_strategy)
+        }
+    };
+
+    if jobs.is_empty() {
+        println!("⚠️  No jobs found. Run the scraper first!");
+        println!("   cargo run -p scraper");
+    }
+
+    println!("📊 Loaded {} jobs\n", jobs.len());
+
+    create_index(index_path, &jobs, config, lock_wait)
+}
+
+/// Builds the API routes. Responses are gzip or brotli compressed when the
+/// client accepts it and the body is large enough to be worth it.
+fn router(state: Arc<AppState>) -> Router {
+    Router::new()
         .route("/", get(root_handler))
-        .route("/search", get(search_handler))
-        .with_state(state);
+        .route("/search", get(search_handler).post(search_post_handler))
+        .route("/search/count", get(count_handler))
+        .route("/aggregations/salary", get(salary_histogram_handler))
+        .route("/companies/:company/jobs", get(company_jobs_handler))
+        .route("/health", get(health_handler))
+        .route("/ready", get(ready_handler))
+        .route("/dashboard", get(dashboard_handler))
+        .route("/suggest", get(suggest_handler))
+        .route("/jobs", post(import_handler))
+        .route("/jobs/csv", post(import_csv_handler))
+        .route("/jobs/new", get(new_jobs_handler))
+        .route("/jobs/:id", put(update_job_handler))
+        .route(
+            "/saved-searches",
+            get(list_saved_searches_handler).post(create_saved_search_handler),
+        )
+        .route(
+            "/saved-searches/:id/results",
+            get(saved_search_results_handler),
+        )
+        .route("/admin/doc", get(admin_doc_handler))
+        .route("/admin/export", get(admin_export_handler))
+        .route("/admin/reload-config", post(admin_reload_config_handler))
+        .route("/admin/reindex", post(admin_reindex_handler))
+        .route("/openapi.json", get(openapi_handler))
+        .with_state(state)
+        .layer(
+            CompressionLayer::new()
+                // Compressing would hold back Server-Sent Events until a block fills
+                .compress_when(
+                    DefaultPredicate::new()
+                        .and(SizeAbove::new(COMPRESSION_MIN_BYTES))
+                        .and(NotForContentType::const_new("text/event-stream")),
+                ),
+        )
+}
+
+/// Loads the jobs, builds the index and serves the API until shutdown
+async fn run(args: &Args) -> Result<(), Error> {
+    let mut config = Config::from_env();
+    config.rebuild_index |= args.rebuild_index;
+
+    // Create search index
+    let index_path = std::path::Path::new(INDEX_DIR);
+    let lock = WriterLock {
+        index_path: Some(index_path.to_path_buf()),
+        wait: args.wait_for_lock.map(Duration::from_secs),
+    };
+    let index = build_index(args.jobs.as_deref(), index_path, &config, lock.wait)?;
+    if args.reindex_only {
+        println!(
+            "🏁 Index built in {}/ (--reindex-only, not serving)",
+            INDEX_DIR
+        );
+        return Ok(());
+    }
+
+    // Create shared state
+    let state = Arc::new(AppState::new(&index, &config, &lock)?);
+    if let Some(path) = &config.query_log_path {
+        tokio::spawn(save_query_log_regularly(state.clone(), PathBuf::from(path)));
+    }
+
+    let app = router(state);
 
     // Start server
     let addr = "127.0.0.1:3000";
     println!("🌐 Server running at http://{}", addr);
     println!("   Try: curl 'http://{}/search?q=developer'\n", addr);
 
-    axum::Server::bind(&addr.parse().unwrap())
+    axum::Server::from_tcp(bind(addr)?)
+        .map_err(|e| Error::Serve {
+            addr: addr.to_string(),
+            message: e.to_string(),
+        })?
         .serve(app.into_make_service())
         .await
-        .unwrap();
+        .map_err(|e| Error::Serve {
+            addr: addr.to_string(),
+            message: e.to_string(),
+        })
+}
+
+/// Opens the server's listening socket; a failure keeps the OS error (e.g.
+/// the address being in use) with the address it happened on
+fn bind(addr: &str) -> Result<std::net::TcpListener, Error> {
+    std::net::TcpListener::bind(addr).map_err(|e| Error::io(addr, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::HttpBody;
+    use index::{build_schema, index_jobs, register_tokenizers};
+    use std::collections::BTreeMap;
+
+    fn job(title: &str, company: &str, description: &str) -> Job {
+        Job {
+            title: title.to_string(),
+            company: company.to_string(),
+            location: "Remote".to_string(),
+            description: description.to_string(),
+            salary_min: None,
+            url: format!("https://example.com/{}", title.replace(' ', "-")),
+            ..Job::default()
+        }
+    }
+
+    /// Builds app state over an in-memory index containing `jobs`
+    fn test_state(jobs: &[Job]) -> Arc<AppState> {
+        test_state_with(&Config::default(), jobs)
+    }
+
+    fn test_state_with(config: &Config, jobs: &[Job]) -> Arc<AppState> {
+        let index = Index::create_in_ram(build_schema(config));
+        register_tokenizers(&index, config);
+        index_jobs(&index, jobs, config, &WriterLock::default()).unwrap();
+        Arc::new(AppState::new(&index, config, &WriterLock::default()).unwrap())
+    }
+
+    /// A server booted without any jobs.json: nothing has ever been committed
+    fn empty_state(config: &Config) -> Arc<AppState> {
+        let index = Index::create_in_ram(build_schema(config));
+        register_tokenizers(&index, config);
+        Arc::new(AppState::new(&index, config, &WriterLock::default()).unwrap())
+    }
+
+    async fn search(state: &Arc<AppState>, q: &str) -> SearchResponse {
+        let params = SearchParams {
+            q: Some(q.to_string()),
+            ..SearchParams::default()
+        };
+        search_with(state, params).await
+    }
+
+    async fn search_with(state: &Arc<AppState>, params: SearchParams) -> SearchResponse {
+        let response = search_handler(State(state.clone()), Query(params)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        serde_json::from_slice(&body_bytes(response).await).unwrap()
+    }
+
+    /// Reads a whole response body, however it was produced
+    async fn body_bytes(response: Response) -> Vec<u8> {
+        let mut body = response.into_body();
+        let mut bytes = Vec::new();
+        while let Some(chunk) = body.data().await {
+            bytes.extend_from_slice(&chunk.unwrap());
+        }
+        bytes
+    }
+
+    #[tokio::test]
+    async fn test_search_prefix_wildcard() {
+        let state = test_state(&[
+            job("Senior Developer", "Acme", "Rust services"),
+            job("Head of Development", "Globex", "Lead the team"),
+            job("Product Designer", "Initech", "Figma"),
+        ]);
+
+        let response = search(&state, "develop*").await;
+
+        let mut titles: Vec<&str> = response.results.iter().map(|r| r.title.as_str()).collect();
+        titles.sort();
+        assert_eq!(titles, vec!["Head of Development", "Senior Developer"]);
+
+        // Company names are only searched when they are weighted
+        assert_eq!(search(&state, "acm*").await.total_results, 0);
+        let params = SearchParams {
+            q: Some("acm*".to_string()),
+            boost_company: Some(2.0),
+            ..SearchParams::default()
+        };
+        let response = search_with(&state, params).await;
+        assert_eq!(response.results.len(), 1);
+        assert_eq!(response.results[0].company, "Acme");
+    }
+
+    #[tokio::test]
+    async fn test_search_short_wildcard_is_not_expanded() {
+        let state = test_state(&[job("Senior Developer", "Acme", "Rust services")]);
+
+        let response = search(&state, "de*").await;
+
+        assert_eq!(response.total_results, 0);
+    }
+
+    #[test]
+    fn test_openapi_spec_documents_search() {
+        let spec = serde_json::to_value(ApiDoc::openapi()).unwrap();
+
+        let search = &spec["paths"]["/search"]["get"];
+        assert!(search.is_object(), "spec is missing GET /search");
+
+        let params: Vec<&str> = search["parameters"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p["name"].as_str().unwrap())
+            .collect();
+        assert!(params.contains(&"q"));
+
+        assert!(spec["components"]["schemas"]["SearchResponse"].is_object());
+    }
+
+    #[tokio::test]
+    async fn test_search_cjk_substring_needs_cjk_tokenizer() {
+        let jobs = [job("高级软件工程师", "Acme", "远程工作")];
+
+        let default_state = test_state(&jobs);
+        assert_eq!(search(&default_state, "软件").await.total_results, 0);
+
+        let config = Config {
+            cjk_tokenizer: true,
+            ..Config::default()
+        };
+        let cjk_state = test_state_with(&config, &jobs);
+        let response = search(&cjk_state, "软件").await;
+        assert_eq!(response.total_results, 1);
+        assert_eq!(response.results[0].title, "高级软件工程师");
+    }
+
+    #[tokio::test]
+    async fn test_search_with_special_characters_falls_back() {
+        let jobs = [
+            job("C++ Engineer", "Acme", "Game engine work"),
+            job("Node.js Developer", "Globex", "APIs"),
+            job("Frontend Developer", "Initech", "React and TypeScript"),
+        ];
+        let state = test_state(&jobs);
+
+        assert_eq!(search(&state, "c++").await.results[0].title, "C++ Engineer");
+        assert_eq!(
+            search(&state, "node.js").await.results[0].title,
+            "Node.js Developer"
+        );
+        // "frontend:" reads as a field name the parser rejects
+        assert!(state.query_parser.parse_query("frontend: react").is_err());
+        let response = search(&state, "frontend: react").await;
+        assert_eq!(response.total_results, 1);
+        assert_eq!(response.results[0].title, "Frontend Developer");
+
+        let config = Config {
+            query_fallback: QueryFallback::Off,
+            ..Config::default()
+        };
+        let strict_state = test_state_with(&config, &jobs);
+        let params = SearchParams {
+            q: Some("frontend: react".to_string()),
+            ..SearchParams::default()
+        };
+        let response = search_handler(State(strict_state), Query(params)).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_query_syntax_and_parse_errors() {
+        let config = Config {
+            query_fallback: QueryFallback::Off,
+            ..Config::default()
+        };
+        let state = test_state_with(
+            &config,
+            &[
+                job("Rust Developer", "Acme", "Backend services in Rust"),
+                job("Go Developer", "Globex", "Backend services in Go"),
+                job("Rust Designer", "Initech", "Design tooling"),
+            ],
+        );
+        let titles = |response: SearchResponse| -> Vec<String> {
+            let mut titles: Vec<String> = response.results.into_iter().map(|r| r.title).collect();
+            titles.sort();
+            titles
+        };
+
+        assert_eq!(
+            titles(search(&state, "rust AND backend").await),
+            ["Rust Developer"]
+        );
+        assert_eq!(
+            titles(search(&state, "developer AND NOT rust").await),
+            ["Go Developer"]
+        );
+        assert_eq!(
+            titles(search(&state, "rust NOT designer").await),
+            ["Rust Developer"]
+        );
+        assert_eq!(
+            with_not_as_exclusion("\"rust dev\"~1 AND NOT go"),
+            "+\"rust dev\"~1 -go"
+        );
+        assert_eq!(with_not_as_exclusion("rust OR NOT go"), "rust OR NOT go");
+        assert_eq!(
+            titles(search(&state, "\"rust designer\" OR go").await),
+            ["Go Developer", "Rust Designer"]
+        );
+        assert_eq!(
+            titles(search(&state, "company:initech").await),
+            ["Rust Designer"]
+        );
+
+        let params = SearchParams {
+            q: Some("title:(rust".to_string()),
+            ..SearchParams::default()
+        };
+        let response = search_handler(State(state.clone()), Query(params.clone())).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let message = String::from_utf8(body_bytes(response).await).unwrap();
+        assert!(
+            message.starts_with("invalid query \"title:(rust\""),
+            "{}",
+            message
+        );
+
+        let error = count_handler(State(state.clone()), Query(params))
+            .await
+            .unwrap_err();
+        assert_eq!(error.0, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_language_names_keep_their_symbols() {
+        let state = test_state(&[
+            job("C++ Engineer", "Acme", "Game engine work"),
+            job("C# Developer", "Globex", "Backend services on .NET"),
+            job("C Programmer", "Initech", "Embedded firmware"),
+            job("Node.js Developer", "Umbrella", "APIs"),
+        ]);
+        let titles = |response: SearchResponse| -> Vec<String> {
+            response.results.into_iter().map(|r| r.title).collect()
+        };
+
+        assert_eq!(titles(search(&state, "c++").await), ["C++ Engineer"]);
+        assert_eq!(titles(search(&state, "C#").await), ["C# Developer"]);
+        assert_eq!(titles(search(&state, ".net").await), ["C# Developer"]);
+        assert_eq!(titles(search(&state, "c").await), ["C Programmer"]);
+        assert_eq!(
+            titles(search(&state, "node.js").await),
+            ["Node.js Developer"]
+        );
+        assert_eq!(titles(search(&state, "node").await), ["Node.js Developer"]);
+    }
+
+    #[tokio::test]
+    async fn test_configured_field_boosts_are_the_default_weights() {
+        let jobs = [
+            job("Rust Developer", "Acme", "Backend services"),
+            job(
+                "Backend Developer",
+                "Globex",
+                "Rust services in Rust, with more Rust",
+            ),
+        ];
+        let config = Config {
+            field_boosts: FieldBoosts {
+                title: 10.0,
+                ..FieldBoosts::default()
+            },
+            ..Config::default()
+        };
+        let state = test_state_with(&config, &jobs);
+
+        let response = search(&state, "rust").await;
+        assert_eq!(response.results[0].title, "Rust Developer");
+        // A request's own weight still wins
+        let params = SearchParams {
+            q: Some("rust".to_string()),
+            boost_title: Some(0.0),
+            ..SearchParams::default()
+        };
+        let response = search_with(&state, params).await;
+        assert_eq!(response.results[0].title, "Backend Developer");
+    }
+
+    #[tokio::test]
+    async fn test_field_scoped_search() {
+        let state = test_state(&[
+            job("Senior Rust Engineer", "Acme", "Backend services"),
+            job(
+                "Senior Go Engineer",
+                "Rust Belt Labs",
+                "Rust and Go services",
+            ),
+            job("Staff Engineer", "Globex", "Senior rust mentoring"),
+        ]);
+        let titles = |response: SearchResponse| -> Vec<String> {
+            let mut titles: Vec<String> = response.results.into_iter().map(|r| r.title).collect();
+            titles.sort();
+            titles
+        };
+
+        assert_eq!(
+            titles(search(&state, "title:\"senior rust\"").await),
+            ["Senior Rust Engineer"]
+        );
+        assert_eq!(
+            titles(search(&state, "Title: \"senior rust\"").await),
+            ["Senior Rust Engineer"]
+        );
+        assert_eq!(
+            titles(search(&state, "company:rust").await),
+            ["Senior Go Engineer"]
+        );
+        assert_eq!(
+            titles(search(&state, "description:rust AND -company:rust").await),
+            ["Staff Engineer"]
+        );
+
+        let params = SearchParams {
+            q: Some("titel:rust".to_string()),
+            ..SearchParams::default()
+        };
+        let response = search_handler(State(state.clone()), Query(params)).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let message = String::from_utf8(body_bytes(response).await).unwrap();
+        assert!(message.contains("unknown field \"titel\""), "{}", message);
+
+        // Text that only looks like a prefix is left alone
+        assert_eq!(
+            with_scoped_fields("frontend: react https://acme.dev \"a:b\"").unwrap(),
+            "frontend: react https://acme.dev \"a:b\""
+        );
+    }
+
+    #[test]
+    fn test_fallback_query_text() {
+        assert_eq!(
+            fallback_query_text("frontend: (react", QueryFallback::Escape).as_deref(),
+            Some("\"frontend:\" \"(react\"")
+        );
+        assert_eq!(
+            fallback_query_text("frontend: (react", QueryFallback::Strip).as_deref(),
+            Some("frontend react")
+        );
+        assert_eq!(fallback_query_text("\" :", QueryFallback::Strip), None);
+        assert_eq!(fallback_query_text("a:b", QueryFallback::Off), None);
+    }
+
+    #[tokio::test]
+    async fn test_phrase_slop_allows_words_in_between() {
+        let state = test_state(&[
+            job("ML Engineer", "Acme", "Work on machine vision learning"),
+            job("Data Scientist", "Globex", "Applied machine learning"),
+        ]);
+        let titles = |response: SearchResponse| -> Vec<String> {
+            response.results.into_iter().map(|r| r.title).collect()
+        };
+        let phrase = |slop| SearchParams {
+            q: Some("\"machine learning\"".to_string()),
+            slop,
+            ..SearchParams::default()
+        };
+
+        assert_eq!(
+            titles(search_with(&state, phrase(None)).await),
+            ["Data Scientist"]
+        );
+        let loose = titles(search_with(&state, phrase(Some(1))).await);
+        assert_eq!(loose.len(), 2);
+        assert_eq!(loose[0], "Data Scientist");
+        // An explicit ~N wins over the parameter
+        assert_eq!(
+            with_phrase_slop("\"machine learning\"~3 \"rust dev\"", 1),
+            "\"machine learning\"~3 \"rust dev\"~1"
+        );
+
+        let response = search_handler(State(state.clone()), Query(phrase(Some(11)))).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_count_matches_unpaged_search() {
+        let state = test_state(&[
+            job("Rust Developer", "Acme", "Backend services"),
+            job("Senior Rust Engineer", "Globex", "Systems work"),
+            job("Go Developer", "Initech", "APIs in Rust and Go"),
+            job("Designer", "Hooli", "Figma"),
+        ]);
+
+        let params = SearchParams {
+            q: Some("rust".to_string()),
+            ..SearchParams::default()
+        };
+        let response = count_handler(State(state.clone()), Query(params))
+            .await
+            .unwrap()
+            .0;
+
+        let query = build_query(&state, "rust", FieldBoosts::default(), 0, 0).unwrap();
+        let all_hits = state
+            .index_reader
+            .searcher()
+            .search(&query, &TopDocs::with_limit(1_000))
+            .unwrap();
+        assert_eq!(response.count, all_hits.len());
+        assert_eq!(response.count, 3);
+    }
+
+    #[tokio::test]
+    async fn test_search_highlights_title_match() {
+        let state = test_state(&[job(
+            "Senior Rust Developer",
+            "Rust & Co",
+            "Backend services",
+        )]);
+
+        let response = search(&state, "rust").await;
+        assert_eq!(response.results[0].title_normalized, "rust developer");
+
+        let highlights = &response.results[0].highlights;
+        assert_eq!(
+            highlights.title.as_deref(),
+            Some("Senior <em>Rust</em> Developer")
+        );
+        assert_eq!(
+            highlights.company.as_deref(),
+            Some("<em>Rust</em> &amp; Co")
+        );
+        // Descriptions aren't stored, so there's nothing to highlight
+        assert_eq!(highlights.description, None);
+    }
+
+    #[tokio::test]
+    async fn test_results_report_matched_term_count() {
+        let state = test_state(&[
+            job(
+                "Rust Developer",
+                "Acme",
+                "Backend services. We sponsor visas",
+            ),
+            job("Go Developer", "Globex", "Kubernetes"),
+        ]);
+
+        let response = search(&state, "rust backend kubernetes").await;
+        let matched = |title: &str| {
+            let result = response.results.iter().find(|r| r.title == title).unwrap();
+            result.matched_terms
+        };
+        assert_eq!(matched("Rust Developer"), Some(2));
+        assert_eq!(matched("Go Developer"), Some(1));
+
+        // Filter terms aren't counted, and browsing has no terms to count
+        let filtered = |q: &str| SearchParams {
+            q: Some(q.to_string()),
+            visa: Some(true),
+            ..SearchParams::default()
+        };
+        let result = &search_with(&state, filtered("rust")).await.results[0];
+        assert_eq!(result.matched_terms, Some(1));
+        let result = &search_with(&state, filtered("")).await.results[0];
+        assert_eq!(result.matched_terms, None);
+    }
+
+    #[tokio::test]
+    async fn test_stored_descriptions_are_returned_and_highlighted() {
+        let long_tail = "Plenty of detail. ".repeat(30);
+        let jobs = [job(
+            "Backend Engineer",
+            "Acme",
+            &format!("Build Rust services. {}", long_tail),
+        )];
+
+        // Without stored descriptions, results show the stored excerpt
+        let default_state = test_state(&jobs);
+        let result = &search(&default_state, "rust").await.results[0];
+        let description = result.description.as_deref().unwrap();
+        assert!(description.starts_with("Build Rust services."));
+        assert!(description.chars().count() <= MAX_DESCRIPTION_CHARS);
+        let highlighted = result.highlights.description.as_deref().unwrap();
+        assert!(highlighted.contains("Build <em>Rust</em> services"));
+        assert_eq!(result.snippet.as_deref(), Some(highlighted));
+
+        let config = Config {
+            store_descriptions: true,
+            ..Config::default()
+        };
+        let state = test_state_with(&config, &jobs);
+        let result = &search(&state, "rust").await.results[0];
+
+        let description = result.description.as_deref().unwrap();
+        assert!(description.starts_with("Build Rust services."));
+        assert!(description.ends_with('…'));
+        assert!(description.chars().count() <= MAX_DESCRIPTION_CHARS);
+        assert!(
+            result
+                .highlights
+                .description
+                .as_deref()
+                .unwrap()
+                .contains("Build <em>Rust</em> services")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_snippet_chars_sets_excerpt_length() {
+        let config = Config {
+            store_descriptions: true,
+            ..Config::default()
+        };
+        let description = "Über café équipe à Zürich. ".repeat(10)
+            + "Rust backend. "
+            + &"Déjà vu naïve façade. ".repeat(10);
+        let state = &test_state_with(&config, &[job("Developer", "Acme", &description)]);
+        let excerpt = |snippet_chars| async move {
+            let params = SearchParams {
+                q: Some("rust".to_string()),
+                snippet_chars,
+                ..SearchParams::default()
+            };
+            let result = search_with(state, params).await.results.remove(0);
+            let excerpt = result.snippet.unwrap();
+            assert!(excerpt.contains("<em>Rust</em>"), "{}", excerpt);
+            excerpt
+                .replace("<em>", "")
+                .replace("</em>", "")
+                .chars()
+                .count()
+        };
+
+        let short = excerpt(Some(40)).await;
+        let long = excerpt(Some(200)).await;
+        assert!(short <= 40, "{}", short);
+        assert!(long > short && long <= 200, "{} vs {}", long, short);
+        // Out-of-range values are clamped rather than rejected
+        assert!(excerpt(Some(1)).await <= 20);
+    }
+
+    #[tokio::test]
+    async fn test_snippet_falls_back_to_title() {
+        let config = Config {
+            store_descriptions: true,
+            ..Config::default()
+        };
+        let state = test_state_with(
+            &config,
+            &[
+                job("Rust Developer", "Acme", ""),
+                job("Go Developer", "Rustic Labs", "Build Rust services"),
+            ],
+        );
+        let response = search(&state, "rust").await;
+        let snippet = |title: &str| {
+            let result = response.results.iter().find(|r| r.title == title).unwrap();
+            result.snippet.clone()
+        };
+
+        // Empty description, so the highlighted title stands in
+        assert_eq!(
+            snippet("Rust Developer").as_deref(),
+            Some("<em>Rust</em> Developer")
+        );
+        assert_eq!(
+            snippet("Go Developer").as_deref(),
+            Some("Build <em>Rust</em> services")
+        );
+
+        // Nothing highlighted in the listed fields: the first one with text
+        let config = Config {
+            snippet_fields: vec![SnippetField::Description, SnippetField::Company],
+            ..Config::default()
+        };
+        let state = test_state_with(&config, &[job("Rust Developer", "Acme & Co", "")]);
+        let params = SearchParams {
+            q: Some("developer".to_string()),
+            ..SearchParams::default()
+        };
+        let result = &search_with(&state, params).await.results[0];
+        assert_eq!(result.snippet.as_deref(), Some("Acme &amp; Co"));
+    }
+
+    #[tokio::test]
+    async fn test_highlight_offsets_bracket_matched_terms() {
+        let state = test_state(&[job("Senior Rust Developer", "Café Rust", "")]);
+
+        let params = SearchParams {
+            q: Some("rust".to_string()),
+            highlights: Some("offsets".to_string()),
+            ..SearchParams::default()
+        };
+        let result = &search_with(&state, params).await.results[0];
+
+        // No markup in offsets mode
+        assert_eq!(result.highlights.title, None);
+        let offsets = result.highlight_offsets.as_ref().unwrap();
+        let matched = |text: &str, range: &MatchRange| -> String {
+            text.chars()
+                .skip(range.start)
+                .take(range.end - range.start)
+                .collect()
+        };
+        let title = offsets.title.as_ref().unwrap();
+        assert_eq!(title, &vec![MatchRange { start: 7, end: 11 }]);
+        assert_eq!(matched(&result.title, &title[0]), "Rust");
+        // Counted in characters: "é" is one character but two bytes
+        let company = offsets.company.as_ref().unwrap();
+        assert_eq!(company, &vec![MatchRange { start: 5, end: 9 }]);
+        assert_eq!(matched(&result.company, &company[0]), "Rust");
+
+        // Prefix and fuzzy matches aren't reported
+        for (q, fuzziness) in [("rus*", None), ("rsut", Some(1))] {
+            let params = SearchParams {
+                q: Some(q.to_string()),
+                fuzziness,
+                highlights: Some("offsets".to_string()),
+                ..SearchParams::default()
+            };
+            let response = search_with(&state, params).await;
+            assert_eq!(response.results.len(), 1, "{}", q);
+            let offsets = response.results[0].highlight_offsets.as_ref().unwrap();
+            assert_eq!((&offsets.title, &offsets.company), (&None, &None), "{}", q);
+        }
+
+        let params = SearchParams {
+            q: Some("rust".to_string()),
+            highlights: Some("markdown".to_string()),
+            ..SearchParams::default()
+        };
+        let response = search_handler(State(state), Query(params)).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_search_highlight_tags_are_configurable() {
+        let config = Config {
+            highlight_pre_tag: "[".to_string(),
+            highlight_post_tag: "]".to_string(),
+            ..Config::default()
+        };
+        let state = test_state_with(&config, &[job("Rust Developer", "Acme", "")]);
+
+        let response = search(&state, "developer").await;
+
+        let highlights = &response.results[0].highlights;
+        assert_eq!(highlights.title.as_deref(), Some("Rust [Developer]"));
+        assert_eq!(highlights.company, None);
+    }
+
+    #[tokio::test]
+    async fn test_empty_query_browses_all_when_enabled() {
+        let mut well_paid = job("Rust Developer", "Acme", "");
+        well_paid.salary_min = Some(150_000);
+        let mut low_paid = job("Go Developer", "Globex", "");
+        low_paid.salary_min = Some(60_000);
+        let jobs = [low_paid, job("Designer", "Initech", ""), well_paid];
+
+        assert_eq!(search(&test_state(&jobs), "").await.total_results, 0);
+
+        let config = Config {
+            allow_match_all: true,
+            ..Config::default()
+        };
+        let state = test_state_with(&config, &jobs);
+        let response = search(&state, "").await;
+
+        let titles: Vec<&str> = response.results.iter().map(|r| r.title.as_str()).collect();
+        assert_eq!(titles, vec!["Rust Developer", "Go Developer", "Designer"]);
+
+        let params = SearchParams {
+            q: Some(String::new()),
+            ..SearchParams::default()
+        };
+        assert_eq!(
+            count_handler(State(state), Query(params))
+                .await
+                .unwrap()
+                .0
+                .count,
+            3
+        );
+    }
+
+    #[tokio::test]
+    async fn test_recency_boost_lets_newer_job_overtake() {
+        let day = 86_400;
+        let mut old = job("Rust Developer", "Acme", "");
+        old.scraped_at = Some(common::format_timestamp(unix_now() - 60 * day));
+        let mut new = job("Senior Rust Developer", "Globex", "");
+        new.scraped_at = Some(common::format_timestamp(unix_now() - day));
+        let state = test_state(&[old, new]);
+
+        // The shorter title scores slightly higher on relevance alone
+        let plain = search(&state, "rust").await;
+        assert_eq!(plain.results[0].title, "Rust Developer");
+        assert!(plain.results[0].score < plain.results[1].score * 1.5);
+
+        let params = SearchParams {
+            q: Some("rust".to_string()),
+            recency_half_life_days: Some(7.0),
+            ..SearchParams::default()
+        };
+        let boosted = search_with(&state, params).await;
+        assert_eq!(boosted.results[0].title, "Senior Rust Developer");
+    }
+
+    #[tokio::test]
+    async fn test_boost_title_reorders_results() {
+        let state = test_state(&[
+            job(
+                "Rust Developer",
+                "Acme",
+                "Build backend services for our platform team",
+            ),
+            job("Platform Engineer", "Globex", "Rust, Rust and more Rust"),
+        ]);
+        let ranked = |boost_title: Option<f32>| {
+            let params = SearchParams {
+                q: Some("rust".to_string()),
+                boost_title,
+                ..SearchParams::default()
+            };
+            let state = state.clone();
+            async move {
+                let response = search_with(&state, params).await;
+                response
+                    .results
+                    .into_iter()
+                    .map(|r| r.title)
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        // Weighted alike, three mentions in the description beat one in the title...
+        assert_eq!(
+            ranked(Some(1.0)).await,
+            ["Platform Engineer", "Rust Developer"]
+        );
+        // ...until title matches weigh more
+        assert_eq!(
+            ranked(Some(5.0)).await,
+            ["Rust Developer", "Platform Engineer"]
+        );
+
+        for boost_title in [-1.0, f32::NAN, f32::INFINITY] {
+            let params = SearchParams {
+                q: Some("rust".to_string()),
+                boost_title: Some(boost_title),
+                ..SearchParams::default()
+            };
+            let response = search_handler(State(state.clone()), Query(params)).await;
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        }
+    }
+
+    #[test]
+    fn test_bind_error_keeps_the_os_error() {
+        let taken = bind("127.0.0.1:0").unwrap();
+        let addr = taken.local_addr().unwrap().to_string();
+
+        let Err(Error::Io { path, source }) = bind(&addr) else {
+            panic!("binding a taken address should fail with an I/O error");
+        };
+        assert_eq!(path, std::path::Path::new(&addr));
+        assert_eq!(source.kind(), io::ErrorKind::AddrInUse);
+    }
+
+    #[tokio::test]
+    async fn test_title_matches_outrank_description_matches_by_default() {
+        let state = test_state(&[
+            job("Platform Engineer", "Globex", "Rust"),
+            job(
+                "Senior Rust Developer for our Platform Team",
+                "Acme",
+                "Build backend services for the team",
+            ),
+        ]);
+        let ranked = |boost_title: Option<f32>| {
+            let params = SearchParams {
+                q: Some("rust".to_string()),
+                boost_title,
+                ..SearchParams::default()
+            };
+            let state = state.clone();
+            async move {
+                search_with(&state, params)
+                    .await
+                    .results
+                    .into_iter()
+                    .map(|r| r.title)
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        // Weighted alike, the short description outscores the long title
+        assert_eq!(ranked(Some(1.0)).await[0], "Platform Engineer");
+        assert_eq!(
+            ranked(None).await[0],
+            "Senior Rust Developer for our Platform Team"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_boost_company_searches_company_names() {
+        let state = test_state(&[job("Backend Developer", "Rustacean Labs", "APIs")]);
+        let params = |boost_company: Option<f32>| SearchParams {
+            q: Some("rustacean".to_string()),
+            boost_company,
+            ..SearchParams::default()
+        };
+        assert_eq!(search_with(&state, params(None)).await.total_results, 0);
+        assert_eq!(
+            search_with(&state, params(Some(1.0))).await.total_results,
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_boost_featured_ranks_featured_job_first() {
+        let mut featured = job("Rust Engineer", "Globex", "");
+        featured.featured = true;
+        let state = test_state(&[job("Rust Developer", "Acme", ""), featured]);
+
+        // Off by default: both match equally
+        let plain = search(&state, "rust").await;
+        assert_eq!(plain.results[0].score, plain.results[1].score);
+
+        let params = SearchParams {
+            q: Some("rust".to_string()),
+            boost_featured: Some(true),
+            ..SearchParams::default()
+        };
+        let boosted = search_with(&state, params).await;
+        assert_eq!(boosted.results[0].company, "Globex");
+        assert_eq!(
+            boosted.results[0].score,
+            boosted.results[1].score * FEATURED_BOOST
+        );
+    }
+
+    #[tokio::test]
+    async fn test_exclude_expired_drops_passed_deadlines() {
+        let now = common::parse_timestamp("2024-05-10T12:00:00Z").unwrap();
+        let with_deadline = |title: &str, deadline: Option<&str>| {
+            let mut job = job(title, "Acme", "");
+            job.deadline = deadline.map(str::to_string);
+            job
+        };
+        let state = test_state(&[
+            with_deadline("Rust Developer", Some("2024-05-09T23:59:59Z")),
+            with_deadline("Rust Engineer", Some("2024-05-10T23:59:59Z")),
+            with_deadline("Rust Intern", None),
+        ]);
+        let params = SearchParams {
+            q: Some("rust".to_string()),
+            ..SearchParams::default()
+        };
+        let titles = |filters: Filters| {
+            let mut titles: Vec<String> = run_filtered_search(&state, params.clone(), filters)
+                .unwrap()
+                .into_response(&state)
+                .results
+                .into_iter()
+                .map(|result| result.title)
+                .collect();
+            titles.sort();
+            titles
+        };
+
+        assert_eq!(titles(Filters::default()).len(), 3);
+        let filters = Filters {
+            expired_before: Some(now),
+            ..Filters::default()
+        };
+        // Undated deadlines are never excluded
+        assert_eq!(titles(filters), ["Rust Engineer", "Rust Intern"]);
+
+        // The parameter filters against the current time
+        let params = SearchParams {
+            q: Some("rust".to_string()),
+            exclude_expired: Some(true),
+            ..SearchParams::default()
+        };
+        assert_eq!(search_with(&state, params).await.results.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_dashboard_aggregates_seeded_jobs() {
+        let day = 86_400;
+        let seeded =
+            |title: &str, company: &str, salary: Option<i64>, days_ago: i64, tags: &[&str]| {
+                let mut job = job(title, company, "");
+                job.salary_min = salary;
+                job.scraped_at = Some(common::format_timestamp(unix_now() - days_ago * day));
+                job.tags = tags.iter().map(|tag| tag.to_string()).collect();
+                job
+            };
+        let state = test_state(&[
+            seeded(
+                "Rust Developer",
+                "Acme",
+                Some(100_000),
+                1,
+                &["rust", "backend"],
+            ),
+            seeded("Rust Engineer", "acme", Some(140_000), 2, &["Rust"]),
+            seeded(
+                "Go Developer",
+                "Globex",
+                Some(90_000),
+                10,
+                &["go", "backend"],
+            ),
+            seeded("Data Analyst", "Initech", None, 30, &["sql"]),
+            seeded("Platform Engineer", "Acme", Some(200_000), 3, &["rust"]),
+        ]);
+
+        let Json(dashboard) = dashboard_handler(State(state.clone())).await.unwrap();
+        assert_eq!(dashboard.total_jobs, 5);
+        assert_eq!(dashboard.jobs_with_salary, 4);
+        assert_eq!(dashboard.median_salary, Some(120_000));
+        assert_eq!(dashboard.scraped_last_7_days, 3);
+        let counts = |list: &[NameCount]| -> Vec<(String, usize)> {
+            list.iter().map(|c| (c.name.clone(), c.count)).collect()
+        };
+        assert_eq!(
+            counts(&dashboard.top_companies),
+            [
+                ("Acme".to_string(), 3),
+                ("Globex".to_string(), 1),
+                ("Initech".to_string(), 1)
+            ]
+        );
+        assert_eq!(
+            counts(&dashboard.top_tags),
+            [
+                ("rust".to_string(), 3),
+                ("backend".to_string(), 2),
+                ("go".to_string(), 1),
+                ("sql".to_string(), 1)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_missing_company_is_not_a_company() {
+        let mut tagged = job("Rust Developer", "", "Rust services");
+        tagged.company_missing = true;
+        // Written by older scrapers
+        let placeholder = job("Rust Engineer", common::UNKNOWN_COMPANY, "Rust services");
+        let state = test_state(&[tagged, placeholder, job("Go Developer", "Acme", "")]);
+
+        let Json(dashboard) = dashboard_handler(State(state.clone())).await.unwrap();
+        assert_eq!(dashboard.total_jobs, 3);
+        assert_eq!(
+            dashboard
+                .top_companies
+                .iter()
+                .map(|c| c.name.as_str())
+                .collect::<Vec<_>>(),
+            ["Acme"]
+        );
+        let Json(response) = company_jobs_handler(
+            State(state.clone()),
+            Path("Unknown Company".to_string()),
+            Query(SearchParams::default()),
+        )
+        .await
+        .unwrap();
+        assert!(response.results.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_suggest_ranks_popular_queries_by_frequency() {
+        let state = test_state(&[job("Rust Developer", "Acme", "Rust services")]);
+        for q in [
+            "rust developer",
+            "Rust  Developer",
+            "rust developer",
+            "rust engineer",
+            "go",
+        ] {
+            search(&state, q).await;
+        }
+        // A later page of a search already counted isn't counted again
+        search_with(
+            &state,
+            SearchParams {
+                q: Some("rust engineer".to_string()),
+                offset: Some(10),
+                ..SearchParams::default()
+            },
+        )
+        .await;
+
+        let Json(response) = suggest_handler(
+            State(state.clone()),
+            Query(SuggestParams {
+                prefix: "Rust".to_string(),
+                limit: None,
+            }),
+        )
+        .await;
+        assert_eq!(
+            response.suggestions,
+            vec![
+                Suggestion {
+                    query: "rust developer".to_string(),
+                    count: 3,
+                },
+                Suggestion {
+                    query: "rust engineer".to_string(),
+                    count: 1,
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_query_log_is_written_only_when_configured() {
+        let path = std::env::temp_dir().join(format!("query-log-save-{}.json", std::process::id()));
+        let config = Config {
+            query_log_path: Some(path.to_str().unwrap().to_string()),
+            ..Config::default()
+        };
+        let state = test_state_with(&config, &[job("Rust Developer", "Acme", "")]);
+        search(&state, "rust").await;
+        // Searching itself doesn't touch the disk
+        assert!(!path.exists());
+
+        save_query_log(&state, &path).await.unwrap();
+        let saved = QueryLog::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(saved.suggest("ru", 10)[0].query, "rust");
+        // Nothing new to write
+        save_query_log(&state, &path).await.unwrap();
+        assert!(!path.exists());
+
+        assert_eq!(Config::default().query_log_path, None);
+    }
+
+    #[tokio::test]
+    async fn test_suggest_completes_titles_and_tags_from_the_index() {
+        let tagged = |title: &str, tags: &[&str]| Job {
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            ..job(title, "Acme", "")
+        };
+        let state = test_state(&[
+            tagged("Senior Rust Developer", &["rust"]),
+            tagged("Rust Developer (Remote)", &["rust", "backend"]),
+            tagged("Rust Engineer", &["rust"]),
+            tagged("Ruby Developer", &["ruby"]),
+        ]);
+        let complete = |prefix: &str, limit: Option<usize>| {
+            suggest_handler(
+                State(state.clone()),
+                Query(SuggestParams {
+                    prefix: prefix.to_string(),
+                    limit,
+                }),
+            )
+        };
+
+        // Normalized titles and tags, each once, on the most jobs first
+        let Json(response) = complete("Ru", None).await;
+        let completions: Vec<(&str, u64)> = response
+            .completions
+            .iter()
+            .map(|c| (c.text.as_str(), c.jobs))
+            .collect();
+        assert_eq!(
+            completions,
+            [
+                ("rust", 3),
+                ("rust developer", 3),
+                ("ruby", 1),
+                ("ruby developer", 1),
+            ]
+        );
+        let Json(response) = complete("rust ", Some(1)).await;
+        assert_eq!(response.completions[0].text, "rust");
+        let Json(response) = complete("  ", None).await;
+        assert!(response.completions.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_salary_range_covers_every_match() {
+        let paid = |title: &str, salary: Option<i64>| {
+            let mut job = job(title, "Acme", "");
+            job.salary_min = salary;
+            job
+        };
+        let state = test_state(&[
+            paid("Rust Developer", Some(80_000)),
+            paid("Rust Engineer", Some(150_000)),
+            paid("Rust Lead", Some(120_000)),
+            paid("Rust Intern", None),
+            paid("Go Developer", Some(300_000)),
+        ]);
+
+        // The bounds span all rust matches although the page holds one,
+        // and the job without a salary doesn't pull the minimum to 0
+        let params = SearchParams {
+            q: Some("rust".to_string()),
+            limit: Some(1),
+            ..SearchParams::default()
+        };
+        let response = search_with(&state, params).await;
+        assert_eq!(response.results.len(), 1);
+        assert_eq!(
+            response.salary_range,
+            Some(SalaryBounds {
+                min: 80_000,
+                max: 150_000
+            })
+        );
+
+        assert_eq!(search(&state, "intern").await.salary_range, None);
+    }
+
+    #[tokio::test]
+    async fn test_profiles_preset_ordering() {
+        let day = 86_400;
+        let seeded = |title: &str, salary: i64, days_ago: i64| {
+            let mut job = job(title, "Acme", "Rust services");
+            job.salary_min = Some(salary);
+            job.posted_date = Some(common::format_timestamp(unix_now() - days_ago * day));
+            job
+        };
+        let state = test_state(&[
+            seeded("Rust Developer", 50_000, 5),
+            seeded("Backend Engineer", 150_000, 30),
+            seeded("Platform Engineer", 90_000, 1),
+        ]);
+        let titles = |profile: &str, sort: Option<&str>| {
+            let params = SearchParams {
+                q: Some("rust".to_string()),
+                profile: Some(profile.to_string()),
+                sort: sort.map(str::to_string),
+                ..SearchParams::default()
+            };
+            let state = state.clone();
+            async move {
+                search_with(&state, params)
+                    .await
+                    .results
+                    .into_iter()
+                    .map(|result| result.title)
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        assert_eq!(
+            titles("relevance", None).await,
+            ["Rust Developer", "Platform Engineer", "Backend Engineer"]
+        );
+        assert_eq!(
+            titles("recent", None).await,
+            ["Platform Engineer", "Rust Developer", "Backend Engineer"]
+        );
+        assert_eq!(
+            titles("comp", None).await,
+            ["Backend Engineer", "Platform Engineer", "Rust Developer"]
+        );
+        // An explicit parameter wins over the profile's
+        assert_eq!(
+            titles("recent", Some("salary_asc")).await,
+            ["Rust Developer", "Platform Engineer", "Backend Engineer"]
+        );
+        // The title match ranks first by score, not the best paid job
+        assert_eq!(titles("comp", Some("relevance")).await[0], "Rust Developer");
+
+        let params = SearchParams {
+            q: Some("rust".to_string()),
+            profile: Some("fastest".to_string()),
+            ..SearchParams::default()
+        };
+        let response = search_handler(State(state), Query(params)).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_compound_sort_breaks_salary_ties_by_date() {
+        let day = 86_400;
+        let dated = |title: &str, salary: i64, days_ago: i64| {
+            let mut job = job(title, "Acme", "");
+            job.salary_min = Some(salary);
+            job.posted_date = Some(common::format_timestamp(unix_now() - days_ago * day));
+            job
+        };
+        let state = test_state(&[
+            dated("Rust Developer", 100_000, 10),
+            dated("Rust Engineer", 100_000, 2),
+            dated("Rust Intern", 40_000, 1),
+        ]);
+
+        let params = SearchParams {
+            q: Some("rust".to_string()),
+            sort: Some("salary_desc,date_desc".to_string()),
+            ..SearchParams::default()
+        };
+        let titles: Vec<String> = search_with(&state, params)
+            .await
+            .results
+            .into_iter()
+            .map(|result| result.title)
+            .collect();
+        assert_eq!(titles, ["Rust Engineer", "Rust Developer", "Rust Intern"]);
+
+        let params = SearchParams {
+            q: Some("rust".to_string()),
+            sort: Some("title_desc".to_string()),
+            ..SearchParams::default()
+        };
+        let response = search_handler(State(state), Query(params)).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_sort_puts_jobs_without_a_value_last_both_ways() {
+        let listed = |title: &str, salary: Option<i64>, days_ago: Option<i64>| {
+            let mut job = job(title, "Acme", "");
+            job.salary_min = salary;
+            job.posted_date =
+                days_ago.map(|days| common::format_timestamp(unix_now() - days * 86_400));
+            job
+        };
+        // The intern lists neither a salary nor a date
+        let state = test_state(&[
+            listed("Rust Developer", Some(90_000), Some(5)),
+            listed("Rust Intern", None, None),
+            listed("Rust Engineer", Some(120_000), Some(1)),
+        ]);
+        let titles = |sort: &str| {
+            let params = SearchParams {
+                q: Some("rust".to_string()),
+                sort: Some(sort.to_string()),
+                ..SearchParams::default()
+            };
+            let state = state.clone();
+            async move {
+                search_with(&state, params)
+                    .await
+                    .results
+                    .into_iter()
+                    .map(|result| result.title)
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        assert_eq!(
+            titles("salary_desc").await,
+            ["Rust Engineer", "Rust Developer", "Rust Intern"]
+        );
+        assert_eq!(
+            titles("salary_asc").await,
+            ["Rust Developer", "Rust Engineer", "Rust Intern"]
+        );
+        assert_eq!(
+            titles("date_desc").await,
+            ["Rust Engineer", "Rust Developer", "Rust Intern"]
+        );
+        assert_eq!(
+            titles("date_asc").await,
+            ["Rust Developer", "Rust Engineer", "Rust Intern"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bm25_params_change_relative_scores() {
+        let jobs = [
+            job("Rust Developer", "Acme", "Backend"),
+            job(
+                "Rust Developer for our payments platform team",
+                "Globex",
+                "Backend",
+            ),
+        ];
+        let scores = |response: SearchResponse| -> Vec<f32> {
+            response.results.iter().map(|result| result.score).collect()
+        };
+
+        // Default parameters: the shorter title wins on length normalization
+        let state = test_state(&jobs);
+        let default_scores = scores(search(&state, "rust developer").await);
+        assert!(default_scores[0] > default_scores[1] * 1.1);
+
+        // Recomputing with the default parameters reproduces Tantivy's scores
+        let query = build_query(&state, "rust developer", FieldBoosts::default(), 0, 0).unwrap();
+        let searcher = state.index_reader.searcher();
+        let bm25 = Bm25::new(&searcher, &*query, Bm25Params::default()).unwrap();
+        let rescored = searcher
+            .search(&query, &ranking::adjusted(10, Some(bm25), None, None))
+            .unwrap();
+        for ((score, _), expected) in rescored.iter().zip(&default_scores) {
+            assert!((score - expected).abs() < 1e-4, "{} vs {}", score, expected);
+        }
+
+        // Without length normalization both titles score the same
+        let config = Config {
+            bm25_b: 0.0,
+            ..Config::default()
+        };
+        let state = test_state_with(&config, &jobs);
+        let flat_scores = scores(search(&state, "rust developer").await);
+        assert!((flat_scores[0] - flat_scores[1]).abs() < 1e-4);
+
+        // Field boosts still weigh in when rescoring
+        let config = Config {
+            bm25_b: 0.0,
+            field_boosts: FieldBoosts {
+                title: 1.0,
+                description: 5.0,
+                company: 0.0,
+            },
+            ..Config::default()
+        };
+        let jobs = [
+            job("Rust Developer", "Acme", "Backend"),
+            job("Backend Developer", "Globex", "Rust"),
+        ];
+        let state = test_state_with(&config, &jobs);
+        let response = search(&state, "rust").await;
+        assert_eq!(response.results[0].title, "Backend Developer");
+        let boosted_scores = scores(response);
+        assert!(boosted_scores[0] > boosted_scores[1] * 2.0);
+    }
+
+    #[tokio::test]
+    async fn test_saved_search_returns_jobs_new_since_last_run() {
+        let day = 86_400;
+        let path = std::env::temp_dir().join(format!("saved-handlers-{}.json", std::process::id()));
+        let config = Config {
+            saved_searches_path: path.to_str().unwrap().to_string(),
+            ..Config::default()
+        };
+        let mut old = job("Rust Developer", "Acme", "");
+        old.scraped_at = Some(common::format_timestamp(unix_now() - 10 * day));
+        let state = test_state_with(&config, &[old, job("Go Developer", "Globex", "")]);
+
+        let request = CreateSavedSearch {
+            name: "Rust jobs".to_string(),
+            params: SearchParams {
+                q: Some("rust".to_string()),
+                ..SearchParams::default()
+            },
+        };
+        let (status, Json(saved)) =
+            create_saved_search_handler(State(state.clone()), Json(request))
+                .await
+                .unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+
+        let Json(listed) = list_saved_searches_handler(State(state.clone())).await;
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].name, "Rust jobs");
+
+        let run = |new_only: bool| {
+            saved_search_results_handler(
+                State(state.clone()),
+                Path(saved.id),
+                Query(SavedResultsParams {
+                    new_only: Some(new_only),
+                }),
+            )
+        };
+        let titles = |response: SearchResponse| -> Vec<String> {
+            response.results.into_iter().map(|r| r.title).collect()
+        };
+
+        // The first run has nothing to compare against, so everything is new
+        let Json(first) = run(true).await.unwrap();
+        assert_eq!(titles(first), ["Rust Developer"]);
+
+        // Pretend that run happened five days ago, then a new job arrives.
+        // It was posted long ago, but is new to this search all the same.
+        state
+            .saved_searches
+            .lock()
+            .unwrap()
+            .mark_run(saved.id, unix_now() - 5 * day);
+        let mut new = job("Senior Rust Developer", "Initech", "");
+        new.posted_date = Some(common::format_timestamp(unix_now() - 30 * day));
+        import(&state, None, vec![new]).await;
+
+        let Json(second) = run(true).await.unwrap();
+        assert_eq!(titles(second), ["Senior Rust Developer"]);
+        let Json(everything) = run(false).await.unwrap();
+        assert_eq!(everything.total_results, 2);
+
+        // Runs are persisted
+        let stored = SavedSearchStore::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert!(stored.get(saved.id).unwrap().last_run.is_some());
+
+        let missing = saved_search_results_handler(
+            State(state.clone()),
+            Path(99),
+            Query(SavedResultsParams::default()),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(missing.0, StatusCode::NOT_FOUND);
+
+        // A run that fails isn't recorded
+        let broken = state.saved_searches.lock().unwrap().create(
+            "Broken".to_string(),
+            SearchParams {
+                posted_after: Some("someday".to_string()),
+                ..SearchParams::default()
+            },
+            unix_now(),
+        );
+        let failed = saved_search_results_handler(
+            State(state.clone()),
+            Path(broken.id),
+            Query(SavedResultsParams::default()),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(failed.0, StatusCode::BAD_REQUEST);
+        let saved_searches = state.saved_searches.lock().unwrap();
+        assert_eq!(saved_searches.get(broken.id).unwrap().last_run, None);
+    }
+
+    #[tokio::test]
+    async fn test_salary_range_matches_overlapping_job_ranges() {
+        let salaried = |title: &str, min: i64, max: Option<i64>| {
+            let mut job = job(title, "Acme", "");
+            job.salary_min = Some(min);
+            job.salary_max = max;
+            job
+        };
+        let state = test_state(&[
+            salaried("Rust Developer", 99_000, Some(110_000)),
+            salaried("Go Developer", 95_000, None),
+            salaried("Java Developer", 130_000, Some(160_000)),
+            job("Python Developer", "Acme", ""),
+        ]);
+        let matching = |salary_min: Option<i64>, salary_max: Option<i64>| {
+            let params = SearchParams {
+                q: Some("developer".to_string()),
+                salary_min,
+                salary_max,
+                ..SearchParams::default()
+            };
+            let state = state.clone();
+            async move {
+                let mut titles: Vec<String> = search_with(&state, params)
+                    .await
+                    .results
+                    .into_iter()
+                    .map(|r| r.title)
+                    .collect();
+                titles.sort();
+                titles
+            }
+        };
+
+        // 99k-110k reaches 100k through its maximum; 95k with no maximum doesn't
+        assert_eq!(
+            matching(Some(100_000), None).await,
+            ["Java Developer", "Rust Developer"]
+        );
+        assert_eq!(
+            matching(Some(100_000), Some(120_000)).await,
+            ["Rust Developer"]
+        );
+        assert_eq!(matching(None, Some(96_000)).await, ["Go Developer"]);
+
+        // `min_salary`/`max_salary` are accepted as the same filters
+        let uri: axum::http::Uri = "/search?q=developer&min_salary=100000&max_salary=120000"
+            .parse()
+            .unwrap();
+        let Query(params) = Query::<SearchParams>::try_from_uri(&uri).unwrap();
+        let response = search_with(&state, params).await;
+        let titles: Vec<&str> = response.results.iter().map(|r| r.title.as_str()).collect();
+        assert_eq!(titles, ["Rust Developer"]);
+
+        let params = SearchParams {
+            q: Some("developer".to_string()),
+            salary_min: Some(120_000),
+            salary_max: Some(100_000),
+            ..SearchParams::default()
+        };
+        let response = search_handler(State(state.clone()), Query(params)).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_max_per_company_caps_a_flood_of_one_company() {
+        let mut jobs: Vec<Job> = (1..=12)
+            .map(|i| job(&format!("Rust Developer {}", i), "Acme", ""))
+            .collect();
+        // Longer titles, so they rank below every Acme job
+        jobs.push(job("Rust Developer for the payments team", "Globex", ""));
+        jobs.push(job("Rust Developer for the platform team", "Initech", ""));
+        let state = test_state(&jobs);
+
+        let companies = |response: SearchResponse| -> Vec<String> {
+            response.results.into_iter().map(|r| r.company).collect()
+        };
+        let flooded = companies(search(&state, "rust").await);
+        assert_eq!(flooded.len(), 10);
+        assert!(flooded.iter().all(|company| company == "Acme"));
+
+        let params = SearchParams {
+            q: Some("rust".to_string()),
+            max_per_company: Some(2),
+            ..SearchParams::default()
+        };
+        let capped = companies(search_with(&state, params).await);
+        assert_eq!(capped, ["Acme", "Acme", "Globex", "Initech"]);
+    }
+
+    #[tokio::test]
+    async fn test_pagination_metadata() {
+        let jobs: Vec<Job> = (1..=25)
+            .map(|i| job(&format!("Rust Developer {}", i), "Acme", ""))
+            .collect();
+        let state = test_state(&jobs);
+        let page = |q: &str, limit: Option<usize>, offset: Option<usize>| {
+            let params = SearchParams {
+                q: Some(q.to_string()),
+                limit,
+                offset,
+                ..SearchParams::default()
+            };
+            search_with(&state, params)
+        };
+
+        // A page in the middle of the results
+        let middle = page("rust", Some(10), Some(10)).await;
+        assert_eq!(middle.results.len(), 10);
+        assert_eq!(
+            middle.pagination,
+            Some(Pagination {
+                page: 2,
+                per_page: 10,
+                total_pages: 3,
+                total_hits: 25,
+                has_next: true,
+            })
+        );
+        let first = page("rust", Some(10), None).await;
+        let middle_titles: Vec<&String> = middle.results.iter().map(|r| &r.title).collect();
+        assert!(
+            first
+                .results
+                .iter()
+                .all(|r| !middle_titles.contains(&&r.title))
+        );
+
+        // The last page is partial, and nothing follows it
+        let last = page("rust", Some(10), Some(20)).await;
+        assert_eq!(last.results.len(), 5);
+        let pagination = last.pagination.unwrap();
+        assert_eq!((pagination.page, pagination.has_next), (3, false));
+
+        let none = page("elixir", Some(10), None).await.pagination.unwrap();
+        assert_eq!((none.total_pages, none.has_next), (0, false));
+
+        // Pages past MAX_OFFSET are rejected, so they aren't counted
+        let deep = Pagination::new(990, 10, 5_000);
+        assert_eq!((deep.total_pages, deep.has_next), (101, true));
+        let deepest = Pagination::new(1_000, 10, 5_000);
+        assert_eq!((deepest.page, deepest.has_next), (101, false));
+        assert_eq!(deepest.total_hits, 5_000);
+        assert_eq!(Pagination::new(0, 30, 5_000).total_pages, 34);
+
+        // Only when asked for; out-of-range values are rejected
+        assert_eq!(search(&state, "rust").await.pagination, None);
+        for (limit, offset) in [(Some(0), None), (Some(101), None), (None, Some(1_001))] {
+            let params = SearchParams {
+                q: Some("rust".to_string()),
+                limit,
+                offset,
+                ..SearchParams::default()
+            };
+            let response = search_handler(State(state.clone()), Query(params)).await;
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_results_carry_location_url_and_salary() {
+        let mut paid = job("Rust Developer", "Acme", "");
+        paid.location = "Europe Only".to_string();
+        paid.salary_min = Some(90_000);
+        let mut unpaid = job("Rust Engineer", "Globex", "");
+        unpaid.location = String::new();
+        let state = test_state(&[paid, unpaid]);
+
+        let response = search(&state, "rust").await;
+        let result = |title: &str| {
+            let result = response.results.iter().find(|r| r.title == title).unwrap();
+            (
+                result.location.as_str(),
+                result.url.as_str(),
+                result.salary_min,
+            )
+        };
+        assert_eq!(
+            result("Rust Developer"),
+            (
+                "Europe Only",
+                "https://example.com/Rust-Developer",
+                Some(90_000)
+            )
+        );
+        assert_eq!(
+            result("Rust Engineer"),
+            ("", "https://example.com/Rust-Engineer", None)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fuzziness_tolerates_typos_ranking_exact_matches_first() {
+        let state = test_state(&[
+            job("Senior Develper", "Acme", ""),
+            job("Senior Developer", "Globex", ""),
+            job("Go Engineer", "Initech", ""),
+        ]);
+        let titles = |q: &str, fuzziness: Option<u8>| {
+            let params = SearchParams {
+                q: Some(q.to_string()),
+                fuzziness,
+                ..SearchParams::default()
+            };
+            let state = state.clone();
+            async move {
+                let response = search_with(&state, params).await;
+                response
+                    .results
+                    .into_iter()
+                    .map(|r| r.title)
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        assert_eq!(titles("developer", None).await, ["Senior Developer"]);
+        assert_eq!(
+            titles("developer", Some(1)).await,
+            ["Senior Developer", "Senior Develper"]
+        );
+        // A swapped pair of letters counts as one edit
+        let mut swapped = titles("develpoer", Some(1)).await;
+        swapped.sort();
+        assert_eq!(swapped, ["Senior Developer", "Senior Develper"]);
+        // Short words allow fewer edits: "ho" isn't fuzzed into "go"
+        assert!(titles("ho", Some(2)).await.is_empty());
+
+        let params = SearchParams {
+            q: Some("developer".to_string()),
+            fuzziness: Some(3),
+            ..SearchParams::default()
+        };
+        let response = search_handler(State(state.clone()), Query(params)).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_zero_hit_queries_suggest_spelling_corrections() {
+        let state = test_state(&[
+            job("Rust Developer", "Acme", "Backend services"),
+            job("Senior Rust Developer", "Globex", "Distributed systems"),
+            job("Python Developer", "Initech", "Data pipelines"),
+        ]);
+
+        let response = search(&state, "rsut develper").await;
+        assert_eq!(response.total_hits, 0);
+        assert_eq!(response.suggestions, ["rust developer"]);
+
+        // Descriptions are searched too; words without a close match stay
+        let response = search(&state, "pythn pipelnes kubernetes").await;
+        assert_eq!(response.suggestions, ["python pipelines kubernetes"]);
+
+        // Only when nothing matched, and only with a close enough word
+        assert!(search(&state, "rust").await.suggestions.is_empty());
+        assert!(search(&state, "kubernetes").await.suggestions.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_spelling_corrections_compare_words_after_field_analysis() {
+        let config = Config {
+            field_tokenizers: [("title".to_string(), "en_stem".to_string())].into(),
+            ..Config::default()
+        };
+        let state = test_state_with(
+            &config,
+            &[job("Rust Developer", "Acme", "Backend services")],
+        );
+
+        // "developer" is indexed in the title as "develop"; it is left alone
+        // rather than "corrected" to the stem, while the typo still is
+        let params = SearchParams {
+            q: Some("developer rsut".to_string()),
+            company: Some("Globex".to_string()),
+            ..SearchParams::default()
+        };
+        let response = search_with(&state, params).await;
+        assert_eq!(response.total_hits, 0);
+        assert_eq!(response.suggestions, ["developer rust"]);
+    }
+
+    #[tokio::test]
+    async fn test_facets_count_every_match_by_company_and_location() {
+        let posted = |title: &str, company: &str, location: &str| Job {
+            location: location.to_string(),
+            ..job(title, company, "")
+        };
+        let mut jobs: Vec<Job> = (1..=12)
+            .map(|i| posted(&format!("Rust Developer {}", i), "Stripe", "Europe"))
+            .collect();
+        jobs.push(posted("Go Developer", "Acme", "USA Only"));
+        jobs.push(posted("Java Developer", " ACME", "USA Only"));
+        jobs.push(posted("Python Developer", "", ""));
+        jobs.push(posted("Designer", "Acme", "Europe"));
+        let state = test_state(&jobs);
+
+        let params = SearchParams {
+            q: Some("developer".to_string()),
+            facets: Some(true),
+            ..SearchParams::default()
+        };
+        let facets = search_with(&state, params).await.facets.unwrap();
+        let counts = |pairs: &[(&str, u64)]| -> BTreeMap<String, u64> {
+            pairs
+                .iter()
+                .map(|(name, count)| (name.to_string(), *count))
+                .collect()
+        };
+        // All 15 matches, not the page of 10; blank values aren't counted,
+        // and companies are counted by the key `company=` matches
+        assert_eq!(facets.company, counts(&[("stripe", 12), ("acme", 2)]));
+        assert_eq!(facets.location, counts(&[("Europe", 12), ("USA Only", 2)]));
+
+        assert!(search(&state, "developer").await.facets.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_posted_date_filters_keep_fresh_jobs() {
+        let posted = |title: &str, days_ago: i64| Job {
+            posted_date: Some(common::format_timestamp(unix_now() - days_ago * 86_400)),
+            ..job(title, "Acme", "")
+        };
+        let state = test_state(&[
+            posted("Rust Developer", 1),
+            posted("Go Developer", 10),
+            posted("Java Developer", 400),
+            job("Python Developer", "Acme", ""),
+        ]);
+        let titles = |posted_after: Option<&str>, posted_within: Option<&str>| {
+            let params = SearchParams {
+                q: Some("developer".to_string()),
+                posted_after: posted_after.map(str::to_string),
+                posted_within: posted_within.map(str::to_string),
+                ..SearchParams::default()
+            };
+            let state = state.clone();
+            async move {
+                let mut titles: Vec<String> = search_with(&state, params)
+                    .await
+                    .results
+                    .into_iter()
+                    .map(|r| r.title)
+                    .collect();
+                titles.sort();
+                titles
+            }
+        };
+
+        assert_eq!(titles(None, Some("7d")).await, ["Rust Developer"]);
+        assert_eq!(
+            titles(None, Some("2w")).await,
+            ["Go Developer", "Rust Developer"]
+        );
+        let a_month_ago = common::format_timestamp(unix_now() - 30 * 86_400);
+        assert_eq!(
+            titles(Some(&a_month_ago[..10]), None).await,
+            ["Go Developer", "Rust Developer"]
+        );
+        // Both apply: the later cutoff wins
+        assert_eq!(
+            titles(Some(&a_month_ago), Some("48h")).await,
+            ["Rust Developer"]
+        );
+
+        for (posted_after, posted_within) in [
+            (Some("last week"), None),
+            (None, Some("7")),
+            (None, Some("-1d")),
+        ] {
+            let params = SearchParams {
+                q: Some("developer".to_string()),
+                posted_after: posted_after.map(str::to_string),
+                posted_within: posted_within.map(str::to_string),
+                ..SearchParams::default()
+            };
+            let response = search_handler(State(state.clone()), Query(params)).await;
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_total_hits_counts_beyond_the_page() {
+        let jobs: Vec<Job> = (1..=15)
+            .map(|i| job(&format!("Rust Developer {}", i), "Acme", ""))
+            .collect();
+        let state = test_state(&jobs);
+
+        let response = search(&state, "rust").await;
+        assert_eq!((response.total_results, response.total_hits), (10, 15));
+        let response = search(&state, "elixir").await;
+        assert_eq!((response.total_results, response.total_hits), (0, 0));
+    }
+
+    #[tokio::test]
+    async fn test_page_number_is_an_alternative_to_offset() {
+        let jobs: Vec<Job> = (1..=5)
+            .map(|i| job(&format!("Rust Developer {}", i), "Acme", ""))
+            .collect();
+        let state = test_state(&jobs);
+        let params = |page: Option<usize>, offset: Option<usize>| SearchParams {
+            q: Some("rust".to_string()),
+            limit: Some(2),
+            page,
+            offset,
+            ..SearchParams::default()
+        };
+        let titles = |response: &SearchResponse| -> Vec<String> {
+            response.results.iter().map(|r| r.title.clone()).collect()
+        };
+
+        let by_page = search_with(&state, params(Some(2), None)).await;
+        let by_offset = search_with(&state, params(None, Some(2))).await;
+        assert_eq!(titles(&by_page), titles(&by_offset));
+        assert_eq!(by_page.pagination, by_offset.pagination);
+        let pagination = by_page.pagination.unwrap();
+        assert_eq!((pagination.page, pagination.total_hits), (2, 5));
+
+        // page=0, both page and offset, or a page past the offset cap
+        for (page, offset) in [(Some(0), None), (Some(2), Some(2)), (Some(502), None)] {
+            let response = search_handler(State(state.clone()), Query(params(page, offset))).await;
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_results_carry_freshness() {
+        let mut yesterday = job("Rust Developer", "Acme", "");
+        yesterday.posted_date = Some(common::format_timestamp(unix_now() - 86_400));
+        let undated = job("Rust Engineer", "Globex", "");
+        let state = test_state(&[yesterday, undated]);
+
+        let response = search(&state, "rust").await;
+        let freshness = |title: &str| {
+            response
+                .results
+                .iter()
+                .find(|result| result.title == title)
+                .unwrap()
+                .freshness
+        };
+        assert_eq!(freshness("Rust Developer"), Freshness::New);
+        assert_eq!(freshness("Rust Engineer"), Freshness::Unknown);
+    }
+
+    #[tokio::test]
+    async fn test_timezone_filter_matches_overlapping_bands() {
+        let mut europe = job("Rust Developer", "Acme", "");
+        europe.location = "Europe Only".to_string();
+        let mut us = job("Go Developer", "Globex", "");
+        us.location = "US timezones".to_string();
+        let mut anywhere = job("Python Developer", "Initech", "");
+        anywhere.location = "Anywhere in the World".to_string();
+        let mut unknown = job("Java Developer", "Hooli", "");
+        unknown.location = "Remote".to_string();
+        let state = test_state(&[europe, us, anywhere, unknown]);
+
+        let titles_for = |timezone: &str| {
+            let params = SearchParams {
+                q: Some("developer".to_string()),
+                timezone: Some(timezone.to_string()),
+                ..SearchParams::default()
+            };
+            let state = state.clone();
+            async move {
+                let response = search_with(&state, params).await;
+                let mut titles: Vec<String> =
+                    response.results.into_iter().map(|r| r.title).collect();
+                titles.sort();
+                titles
+            }
+        };
+
+        // "+1" arrives URL-decoded as " 1"
+        assert_eq!(
+            titles_for(" 1").await,
+            vec!["Python Developer", "Rust Developer"]
+        );
+        assert_eq!(
+            titles_for("-5").await,
+            vec!["Go Developer", "Python Developer"]
+        );
+        // Only the worldwide job covers UTC+9
+        assert_eq!(titles_for("+9").await, vec!["Python Developer"]);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_timezone_is_rejected() {
+        let state = test_state(&[job("Rust Developer", "Acme", "")]);
+        let params = SearchParams {
+            q: Some("rust".to_string()),
+            timezone: Some("Mars".to_string()),
+            ..SearchParams::default()
+        };
+
+        let response = search_handler(State(state), Query(params)).await;
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_salary_bucket_filters_by_band() {
+        let salaried = |title: &str, salary: i64| {
+            let mut job = job(title, "Acme", "");
+            job.salary_min = Some(salary);
+            job
+        };
+        let state = test_state(&[
+            salaried("Junior Developer", 40_000),
+            salaried("Mid Developer", 50_000),
+            salaried("Senior Developer", 99_999),
+            salaried("Staff Developer", 100_000),
+            job("Unpaid Developer", "Acme", ""),
+        ]);
+
+        let params = SearchParams {
+            q: Some("developer".to_string()),
+            salary_bucket: Some("50to100k".to_string()),
+            ..SearchParams::default()
+        };
+        let response = search_with(&state, params).await;
+
+        let mut titles: Vec<&str> = response.results.iter().map(|r| r.title.as_str()).collect();
+        titles.sort();
+        assert_eq!(titles, vec!["Mid Developer", "Senior Developer"]);
+
+        let params = SearchParams {
+            q: Some("developer".to_string()),
+            salary_bucket: Some("200k".to_string()),
+            ..SearchParams::default()
+        };
+        let response = search_handler(State(state), Query(params)).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_salary_filter_normalizes_currencies() {
+        let paid = |title: &str, min: i64, currency: &str| {
+            let mut job = job(title, "Acme", "");
+            job.salary_min = Some(min);
+            job.salary_currency = Some(currency.to_string());
+            job
+        };
+        let state = test_state(&[
+            paid("Rust Developer", 90_000, "USD"),
+            paid("Go Developer", 85_000, "USD"),
+            paid("Python Developer", 82_000, "EUR"),
+            paid("Java Developer", 9_000_000, "JPY"),
+        ]);
+        let titles = |response: SearchResponse| -> Vec<String> {
+            let mut titles: Vec<String> = response.results.into_iter().map(|r| r.title).collect();
+            titles.sort();
+            titles
+        };
+
+        // 80k EUR is about 86.4k USD: 90k USD clears it, 85k USD doesn't,
+        // and 82k EUR is compared in the same currency
+        let params = SearchParams {
+            q: Some("developer".to_string()),
+            salary_min: Some(80_000),
+            currency: Some("EUR".to_string()),
+            ..SearchParams::default()
+        };
+        let response = search_with(&state, params).await;
+        // The slider bounds are in euros too: 82k EUR and 90k USD (83,333.3 EUR)
+        assert_eq!(
+            response.salary_range,
+            Some(SalaryBounds {
+                min: 82_000,
+                max: 83_334
+            })
+        );
+        assert_eq!(titles(response), ["Python Developer", "Rust Developer"]);
+
+        // Without a currency the listed figures are compared as they are
+        let params = SearchParams {
+            q: Some("developer".to_string()),
+            salary_min: Some(80_000),
+            ..SearchParams::default()
+        };
+        assert_eq!(titles(search_with(&state, params).await).len(), 4);
+
+        let params = SearchParams {
+            q: Some("developer".to_string()),
+            salary_min: Some(80_000),
+            currency: Some("XYZ".to_string()),
+            ..SearchParams::default()
+        };
+        let response = search_handler(State(state), Query(params)).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_visa_filter() {
+        let mut sponsored = job("Rust Developer", "Acme", "");
+        sponsored.visa_sponsorship = Some(true);
+        let state = test_state(&[
+            sponsored,
+            // Inferred from the description at index time
+            job(
+                "Go Developer",
+                "Globex",
+                "Visa sponsorship is not available",
+            ),
+            job("Python Developer", "Initech", "Fully remote"),
+        ]);
+        let titles_for = |visa: bool| {
+            let params = SearchParams {
+                q: Some("developer".to_string()),
+                visa: Some(visa),
+                ..SearchParams::default()
+            };
+            let state = state.clone();
+            async move {
+                let response = search_with(&state, params).await;
+                response
+                    .results
+                    .into_iter()
+                    .map(|r| r.title)
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        // Jobs that don't mention sponsorship match neither
+        assert_eq!(titles_for(true).await, ["Rust Developer"]);
+        assert_eq!(titles_for(false).await, ["Go Developer"]);
+    }
+
+    #[tokio::test]
+    async fn test_contract_type_filter() {
+        let mut freelance = job("Rust Developer", "Acme", "");
+        freelance.contract_type = Some(common::ContractType::Contract);
+        let state = test_state(&[
+            freelance,
+            // Inferred from the description at index time
+            job("Go Developer", "Globex", "A full-time role"),
+            job("Python Developer", "Initech", "Full-time or contract"),
+        ]);
+
+        let params = SearchParams {
+            q: Some("developer".to_string()),
+            contract_type: Some("contract".to_string()),
+            ..SearchParams::default()
+        };
+        let response = search_with(&state, params).await;
+        let titles: Vec<&str> = response.results.iter().map(|r| r.title.as_str()).collect();
+        assert_eq!(titles, vec!["Rust Developer"]);
+
+        let params = SearchParams {
+            q: Some("developer".to_string()),
+            contract_type: Some("Full-Time".to_string()),
+            ..SearchParams::default()
+        };
+        let response = search_with(&state, params).await;
+        let titles: Vec<&str> = response.results.iter().map(|r| r.title.as_str()).collect();
+        assert_eq!(titles, vec!["Go Developer"]);
+
+        let params = SearchParams {
+            q: Some("developer".to_string()),
+            contract_type: Some("temporary".to_string()),
+            ..SearchParams::default()
+        };
+        let response = search_handler(State(state), Query(params)).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_country_filter() {
+        let located = |title: &str, location: &str| Job {
+            location: location.to_string(),
+            ..job(title, "Acme", "")
+        };
+        let state = test_state(&[
+            located("Rust Developer", "USA Only"),
+            located("Go Developer", "Europe"),
+            located("Python Developer", "Anywhere in the World"),
+            located("Java Developer", "Remote"),
+        ]);
+        let titles_for = |country: &str| {
+            let params = SearchParams {
+                q: Some("developer".to_string()),
+                country: Some(country.to_string()),
+                ..SearchParams::default()
+            };
+            let state = state.clone();
+            async move {
+                let response = search_with(&state, params).await;
+                let mut titles: Vec<String> =
+                    response.results.into_iter().map(|r| r.title).collect();
+                titles.sort();
+                titles
+            }
+        };
+
+        // Worldwide jobs match any country; unplaced ones match none
+        assert_eq!(
+            titles_for("us").await,
+            ["Python Developer", "Rust Developer"]
+        );
+        assert_eq!(titles_for("DE").await, ["Go Developer", "Python Developer"]);
+        assert_eq!(titles_for("JP").await, ["Python Developer"]);
+
+        let params = SearchParams {
+            q: Some("developer".to_string()),
+            country: Some("Germany".to_string()),
+            ..SearchParams::default()
+        };
+        let response = search_handler(State(state), Query(params)).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_location_filter_matches_location_words() {
+        let located = |title: &str, location: &str| Job {
+            location: location.to_string(),
+            ..job(title, "Acme", "")
+        };
+        let state = test_state(&[
+            located("Rust Developer", "USA Only"),
+            located("Go Developer", "Europe Only"),
+            located("Python Developer", "Only Europe or USA"),
+        ]);
+        let titles_for = |location: &str| {
+            let params = SearchParams {
+                q: Some("developer".to_string()),
+                location: Some(location.to_string()),
+                ..SearchParams::default()
+            };
+            let state = state.clone();
+            async move {
+                let response = search_with(&state, params).await;
+                let mut titles: Vec<String> =
+                    response.results.into_iter().map(|r| r.title).collect();
+                titles.sort();
+                titles
+            }
+        };
+
+        // Any case and punctuation; several words must appear in order
+        assert_eq!(
+            titles_for("europe").await,
+            ["Go Developer", "Python Developer"]
+        );
+        assert_eq!(titles_for("usa-only").await, ["Rust Developer"]);
+        assert!(titles_for("asia").await.is_empty());
+
+        let params = SearchParams {
+            q: Some("developer".to_string()),
+            location: Some(" ,".to_string()),
+            ..SearchParams::default()
+        };
+        let response = search_handler(State(state), Query(params)).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_put_job_replaces_only_that_job() {
+        let mut rust = job("Rust Developer", "Acme", "");
+        rust.salary_min = Some(40_000);
+        let mut go = job("Go Developer", "Globex", "");
+        go.salary_min = Some(45_000);
+        let state = test_state(&[rust.clone(), go]);
+
+        let in_bucket = |bucket: &str| {
+            let params = SearchParams {
+                q: Some("developer".to_string()),
+                salary_bucket: Some(bucket.to_string()),
+                ..SearchParams::default()
+            };
+            let state = state.clone();
+            async move {
+                let mut titles: Vec<String> = search_with(&state, params)
+                    .await
+                    .results
+                    .into_iter()
+                    .map(|r| r.title)
+                    .collect();
+                titles.sort();
+                titles
+            }
+        };
+        assert_eq!(in_bucket("lt50k").await.len(), 2);
+
+        rust.salary_min = Some(160_000);
+        let response =
+            update_job_handler(State(state.clone()), Path(rust.id()), Json(rust.clone())).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let returned: Job = serde_json::from_slice(&body_bytes(response).await).unwrap();
+        assert_eq!(returned.salary_min, Some(160_000));
+
+        assert_eq!(in_bucket("gt150k").await, vec!["Rust Developer"]);
+        assert_eq!(in_bucket("lt50k").await, vec!["Go Developer"]);
+        assert_eq!(search(&state, "developer").await.total_results, 2);
+
+        // The body can't move the job to another URL, and so another id
+        let mut moved = rust.clone();
+        moved.url = "https://example.com/elsewhere".to_string();
+        let response = update_job_handler(State(state.clone()), Path(rust.id()), Json(moved)).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(in_bucket("gt150k").await, vec!["Rust Developer"]);
+
+        let missing = update_job_handler(
+            State(state),
+            Path("0000000000000000".to_string()),
+            Json(rust),
+        )
+        .await;
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_admin_doc_returns_stored_fields() {
+        let config = Config {
+            store_descriptions: true,
+            admin_token: Some("secret".to_string()),
+            ..Config::default()
+        };
+        let mut rust = job("Rust Developer", "Acme", "Backend services");
+        rust.salary_min = Some(120_000);
+        let state = test_state_with(&config, &[rust.clone()]);
+
+        let fetch = |token: &str, url: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(
+                header::AUTHORIZATION,
+                HeaderValue::from_str(&format!("Bearer {}", token)).unwrap(),
+            );
+            let params = AdminDocParams {
+                url: url.to_string(),
+            };
+            admin_doc_handler(State(state.clone()), headers, Query(params))
+        };
+
+        let Json(doc) = fetch("secret", &rust.url).await.unwrap();
+        let doc = serde_json::to_value(doc).unwrap();
+        assert_eq!(doc["title"], serde_json::json!([rust.title]));
+        assert_eq!(doc["company"], serde_json::json!([rust.company]));
+        assert_eq!(doc["description"], serde_json::json!([rust.description]));
+        assert_eq!(doc["url"], serde_json::json!([rust.url]));
+        assert_eq!(doc["id"], serde_json::json!([rust.id()]));
+        assert_eq!(doc["salary_min"], serde_json::json!([120_000]));
+        // Indexed-only fields aren't stored
+        assert!(doc.get("country").is_none());
+
+        let wrong_token = fetch("guess", &rust.url).await.unwrap_err();
+        assert_eq!(wrong_token.0, StatusCode::UNAUTHORIZED);
+        let missing = fetch("secret", "https://example.com/nope")
+            .await
+            .unwrap_err();
+        assert_eq!(missing.0, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_reload_config_applies_new_synonyms() {
+        let dir = std::env::temp_dir().join(format!("vocabulary-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let synonyms = dir.join("synonyms.txt");
+        std::fs::write(&synonyms, "# none yet\n").unwrap();
+        let config = Config {
+            admin_token: Some("secret".to_string()),
+            synonyms_path: synonyms.to_str().unwrap().to_string(),
+            stopwords_path: dir.join("stopwords.txt").to_str().unwrap().to_string(),
+            ..Config::default()
+        };
+        let state = test_state_with(
+            &config,
+            &[job("JavaScript Developer", "Acme", "Frontend work")],
+        );
+        let reload = |token: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(
+                header::AUTHORIZATION,
+                HeaderValue::from_str(&format!("Bearer {}", token)).unwrap(),
+            );
+            admin_reload_config_handler(State(state.clone()), headers)
+        };
+
+        assert_eq!(search(&state, "js").await.total_results, 0);
+
+        // Edits only apply once reloaded, and only with the admin token
+        std::fs::write(&synonyms, "js, javascript\n").unwrap();
+        assert_eq!(search(&state, "js").await.total_results, 0);
+        let error = reload("guess").await.unwrap_err();
+        assert_eq!(error.0, StatusCode::UNAUTHORIZED);
+
+        let Json(reloaded) = reload("secret").await.unwrap();
+        assert_eq!(reloaded.synonyms, 2);
+        assert_eq!(reloaded.stopwords, 0);
+        assert_eq!(search(&state, "js").await.total_results, 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_admin_export_round_trips_jobs() {
+        let config = Config {
+            store_descriptions: true,
+            admin_token: Some("secret".to_string()),
+            ..Config::default()
+        };
+        let mut rust = job("Rust Developer", "Acme", "Backend services");
+        rust.location = "Europe Only".to_string();
+        rust.salary_min = Some(120_000);
+        rust.posted_date = Some("2024-03-01T00:00:00Z".to_string());
+        rust.tags = vec!["full-time".to_string()];
+        let go = job("Go Developer", "Globex", "APIs");
+        let seeded = vec![rust, go];
+        let state = test_state_with(&config, &seeded);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer secret"),
+        );
+        let Json(exported) = admin_export_handler(State(state), headers.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(&exported).unwrap(),
+            serde_json::to_value(&seeded).unwrap()
+        );
+
+        // Without stored jobs there is nothing to export from
+        let config = Config {
+            admin_token: Some("secret".to_string()),
+            ..Config::default()
+        };
+        let state = test_state_with(&config, &seeded);
+        let error = admin_export_handler(State(state), headers)
+            .await
+            .unwrap_err();
+        assert_eq!(error.0, StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn test_reindex_streams_progress_events() {
+        let dir = std::env::temp_dir().join(format!("reindex-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let jobs: Vec<Job> = (0..50)
+            .map(|i| job(&format!("Rust Developer {}", i), "Acme", ""))
+            .collect();
+        std::fs::write(dir.join("jobs.json"), serde_json::to_vec(&jobs).unwrap()).unwrap();
+        let config = Config {
+            jobs_glob: dir.join("*.json").to_str().unwrap().to_string(),
+            admin_token: Some("secret".to_string()),
+            ..Config::default()
+        };
+        // Starts with a job the job files no longer have
+        let state = test_state_with(&config, &[job("Go Developer", "Globex", "")]);
+
+        let unauthorized = admin_reindex_handler(State(state.clone()), HeaderMap::new())
+            .await
+            .unwrap_err();
+        assert_eq!(unauthorized.0, StatusCode::UNAUTHORIZED);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer secret"),
+        );
+        let response = admin_reindex_handler(State(state.clone()), headers)
+            .await
+            .unwrap();
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "text/event-stream"
+        );
+        let body = String::from_utf8(body_bytes(response).await).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let events: Vec<(&str, &str)> = body
+            .split("\n\n")
+            .filter_map(|event| {
+                let name = event.lines().find_map(|line| line.strip_prefix("event:"))?;
+                let data = event.lines().find_map(|line| line.strip_prefix("data:"))?;
+                Some((name, data))
+            })
+            .collect();
+        let progress: Vec<&str> = events
+            .iter()
+            .filter(|(name, _)| *name == "progress")
+            .map(|(_, data)| *data)
+            .collect();
+        assert_eq!(progress.len(), 10, "{:?}", events);
+        assert_eq!(progress[0], "indexed 5 of 50");
+        assert_eq!(progress[9], "indexed 50 of 50");
+        let (last, summary) = events.last().unwrap();
+        assert_eq!(*last, "done");
+        assert!(summary.starts_with("indexed 50 jobs in"), "{}", summary);
+
+        state.index_reader.reload().unwrap();
+        assert_eq!(state.index_reader.searcher().num_docs(), 50);
+    }
+
+    #[tokio::test]
+    async fn test_substring_mode_matches_inside_words() {
+        let state = test_state(&[
+            job("Shopify Developer", "Shopify", ""),
+            job("Backend Engineer", "Workshop Labs", ""),
+            job("Designer", "Acme", ""),
+        ]);
+
+        assert_eq!(search(&state, "shop").await.total_results, 0);
+
+        let params = SearchParams {
+            q: Some("SHOP".to_string()),
+            substring: Some(true),
+            ..SearchParams::default()
+        };
+        let response = search_with(&state, params).await;
+
+        let mut titles: Vec<&str> = response.results.iter().map(|r| r.title.as_str()).collect();
+        titles.sort();
+        assert_eq!(titles, vec!["Backend Engineer", "Shopify Developer"]);
+    }
+
+    #[tokio::test]
+    async fn test_debug_returns_parsed_query() {
+        let state = test_state(&[job("Rust Developer", "Acme", "")]);
+
+        assert_eq!(search(&state, "rust developer").await.parsed_query, None);
+
+        let params = SearchParams {
+            q: Some("rust developer".to_string()),
+            salary_min: Some(100_000),
+            debug: Some(true),
+            ..SearchParams::default()
+        };
+        let parsed = search_with(&state, params).await.parsed_query.unwrap();
+        // Both terms are searched, and the filter is part of the query
+        assert!(parsed.contains("rust"));
+        assert!(parsed.contains("developer"));
+        assert!(parsed.contains("RangeQuery"));
+    }
+
+    #[tokio::test]
+    async fn test_search_streams_ndjson() {
+        let state = test_state(&[
+            job("Rust Developer", "Acme", ""),
+            job("Senior Rust Engineer", "Globex", ""),
+            job("Designer", "Initech", ""),
+        ]);
+        let params = SearchParams {
+            q: Some("rust".to_string()),
+            format: Some("ndjson".to_string()),
+            ..SearchParams::default()
+        };
+
+        let response = search_handler(State(state), Query(params)).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "application/x-ndjson"
+        );
+        let body = String::from_utf8(body_bytes(response).await).unwrap();
+        let results: Vec<SearchResult> = body
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let mut titles: Vec<&str> = results.iter().map(|r| r.title.as_str()).collect();
+        titles.sort();
+        assert_eq!(titles, vec!["Rust Developer", "Senior Rust Engineer"]);
+    }
+
+    async fn import(state: &Arc<AppState>, key: Option<&str>, jobs: Vec<Job>) -> Response {
+        let mut headers = HeaderMap::new();
+        if let Some(key) = key {
+            headers.insert("idempotency-key", HeaderValue::from_str(key).unwrap());
+        }
+        import_handler(State(state.clone()), headers, Json(jobs)).await
+    }
+
+    #[tokio::test]
+    async fn test_import_upserts_by_url() {
+        let state = test_state(&[job("Rust Developer", "Acme", "Backend")]);
+
+        let mut updated = job("Rust Developer", "Acme", "Backend");
+        updated.title = "Senior Rust Developer".to_string();
+        let response = import(&state, None, vec![updated]).await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let results = search(&state, "rust").await.results;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].title, "Senior Rust Developer");
+    }
+
+    #[tokio::test]
+    async fn test_csv_import_indexes_valid_rows() {
+        let state = test_state(&[job("Go Developer", "Globex", "APIs")]);
+        let csv = "title,company,location,description,url,salary_min,tags\n\
+                   Elixir Developer,Acme,Europe,\"Phoenix, LiveView\",https://example.com/elixir,90000,elixir;backend\n\
+                   ,,,,,,\n\
+                   Broken Row,Acme,Europe,Missing URL,,,\n";
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("text/csv"));
+
+        let Json(response) =
+            import_csv_handler(State(state.clone()), headers.clone(), csv.to_string())
+                .await
+                .unwrap();
+        assert_eq!(
+            (
+                response.parsed,
+                response.skipped,
+                response.errored,
+                response.indexed
+            ),
+            (1, 1, 1, 1)
+        );
+        assert_eq!(response.errors[0].line, 4);
+
+        let results = search(&state, "phoenix").await.results;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].title, "Elixir Developer");
+        assert_eq!(search(&state, "developer").await.total_results, 2);
+
+        // A bad header fails the whole request; JSON isn't CSV
+        let error = import_csv_handler(State(state.clone()), headers, "name\nx\n".to_string())
+            .await
+            .unwrap_err();
+        assert_eq!(error.0, StatusCode::BAD_REQUEST);
+        let mut json = HeaderMap::new();
+        json.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/json"),
+        );
+        let error = import_csv_handler(State(state), json, "[]".to_string())
+            .await
+            .unwrap_err();
+        assert_eq!(error.0, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+
+    #[tokio::test]
+    async fn test_import_with_same_idempotency_key_indexes_once() {
+        let state = test_state(&[]);
+
+        let first = import(
+            &state,
+            Some("batch-1"),
+            vec![job("Go Developer", "Acme", "")],
+        )
+        .await;
+        assert_eq!(first.status(), StatusCode::OK);
+        assert!(first.headers().get("idempotent-replayed").is_none());
+
+        // A retry with the same key replays the first result and writes nothing,
+        // even though the body differs
+        let retry = import(
+            &state,
+            Some("batch-1"),
+            vec![job("Elixir Developer", "Acme", "")],
+        )
+        .await;
+        assert_eq!(retry.status(), StatusCode::OK);
+        assert_eq!(retry.headers()["idempotent-replayed"], "true");
+
+        assert_eq!(search(&state, "go").await.total_results, 1);
+        assert_eq!(search(&state, "elixir").await.total_results, 0);
+    }
+
+    #[tokio::test]
+    async fn test_empty_index_search_returns_no_results() {
+        let config = Config {
+            allow_match_all: true,
+            store_descriptions: true,
+            bm25_b: 0.5,
+            ..Config::default()
+        };
+        let state = empty_state(&config);
+        let query = |q: Option<&str>| SearchParams {
+            q: q.map(str::to_string),
+            ..SearchParams::default()
+        };
+
+        let variants = [
+            query(Some("rust developer")),
+            // Browsing everything
+            query(None),
+            SearchParams {
+                sort: Some("salary_desc,date_asc".to_string()),
+                ..query(Some("rust"))
+            },
+            SearchParams {
+                recency_half_life_days: Some(7.0),
+                max_per_company: Some(1),
+                ..query(Some("rust"))
+            },
+            SearchParams {
+                substring: Some(true),
+                highlights: Some("offsets".to_string()),
+                debug: Some(true),
+                ..query(Some("shop"))
+            },
+            SearchParams {
+                timezone: Some("+1".to_string()),
+                salary_min: Some(50_000),
+                contract_type: Some("contract".to_string()),
+                ..query(None)
+            },
+        ];
+        for params in variants {
+            let response = search_with(&state, params.clone()).await;
+            assert_eq!(response.total_results, 0, "{:?}", params);
+
+            let Json(count) = count_handler(State(state.clone()), Query(params))
+                .await
+                .unwrap();
+            assert_eq!(count.count, 0);
+        }
+
+        let params = SearchParams {
+            format: Some("ndjson".to_string()),
+            ..query(Some("rust"))
+        };
+        let response = search_handler(State(state), Query(params)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(body_bytes(response).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_empty_index_other_endpoints_respond_cleanly() {
+        let path = std::env::temp_dir().join(format!("saved-empty-{}.json", std::process::id()));
+        let config = Config {
+            store_descriptions: true,
+            admin_token: Some("secret".to_string()),
+            saved_searches_path: path.to_str().unwrap().to_string(),
+            ..Config::default()
+        };
+        let state = empty_state(&config);
+
+        let Json(health) = health_handler(State(state.clone())).await;
+        assert_eq!(health.status, "ok");
+        assert_eq!(health.num_docs, 0);
+        // Nothing to find isn't a failure
+        let (status, Json(ready)) = ready_handler(State(state.clone())).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(ready.status, "ok");
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer secret"),
+        );
+        let Json(exported) = admin_export_handler(State(state.clone()), headers.clone())
+            .await
+            .unwrap();
+        assert!(exported.is_empty());
+        let missing = admin_doc_handler(
+            State(state.clone()),
+            headers,
+            Query(AdminDocParams {
+                url: "https://example.com/1".to_string(),
+            }),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(missing.0, StatusCode::NOT_FOUND);
+
+        let Json(listed) = list_saved_searches_handler(State(state.clone())).await;
+        assert!(listed.is_empty());
+        let request = CreateSavedSearch {
+            name: "Rust jobs".to_string(),
+            params: SearchParams {
+                q: Some("rust".to_string()),
+                ..SearchParams::default()
+            },
+        };
+        let (_, Json(saved)) = create_saved_search_handler(State(state.clone()), Json(request))
+            .await
+            .unwrap();
+        let Json(results) = saved_search_results_handler(
+            State(state.clone()),
+            Path(saved.id),
+            Query(SavedResultsParams {
+                new_only: Some(true),
+            }),
+        )
+        .await
+        .unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(results.total_results, 0);
+
+        // The first import into an empty index makes jobs searchable
+        let response = import(&state, None, vec![job("Rust Developer", "Acme", "")]).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(search(&state, "rust").await.total_results, 1);
+        let Json(health) = health_handler(State(state)).await;
+        assert_eq!(health.num_docs, 1);
+    }
+
+    #[tokio::test]
+    async fn test_ready_reports_degraded_when_search_fails() {
+        let state = test_state(&[job("Rust Developer", "Acme", "Backend services")]);
+        let (status, Json(ready)) = ready_handler(State(state)).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(ready.status, "ok");
+        assert_eq!(ready.num_docs, 1);
+        assert!(ready.error.is_none());
+
+        // A searcher that errors, or finds nothing among existing docs
+        let broken = readiness(
+            3,
+            Err(tantivy::TantivyError::InternalError(
+                "segment file missing".to_string(),
+            )),
+        );
+        assert_eq!(broken.status, "degraded");
+        assert_eq!(broken.num_docs, 3);
+        assert!(broken.error.unwrap().contains("segment file missing"));
+        assert_eq!(readiness(3, Ok(0)).status, "degraded");
+    }
+
+    #[test]
+    fn test_reindex_only_builds_index_from_jobs_file() {
+        let dir = std::env::temp_dir().join(format!("reindex-only-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let jobs_path = dir.join("fixture.json");
+        let jobs = vec![
+            job("Rust Developer", "Acme", ""),
+            job("Go Developer", "Globex", ""),
+        ];
+        std::fs::write(&jobs_path, serde_json::to_string(&jobs).unwrap()).unwrap();
+
+        let args = Args::try_parse_from([
+            "server",
+            "--jobs",
+            jobs_path.to_str().unwrap(),
+            "--reindex-only",
+        ])
+        .unwrap();
+        assert!(args.reindex_only);
+
+        let index_path = dir.join("index");
+        let config = Config::default();
+        let built = build_index(args.jobs.as_deref(), &index_path, &config, None)
+            .map(|index| index.reader().unwrap().searcher().num_docs());
+        // Naming a file that doesn't exist fails instead of indexing nothing
+        let missing = build_index(Some(&dir.join("missing.json")), &index_path, &config, None);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(built.unwrap(), 2);
+        assert!(matches!(missing, Err(Error::Io { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_large_responses_are_compressed() {
+        use axum::body::Body;
+        use axum::http::Request;
+        use tower::ServiceExt;
+
+        let config = Config {
+            store_descriptions: true,
+            ..Config::default()
+        };
+        let description = "Build backend services in Rust. ".repeat(20);
+        let jobs: Vec<Job> = (0..10)
+            .map(|i| job(&format!("Rust Developer {}", i), "Acme", &description))
+            .collect();
+        let app = router(test_state_with(&config, &jobs));
+        let get = |uri: &str, accept_encoding: Option<&str>| {
+            let mut request = Request::builder().uri(uri);
+            if let Some(encoding) = accept_encoding {
+                request = request.header(header::ACCEPT_ENCODING, encoding);
+            }
+            app.clone().oneshot(request.body(Body::empty()).unwrap())
+        };
+
+        let response = get("/search?q=rust", Some("gzip")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_ENCODING], "gzip");
+
+        // Not without Accept-Encoding, and not for small bodies
+        let plain = get("/search?q=rust", None).await.unwrap();
+        assert!(plain.headers().get(header::CONTENT_ENCODING).is_none());
+        let small = get("/health", Some("gzip")).await.unwrap();
+        assert!(small.headers().get(header::CONTENT_ENCODING).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_post_search_nested_filter() {
+        use axum::body::Body;
+        use axum::http::Request;
+        use common::ContractType;
+        use tower::ServiceExt;
+
+        let with_contract = |title: &str, contract_type: ContractType| Job {
+            contract_type: Some(contract_type),
+            ..job(title, "Acme", "Backend work")
+        };
+        let app = router(test_state(&[
+            with_contract("Senior Rust Developer", ContractType::FullTime),
+            with_contract("Senior Go Developer", ContractType::FullTime),
+            with_contract("Senior Rust Contractor", ContractType::Contract),
+            with_contract("Junior Rust Developer", ContractType::FullTime),
+            with_contract("Senior Python Developer", ContractType::FullTime),
+        ]));
+        let post = |body: serde_json::Value| {
+            let request = Request::builder()
+                .method("POST")
+                .uri("/search")
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            app.clone().oneshot(request)
+        };
+
+        // (rust OR go) AND senior AND NOT contract
+        let response = post(serde_json::json!({
+            "filter": {"and": [
+                {"or": [
+                    {"field": "title", "term": "rust"},
+                    {"field": "title", "term": "go"}
+                ]},
+                {"field": "title", "term": "senior"},
+                {"not": {"field": "contract_type", "term": "contract"}}
+            ]}
+        }))
+        .await
+        .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response: SearchResponse = serde_json::from_slice(&body_bytes(response).await).unwrap();
+        let mut titles: Vec<String> = response.results.into_iter().map(|r| r.title).collect();
+        titles.sort();
+        assert_eq!(titles, ["Senior Go Developer", "Senior Rust Developer"]);
+
+        // Keywords still apply alongside the filter
+        let response = post(serde_json::json!({
+            "q": "go",
+            "filter": {"field": "title", "term": "senior"}
+        }))
+        .await
+        .unwrap();
+        let response: SearchResponse = serde_json::from_slice(&body_bytes(response).await).unwrap();
+        assert_eq!(response.total_results, 1);
+
+        // Too deep, unknown fields and empty groups are rejected
+        let mut deep = serde_json::json!({"field": "title", "term": "rust"});
+        for _ in 0..filter_tree::MAX_FILTER_DEPTH {
+            deep = serde_json::json!({"not": deep});
+        }
+        for filter in [
+            deep,
+            serde_json::json!({"field": "salary", "term": "high"}),
+            serde_json::json!({"or": []}),
+        ] {
+            let response = post(serde_json::json!({ "filter": filter.clone() }))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{}", filter);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_salary_histogram_buckets_matching_salaries() {
+        use axum::body::Body;
+        use axum::http::Request;
+        use tower::ServiceExt;
+
+        let paid = |title: &str, salary: Option<i64>| Job {
+            salary_min: salary,
+            ..job(title, "Acme", "")
+        };
+        let app = router(test_state(&[
+            paid("Rust Developer", Some(95_000)),
+            paid("Go Developer", Some(101_000)),
+            paid("Java Developer", Some(108_000)),
+            // 100,000 EUR is 108,000 USD, and is counted as such
+            Job {
+                salary_currency: Some("EUR".to_string()),
+                ..paid("Kotlin Developer", Some(100_000))
+            },
+            paid("Scala Developer", Some(130_000)),
+            paid("Python Developer", None),
+            paid("Staff Engineer", Some(700_000)),
+            paid("Support Engineer", Some(40_000)),
+        ]));
+        let get = |uri: &str| {
+            let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+            app.clone().oneshot(request)
+        };
+
+        let response = get("/aggregations/salary?q=developer").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let histogram: SalaryHistogramResponse =
+            serde_json::from_slice(&body_bytes(response).await).unwrap();
+        let buckets: Vec<(i64, u64)> = histogram
+            .buckets
+            .iter()
+            .map(|bucket| (bucket.from, bucket.count))
+            .collect();
+        // Empty buckets in between are kept; the job without a salary isn't counted
+        assert_eq!(
+            buckets,
+            [
+                (90_000, 1),
+                (100_000, 3),
+                (110_000, 0),
+                (120_000, 0),
+                (130_000, 1)
+            ]
+        );
+        assert_eq!((histogram.bucket, histogram.total), (10_000, 5));
+        assert_eq!(histogram.currency, "USD");
+
+        let response = get("/aggregations/salary?q=developer&bucket=50000")
+            .await
+            .unwrap();
+        let histogram: SalaryHistogramResponse =
+            serde_json::from_slice(&body_bytes(response).await).unwrap();
+        assert_eq!(histogram.buckets[0].to, 100_000);
+        assert_eq!(histogram.buckets.len(), 2);
+
+        // Too narrow a bucket, or one spreading the salaries over too many
+        for uri in [
+            "/aggregations/salary?q=developer&bucket=500",
+            "/aggregations/salary?q=engineer&bucket=1000",
+        ] {
+            let response = get(uri).await.unwrap();
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_company_jobs_endpoint_scopes_to_one_company() {
+        use axum::body::Body;
+        use axum::http::Request;
+        use tower::ServiceExt;
+
+        let app = router(test_state(&[
+            job("Rust Developer", "Acme Corp", "Backend services"),
+            job("Go Developer", "Acme Corp", "APIs"),
+            job("Rust Engineer", "Acme", "Embedded work"),
+            job("Senior Rust Developer", "Globex", "Systems work"),
+        ]));
+        let get = |uri: &str| {
+            let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+            app.clone().oneshot(request)
+        };
+
+        let response = get("/companies/Acme%20Corp/jobs?q=rust").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response: SearchResponse = serde_json::from_slice(&body_bytes(response).await).unwrap();
+        assert_eq!(response.total_results, 1);
+        assert_eq!(response.results[0].title, "Rust Developer");
+
+        // The whole name, ignoring case; no query lists all of its jobs
+        let response = get("/companies/acme%20corp/jobs").await.unwrap();
+        let response: SearchResponse = serde_json::from_slice(&body_bytes(response).await).unwrap();
+        assert_eq!(response.total_results, 2);
+        assert!(response.results.iter().all(|r| r.company == "Acme Corp"));
+
+        // The same filter as a /search parameter; a blank name is rejected
+        let response = get("/search?q=rust&company=ACME").await.unwrap();
+        let response: SearchResponse = serde_json::from_slice(&body_bytes(response).await).unwrap();
+        let titles: Vec<&str> = response.results.iter().map(|r| r.title.as_str()).collect();
+        assert_eq!(titles, ["Rust Engineer"]);
+        let response = get("/search?q=rust&company=%20").await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_new_jobs_returns_jobs_after_cutoff_newest_first() {
+        use axum::body::Body;
+        use axum::http::Request;
+        use tower::ServiceExt;
+
+        let scraped = |title: &str, scraped_at: &str| Job {
+            scraped_at: Some(scraped_at.to_string()),
+            ..job(title, "Acme", "")
+        };
+        let config = Config {
+            store_descriptions: true,
+            ..Config::default()
+        };
+        let app = router(test_state_with(
+            &config,
+            &[
+                // Posted recently, but picked up before the cutoff
+                Job {
+                    posted_date: Some("2024-05-10T00:00:00Z".to_string()),
+                    ..scraped("Old Job", "2024-04-01T00:00:00Z")
+                },
+                scraped("Newer Job", "2024-05-02T00:00:00Z"),
+                scraped("Newest Job", "2024-05-03T00:00:00Z"),
+            ],
+        ));
+        let get = |uri: &str| {
+            let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+            app.clone().oneshot(request)
+        };
+
+        let response = get("/jobs/new?since=2024-05-01T00:00:00Z").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let jobs: Vec<Job> = serde_json::from_slice(&body_bytes(response).await).unwrap();
+        let titles: Vec<&str> = jobs.iter().map(|job| job.title.as_str()).collect();
+        assert_eq!(titles, ["Newest Job", "Newer Job"]);
+
+        let response = get("/jobs/new?since=2024-05-02T00:00:00Z").await.unwrap();
+        let jobs: Vec<Job> = serde_json::from_slice(&body_bytes(response).await).unwrap();
+        assert_eq!(jobs.len(), 1);
+
+        let malformed = get("/jobs/new?since=yesterday").await.unwrap();
+        assert_eq!(malformed.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_jobs_pushed_by_the_scraper_are_searchable() {
+        use common::DedupStrategy;
+        use job_scraper::company::MissingCompany;
+        use job_scraper::config::ScraperConfig;
+        use job_scraper::metrics::SelectorReport;
+        use job_scraper::push::{BatchPusher, HttpSink};
+        use job_scraper::quality::QualityThresholds;
+        use job_scraper::{Fetcher, HttpFetcher, SeenKeys, push_page, scrape_board};
+
+        let state = test_state(&[]);
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = axum::Server::from_tcp(listener)
+            .unwrap()
+            .serve(router(state.clone()).into_make_service());
+        tokio::spawn(server);
+
+        // A listing page as the board serves it, in place of the network
+        struct ListingPage(String);
+        impl Fetcher for ListingPage {
+            fn fetch(&self, _url: &str) -> Result<String, Error> {
+                Ok(self.0.clone())
+            }
+        }
+        let listing: String = [
+            ("Rust Developer", "Acme"),
+            ("Go Developer", "Globex"),
+            ("Senior Rust Engineer", "Initech"),
+        ]
+        .iter()
+        .enumerate()
+        .map(|(i, (title, company))| {
+            format!(
+                r#"<li class="new-listing-container">
+                    <a class="listing-link--unlocked" href="/remote-jobs/{}"></a>
+                    <h3 class="new-listing__header__title">{}</h3>
+                    <p class="new-listing__company-name">{}</p>
+                </li>"#,
+                i, title, company
+            )
+        })
+        .collect();
+        let page = ListingPage(format!("<html><body><ul>{}</ul></body></html>", listing));
+
+        // The scraper's client is blocking, so it runs off the runtime
+        let pushed = tokio::task::spawn_blocking(move || {
+            let mut board = ScraperConfig::load(None)
+                .unwrap()
+                .into_boards()
+                .unwrap()
+                .remove(0);
+            board.urls = vec![format!("{}/remote-jobs", board.base_url)];
+            let http = HttpFetcher::new(1, Duration::from_secs(5)).unwrap();
+            let mut sink = HttpSink::new(&http, &format!("http://{}", address));
+            let mut pusher = BatchPusher::new(&mut sink, 2);
+            let thresholds = QualityThresholds {
+                min_company_ratio: 0.5,
+                min_valid_ratio: 0.8,
+                min_salary_ratio: 0.0,
+            };
+            let mut push = |jobs| {
+                push_page(
+                    &mut pusher,
+                    &board,
+                    jobs,
+                    &thresholds,
+                    false,
+                    MissingCompany::Tag,
+                )
+                .map(|sent| assert!(sent))
+            };
+            scrape_board(
+                &page,
+                &board,
+                1,
+                &mut SeenKeys::new(DedupStrategy::default()),
+                &mut SelectorReport::default(),
+                &mut push,
+            )
+            .unwrap();
+            pusher.pushed
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(pushed, 3);
+        let found = search(&state, "rust").await;
+        assert_eq!(found.total_results, 2);
+        assert!(found.results.iter().all(|result| {
+            result
+                .url
+                .starts_with("https://weworkremotely.com/remote-jobs/")
+        }));
+        assert_eq!(search(&state, "go").await.results[0].company, "Globex");
+    }
+
+    #[tokio::test]
+    async fn test_slow_search_times_out_with_503() {
+        let config = Config {
+            search_timeout_ms: Some(50),
+            ..Config::default()
+        };
+        let state = test_state_with(&config, &[job("Rust Developer", "Acme", "")]);
+
+        // Scores every hit, slowly
+        let slow = TopDocs::with_limit(1).tweak_score(|_: &tantivy::SegmentReader| {
+            |_: tantivy::DocId, score: tantivy::Score| {
+                std::thread::sleep(Duration::from_millis(500));
+                score
+            }
+        });
+        let result = timed_search(&state, move |state| {
+            state
+                .index_reader
+                .searcher()
+                .search(&AllQuery, &slow)
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+        })
+        .await;
+
+        let (status, message) = result.unwrap_err();
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert!(message.contains("timed out after 50 ms"));
+        // Quick searches get through the same limit
+        assert_eq!(search(&state, "rust").await.total_results, 1);
+    }
 }
@@ -0,0 +1,155 @@
+//! Popular past queries, for GET /suggest
+//!
+//! Every first-page search is counted under its normalized text (lowercased,
+//! whitespace collapsed). The map holds at most a fixed number of queries:
+//! when it is full, the least frequent one (oldest first among ties) makes
+//! room, so one-off typos age out while popular queries stay. The counts
+//! live in memory; only when `QUERY_LOG_PATH` names a file are they written
+//! to it, every few seconds by a background task, and read back on startup.
+
+use common::Error;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use utoipa::ToSchema;
+
+/// How often searches recorded since the last write are written to the log file
+pub const SAVE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How often a query was run, and when last
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct QueryCount {
+    count: u64,
+    /// Unix seconds
+    last_seen: i64,
+}
+
+/// A suggested query and how often it was run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct Suggestion {
+    pub query: String,
+    pub count: u64,
+}
+
+/// Query frequencies, bounded to `capacity` distinct queries
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct QueryLog {
+    queries: HashMap<String, QueryCount>,
+    /// Searches recorded since the last save
+    #[serde(skip)]
+    unsaved: usize,
+}
+
+impl QueryLog {
+    /// Loads the log from `path`. A missing or unreadable file yields an empty log.
+    pub fn load(path: &Path) -> QueryLog {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// The log as JSON, to be written out, when searches were recorded since
+    /// the last call; None when there is nothing new to write
+    pub fn unsaved_json(&mut self) -> Result<Option<String>, Error> {
+        if self.unsaved == 0 {
+            return Ok(None);
+        }
+        let json = serde_json::to_string(self)?;
+        self.unsaved = 0;
+        Ok(Some(json))
+    }
+
+    /// Counts one run of `query`, evicting the least frequent query first if
+    /// `capacity` distinct queries are already kept
+    pub fn record(&mut self, query: &str, now: i64, capacity: usize) {
+        let query = normalize(query);
+        if query.is_empty() || capacity == 0 {
+            return;
+        }
+        if !self.queries.contains_key(&query) && self.queries.len() >= capacity {
+            let evicted = self
+                .queries
+                .iter()
+                .min_by(|(a_query, a), (b_query, b)| {
+                    (a.count, a.last_seen, a_query).cmp(&(b.count, b.last_seen, b_query))
+                })
+                .map(|(query, _)| query.clone());
+            if let Some(evicted) = evicted {
+                self.queries.remove(&evicted);
+            }
+        }
+        let entry = self.queries.entry(query).or_insert(QueryCount {
+            count: 0,
+            last_seen: now,
+        });
+        entry.count += 1;
+        entry.last_seen = now;
+        self.unsaved += 1;
+    }
+
+    /// Up to `limit` logged queries starting with `prefix`, most frequent
+    /// first and alphabetical among ties
+    pub fn suggest(&self, prefix: &str, limit: usize) -> Vec<Suggestion> {
+        let prefix = normalize(prefix);
+        let mut suggestions: Vec<Suggestion> = self
+            .queries
+            .iter()
+            .filter(|(query, _)| query.starts_with(&prefix))
+            .map(|(query, counted)| Suggestion {
+                query: query.clone(),
+                count: counted.count,
+            })
+            .collect();
+        suggestions.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.query.cmp(&b.query)));
+        suggestions.truncate(limit);
+        suggestions
+    }
+}
+
+/// Lowercases `query` and collapses its whitespace, so "Rust  Developer"
+/// and "rust developer" count as one query
+fn normalize(query: &str) -> String {
+    query
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_evicts_least_frequent_and_survives_reload() {
+        let mut log = QueryLog::default();
+        log.record("Rust  Developer", 1, 2);
+        log.record("rust developer", 2, 2);
+        log.record("rust typo", 3, 2);
+        // Full: the one-off "rust typo" makes room for "go developer"
+        log.record("go developer", 4, 2);
+        assert_eq!(
+            log.suggest("", 10),
+            vec![
+                Suggestion {
+                    query: "rust developer".to_string(),
+                    count: 2,
+                },
+                Suggestion {
+                    query: "go developer".to_string(),
+                    count: 1,
+                },
+            ]
+        );
+
+        let path = std::env::temp_dir().join(format!("query-log-{}.json", std::process::id()));
+        fs::write(&path, log.unsaved_json().unwrap().unwrap()).unwrap();
+        assert_eq!(log.unsaved_json().unwrap(), None);
+        let reloaded = QueryLog::load(&path);
+        let _ = fs::remove_file(&path);
+        assert_eq!(reloaded.suggest("rust", 10), log.suggest("rust", 10));
+    }
+}
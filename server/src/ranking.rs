@@ -0,0 +1,415 @@
+//! Ranking adjustments applied on top of BM25
+//!
+//! A recency boost multiplies each hit's relevance score by a factor that
+//! decays with the job's age, so a fresh, decent match can overtake a stale
+//! one that matches slightly better.
+//!
+//! Tantivy hardcodes its BM25 parameters, so custom `k1`/`b` values are applied
+//! by recomputing each hit's BM25 score from the query terms. Tantivy's score
+//! also carries the field and query boosts, which the terms don't, so it is
+//! scaled by how the custom score compares to the default one rather than
+//! replaced.
+//!
+//! Profiles bundle these knobs, and sorting, into presets for common uses.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::str::FromStr;
+use tantivy::{
+    DocAddress, DocId, DocSet, Postings, Score, Searcher, SegmentReader, Term,
+    collector::{Collector, TopDocs},
+    fieldnorm::FieldNormReader,
+    postings::SegmentPostings,
+    query::Query,
+    schema::{Field, IndexRecordOption},
+};
+use utoipa::ToSchema;
+
+/// Jobs posted at most this long ago are "new"
+const NEW_SECS: i64 = 3 * 86_400;
+
+/// Jobs posted at most this long ago are "recent"
+const RECENT_SECS: i64 = 14 * 86_400;
+
+/// Default weight of keyword matches in each field, unless the `BOOST_*`
+/// settings or a request set their own. A title names what the job is, so
+/// its matches count double a description's.
+pub const TITLE_BOOST: Score = 2.0;
+pub const DESCRIPTION_BOOST: Score = 1.0;
+/// Company names aren't searched for keywords unless a request weights them
+pub const COMPANY_BOOST: Score = 0.0;
+
+/// Per-field weights of keyword matches, from `boost_title`,
+/// `boost_description` and `boost_company`, or the `BOOST_*` settings
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FieldBoosts {
+    pub title: Score,
+    pub description: Score,
+    pub company: Score,
+}
+
+impl Default for FieldBoosts {
+    fn default() -> Self {
+        FieldBoosts {
+            title: TITLE_BOOST,
+            description: DESCRIPTION_BOOST,
+            company: COMPANY_BOOST,
+        }
+    }
+}
+
+/// A ranking preset chosen with `profile`. Its settings only fill in what a
+/// request leaves unset, so any of them can still be overridden.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Profile {
+    /// Search: best match first, title matches weighted triple, with a
+    /// gentle 90-day recency half-life to break near ties
+    Relevance,
+    /// Feed: newest first (`sort=date_desc`)
+    Recent,
+    /// Pay browsing: best paid first, newest first among equal pay
+    /// (`sort=salary_desc,date_desc`)
+    Comp,
+}
+
+/// The settings a profile stands for, as search parameter values
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ProfilePreset {
+    pub sort: Option<&'static str>,
+    pub boost_title: Option<Score>,
+    pub recency_half_life_days: Option<f64>,
+}
+
+impl Profile {
+    pub fn preset(self) -> ProfilePreset {
+        match self {
+            Profile::Relevance => ProfilePreset {
+                boost_title: Some(3.0),
+                recency_half_life_days: Some(90.0),
+                ..ProfilePreset::default()
+            },
+            Profile::Recent => ProfilePreset {
+                sort: Some("date_desc"),
+                ..ProfilePreset::default()
+            },
+            Profile::Comp => ProfilePreset {
+                sort: Some("salary_desc,date_desc"),
+                ..ProfilePreset::default()
+            },
+        }
+    }
+}
+
+impl FromStr for Profile {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.trim().to_lowercase().as_str() {
+            "relevance" => Ok(Profile::Relevance),
+            "recent" => Ok(Profile::Recent),
+            "comp" => Ok(Profile::Comp),
+            other => Err(format!(
+                "unknown profile {:?}: expected relevance, recent or comp",
+                other
+            )),
+        }
+    }
+}
+
+/// How long ago a job was posted, so clients can badge it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Freshness {
+    /// Up to 3 days old
+    New,
+    /// Up to 14 days old
+    Recent,
+    Old,
+    /// The job has no posted or scraped date
+    Unknown,
+}
+
+impl Freshness {
+    /// Labels a job by its timestamp (0 or earlier when undated)
+    pub fn from_timestamp(timestamp: i64, now: i64) -> Freshness {
+        if timestamp <= 0 {
+            return Freshness::Unknown;
+        }
+        match now.saturating_sub(timestamp) {
+            age if age <= NEW_SECS => Freshness::New,
+            age if age <= RECENT_SECS => Freshness::Recent,
+            _ => Freshness::Old,
+        }
+    }
+}
+
+/// Recency multiplier for a job without a date: midway between a brand-new
+/// and a very old job, the factor of one posted a half-life ago, so not
+/// knowing a job's age neither promotes nor buries it
+pub const UNDATED_RECENCY_FACTOR: Score = 1.5;
+
+/// Score multiplier for a job posted `age_secs` ago.
+///
+/// Starts at 2.0 for a brand-new job and halves its distance to 1.0 every
+/// `half_life_secs`, so old jobs keep their plain relevance score. Undated
+/// jobs (timestamp 0 or earlier) get `UNDATED_RECENCY_FACTOR`.
+pub fn recency_factor(timestamp: i64, now: i64, half_life_secs: f64) -> Score {
+    if timestamp <= 0 {
+        return UNDATED_RECENCY_FACTOR;
+    }
+    let age_secs = now.saturating_sub(timestamp).max(0) as f64;
+    (1.0 + 0.5f64.powf(age_secs / half_life_secs)) as Score
+}
+
+/// BM25 term-frequency saturation (`k1`) and length normalization (`b`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bm25Params {
+    pub k1: Score,
+    pub b: Score,
+}
+
+impl Default for Bm25Params {
+    /// The values Tantivy scores with
+    fn default() -> Self {
+        Bm25Params { k1: 1.2, b: 0.75 }
+    }
+}
+
+/// A query term with its index-wide statistics
+struct WeightedTerm {
+    term: Term,
+    idf: Score,
+    average_fieldnorm: Score,
+}
+
+/// BM25 with custom parameters over the terms of one query.
+///
+/// Each matched term adds its own BM25 score, as for Tantivy's term and
+/// boolean queries; a phrase counts as its separate words. A hit's Tantivy
+/// score is multiplied by its score under these parameters over its score
+/// under the default ones, so boosts carry over and the default parameters
+/// change nothing.
+pub struct Bm25 {
+    params: Bm25Params,
+    terms: Vec<WeightedTerm>,
+}
+
+impl Bm25 {
+    /// Gathers document frequencies and average field lengths for the terms of `query`
+    pub fn new(
+        searcher: &Searcher,
+        query: &dyn Query,
+        params: Bm25Params,
+    ) -> tantivy::Result<Bm25> {
+        let mut query_terms = Vec::new();
+        query.query_terms(&mut |term, _| query_terms.push(term.clone()));
+
+        // Same statistics as Tantivy: deleted documents still count
+        let total_num_docs: u64 = searcher
+            .segment_readers()
+            .iter()
+            .map(|segment_reader| u64::from(segment_reader.max_doc()))
+            .sum();
+
+        let mut terms = Vec::new();
+        for term in query_terms {
+            let mut total_num_tokens = 0;
+            for segment_reader in searcher.segment_readers() {
+                total_num_tokens += segment_reader
+                    .inverted_index(term.field())?
+                    .total_num_tokens();
+            }
+            let doc_freq = searcher.doc_freq(&term)?;
+            terms.push(WeightedTerm {
+                idf: idf(doc_freq, total_num_docs),
+                average_fieldnorm: total_num_tokens as Score / total_num_docs.max(1) as Score,
+                term,
+            });
+        }
+        Ok(Bm25 { params, terms })
+    }
+
+    /// Rescores documents of one segment from their Tantivy score. Documents
+    /// must be asked for in increasing order.
+    fn segment_scorer(
+        &self,
+        segment_reader: &SegmentReader,
+    ) -> impl FnMut(DocId, Score) -> Score + 'static {
+        let params = self.params;
+        let defaults = Bm25Params::default();
+        let mut terms: Vec<(Option<SegmentPostings>, FieldNormReader, Score, Score)> = self
+            .terms
+            .iter()
+            .map(|weighted| {
+                let field = weighted.term.field();
+                let postings = segment_reader
+                    .inverted_index(field)
+                    .ok()
+                    .and_then(|index| {
+                        index
+                            .read_postings(&weighted.term, IndexRecordOption::WithFreqs)
+                            .ok()
+                    })
+                    .flatten();
+                // Fields indexed without lengths count every document as one token long
+                let fieldnorms = segment_reader
+                    .get_fieldnorms_reader(field)
+                    .unwrap_or_else(|_| FieldNormReader::constant(segment_reader.max_doc(), 1));
+                (
+                    postings,
+                    fieldnorms,
+                    weighted.idf,
+                    weighted.average_fieldnorm,
+                )
+            })
+            .collect();
+
+        move |doc: DocId, score: Score| {
+            let mut custom = 0.0;
+            let mut default = 0.0;
+            for (postings, fieldnorms, idf, average_fieldnorm) in &mut terms {
+                let Some(postings) = postings else {
+                    continue;
+                };
+                if postings.doc() < doc {
+                    postings.seek(doc);
+                }
+                if postings.doc() != doc {
+                    continue;
+                }
+                let term_freq = postings.term_freq() as Score;
+                let length = fieldnorms.fieldnorm(doc) as Score / *average_fieldnorm;
+                let term_score = |params: Bm25Params| {
+                    let norm = params.k1 * (1.0 - params.b + params.b * length);
+                    *idf * (params.k1 + 1.0) * term_freq / (term_freq + norm)
+                };
+                custom += term_score(params);
+                default += term_score(defaults);
+            }
+            if default > 0.0 {
+                score * custom / default
+            } else {
+                score
+            }
+        }
+    }
+}
+
+/// Inverse document frequency, as Tantivy computes it
+fn idf(doc_freq: u64, doc_count: u64) -> Score {
+    let x = (doc_count.saturating_sub(doc_freq) as Score + 0.5) / (doc_freq as Score + 0.5);
+    (1.0 + x).ln()
+}
+
+/// Settings for the recency boost
+#[derive(Debug, Clone, Copy)]
+pub struct RecencyBoost {
+    /// Fast field holding each job's timestamp
+    pub date_field: Field,
+    pub now: i64,
+    pub half_life_secs: f64,
+}
+
+/// Settings for the featured-listing boost
+#[derive(Debug, Clone, Copy)]
+pub struct FeaturedBoost {
+    /// Fast field holding 1 for featured jobs, 0 otherwise
+    pub featured_field: Field,
+    /// Score multiplier for featured jobs
+    pub factor: Score,
+}
+
+/// Top-`limit` collector ranking hits by relevance, scored with `bm25` when
+/// set (otherwise Tantivy's own score), times `recency_factor` when boosting
+/// recency and times the featured factor for featured jobs
+pub fn adjusted(
+    limit: usize,
+    bm25: Option<Bm25>,
+    recency: Option<RecencyBoost>,
+    featured: Option<FeaturedBoost>,
+) -> impl Collector<Fruit = Vec<(Score, DocAddress)>> {
+    TopDocs::with_limit(limit).tweak_score(move |segment_reader: &SegmentReader| {
+        let mut rescore = bm25
+            .as_ref()
+            .map(|bm25| bm25.segment_scorer(segment_reader));
+        // An index built before the date field existed gets no boost
+        let dates = recency.and_then(|recency| {
+            let dates = segment_reader.fast_fields().i64(recency.date_field).ok()?;
+            Some((dates, recency))
+        });
+        let featured = featured.and_then(|featured| {
+            let flags = segment_reader
+                .fast_fields()
+                .i64(featured.featured_field)
+                .ok()?;
+            Some((flags, featured.factor))
+        });
+        move |doc: DocId, score: Score| {
+            let score = rescore
+                .as_mut()
+                .map_or(score, |rescore| rescore(doc, score));
+            let factor = dates.as_ref().map_or(1.0, |(dates, recency)| {
+                recency_factor(dates.get_val(doc), recency.now, recency.half_life_secs)
+            });
+            let featured_factor = featured
+                .as_ref()
+                .filter(|(flags, _)| flags.get_val(doc) != 0)
+                .map_or(1.0, |&(_, factor)| factor);
+            score * factor * featured_factor
+        }
+    })
+}
+
+/// Keeps at most `max_per_key` hits for each key, in rank order, so the
+/// best-ranked hits of every key survive and the rest move up
+pub fn cap_per_key<K: Eq + Hash>(
+    hits: Vec<(Score, DocAddress)>,
+    max_per_key: usize,
+    key: impl Fn(DocAddress) -> K,
+) -> Vec<(Score, DocAddress)> {
+    let mut counts: HashMap<K, usize> = HashMap::new();
+    hits.into_iter()
+        .filter(|&(_, doc_address)| {
+            let count = counts.entry(key(doc_address)).or_insert(0);
+            *count += 1;
+            *count <= max_per_key
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recency_factor_halves_boost_each_half_life() {
+        let day = 86_400;
+        let now = 100 * day;
+
+        assert_eq!(recency_factor(now, now, day as f64), 2.0);
+        assert_eq!(recency_factor(now - day, now, day as f64), 1.5);
+        assert_eq!(recency_factor(now - 2 * day, now, day as f64), 1.25);
+        assert_eq!(recency_factor(0, now, day as f64), UNDATED_RECENCY_FACTOR);
+    }
+
+    #[test]
+    fn test_freshness_bands() {
+        let day = 86_400;
+        let now = 100 * day;
+
+        assert_eq!(
+            Freshness::from_timestamp(now - 3 * day, now),
+            Freshness::New
+        );
+        assert_eq!(
+            Freshness::from_timestamp(now - 10 * day, now),
+            Freshness::Recent
+        );
+        assert_eq!(
+            Freshness::from_timestamp(now - 15 * day, now),
+            Freshness::Old
+        );
+        assert_eq!(Freshness::from_timestamp(0, now), Freshness::Unknown);
+    }
+}
@@ -0,0 +1,116 @@
+//! Saved searches
+//!
+//! Users can store a search under a name and re-run it later. Each run is
+//! timestamped, so a re-run can be limited to jobs dated after the last one.
+//! The store is a small JSON file rewritten on every change.
+
+use crate::SearchParams;
+use common::Error;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use utoipa::ToSchema;
+
+/// A named search and when it was last run
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SavedSearch {
+    pub id: u64,
+    pub name: String,
+    /// Same parameters as GET /search
+    #[schema(value_type = Object)]
+    pub params: SearchParams,
+    /// Unix seconds
+    pub created_at: i64,
+    /// Unix seconds; None until the search is first run
+    pub last_run: Option<i64>,
+}
+
+/// All saved searches, in creation order
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SavedSearchStore {
+    searches: Vec<SavedSearch>,
+}
+
+impl SavedSearchStore {
+    /// Loads the store from `path`. A missing or unreadable file yields an empty store.
+    pub fn load(path: &Path) -> SavedSearchStore {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Writes the store to `path` as JSON
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json).map_err(|e| Error::io(path, e))
+    }
+
+    /// Adds a search and returns it with its new id
+    pub fn create(&mut self, name: String, params: SearchParams, now: i64) -> SavedSearch {
+        let id = self
+            .searches
+            .iter()
+            .map(|search| search.id)
+            .max()
+            .unwrap_or(0)
+            + 1;
+        let search = SavedSearch {
+            id,
+            name,
+            params,
+            created_at: now,
+            last_run: None,
+        };
+        self.searches.push(search.clone());
+        search
+    }
+
+    pub fn list(&self) -> &[SavedSearch] {
+        &self.searches
+    }
+
+    pub fn get(&self, id: u64) -> Option<&SavedSearch> {
+        self.searches.iter().find(|search| search.id == id)
+    }
+
+    /// Records a run of search `id` at `now`. Returns the previous run time,
+    /// or None when there is no such search.
+    pub fn mark_run(&mut self, id: u64, now: i64) -> Option<Option<i64>> {
+        let search = self.searches.iter_mut().find(|search| search.id == id)?;
+        Some(search.last_run.replace(now))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_store_round_trips_and_tracks_runs() {
+        let path = std::env::temp_dir().join(format!("saved-searches-{}.json", std::process::id()));
+        let params = SearchParams {
+            q: Some("rust".to_string()),
+            ..SearchParams::default()
+        };
+
+        let mut store = SavedSearchStore::default();
+        let first = store.create("Rust jobs".to_string(), params.clone(), 100);
+        let second = store.create("More rust".to_string(), params, 100);
+        assert_eq!((first.id, second.id), (1, 2));
+
+        assert_eq!(store.mark_run(first.id, 200), Some(None));
+        assert_eq!(store.mark_run(first.id, 300), Some(Some(200)));
+        assert_eq!(store.mark_run(99, 300), None);
+
+        store.save(&path).unwrap();
+        let loaded = SavedSearchStore::load(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.list().len(), 2);
+        let reloaded = loaded.get(first.id).unwrap();
+        assert_eq!(reloaded.name, "Rust jobs");
+        assert_eq!(reloaded.params.q.as_deref(), Some("rust"));
+        assert_eq!(reloaded.last_run, Some(300));
+    }
+}
@@ -0,0 +1,163 @@
+//! Explicit result ordering
+//!
+//! `sort=salary_desc,date_desc` orders hits by fast field values instead of
+//! relevance. Keys apply in order: later keys only break ties left by earlier
+//! ones.
+
+use crate::index::JobFields;
+use tantivy::{
+    DocAddress, DocId, DocSet, SegmentReader, TERMINATED,
+    collector::{Collector, TopDocs},
+    schema::{Field, IndexRecordOption},
+};
+
+/// Fields results can be sorted by; each must be a fast field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortField {
+    Salary,
+    Date,
+}
+
+impl SortField {
+    fn field(self, fields: &JobFields) -> Field {
+        match self {
+            SortField::Salary => fields.salary_min,
+            SortField::Date => fields.date,
+        }
+    }
+}
+
+/// One `<field>_<asc|desc>` sort key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortKey {
+    pub field: SortField,
+    pub descending: bool,
+}
+
+/// Parses a comma-separated list of sort keys such as `salary_desc,date_asc`.
+/// `relevance` on its own asks for the default ranking by score, and parses
+/// to no keys.
+pub fn parse_sort(text: &str) -> Result<Vec<SortKey>, String> {
+    if text.trim() == "relevance" {
+        return Ok(Vec::new());
+    }
+    text.split(',')
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+        .map(|key| {
+            if key == "relevance" {
+                return Err("sort=relevance can't be combined with other keys".to_string());
+            }
+            let (name, direction) = key.rsplit_once('_').unwrap_or((key, ""));
+            let field = match name {
+                "salary" => SortField::Salary,
+                "date" => SortField::Date,
+                _ => {
+                    return Err(format!(
+                        "invalid sort key '{}': sortable fields are salary and date",
+                        key
+                    ));
+                }
+            };
+            let descending = match direction {
+                "asc" => false,
+                "desc" => true,
+                _ => {
+                    return Err(format!(
+                        "invalid sort key '{}': expected {}_asc or {}_desc",
+                        key, name, name
+                    ));
+                }
+            };
+            Ok(SortKey { field, descending })
+        })
+        .collect()
+}
+
+/// Top-`limit` collector ordering hits by `keys`. Each hit's sort value holds
+/// two entries per key: whether the doc has a value, then the value, negated
+/// for ascending keys, so comparing the vectors applies the keys in order
+/// and puts jobs without a value after those with one in either direction.
+pub fn sorted_by(
+    keys: &[SortKey],
+    limit: usize,
+    fields: &JobFields,
+) -> impl Collector<Fruit = Vec<(Vec<i64>, DocAddress)>> {
+    let keys: Vec<(Field, bool)> = keys
+        .iter()
+        .map(|key| (key.field.field(fields), key.descending))
+        .collect();
+    TopDocs::with_limit(limit).custom_score(move |segment_reader: &SegmentReader| {
+        let columns: Vec<_> = keys
+            .iter()
+            .map(|&(field, descending)| {
+                (
+                    segment_reader.fast_fields().i64(field).ok(),
+                    docs_with_value(segment_reader, field).ok(),
+                    descending,
+                )
+            })
+            .collect();
+        move |doc: DocId| {
+            let mut sort_value = Vec::with_capacity(columns.len() * 2);
+            for (column, with_value, descending) in &columns {
+                let present = with_value.as_ref().is_some_and(|docs| docs[doc as usize]);
+                let value = match column {
+                    Some(column) if present => column.get_val(doc),
+                    _ => 0,
+                };
+                sort_value.push(i64::from(present));
+                sort_value.push(if *descending {
+                    value
+                } else {
+                    value.saturating_neg()
+                });
+            }
+            sort_value
+        }
+    })
+}
+
+/// The docs of the segment that have a value in the indexed i64 `field`.
+/// Its fast field reads 0 for the others, which looks like a real value.
+fn docs_with_value(segment_reader: &SegmentReader, field: Field) -> tantivy::Result<Vec<bool>> {
+    let inverted_index = segment_reader.inverted_index(field)?;
+    let mut docs = vec![false; segment_reader.max_doc() as usize];
+    let mut terms = inverted_index.terms().stream()?;
+    while terms.advance() {
+        let mut postings =
+            inverted_index.read_postings_from_terminfo(terms.value(), IndexRecordOption::Basic)?;
+        while postings.doc() != TERMINATED {
+            docs[postings.doc() as usize] = true;
+            postings.advance();
+        }
+    }
+    Ok(docs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sort_keys() {
+        assert_eq!(
+            parse_sort("salary_desc, date_asc").unwrap(),
+            vec![
+                SortKey {
+                    field: SortField::Salary,
+                    descending: true
+                },
+                SortKey {
+                    field: SortField::Date,
+                    descending: false
+                },
+            ]
+        );
+        assert!(parse_sort("title_desc").is_err());
+        assert!(parse_sort("salary").is_err());
+        assert!(parse_sort("").unwrap().is_empty());
+        assert!(parse_sort(" relevance ").unwrap().is_empty());
+        assert!(parse_sort("relevance,date_desc").is_err());
+    }
+}
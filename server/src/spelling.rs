@@ -0,0 +1,233 @@
+//! "Did you mean" corrections for searches that found nothing
+//!
+//! Each word is run through the analyzer of every searched field, so it is
+//! compared with the terms the way that field indexed them (a stemmed field
+//! is checked stem to stem). A word that no field has is replaced by the
+//! closest indexed terms: fewest edits first (an edit adds, drops or changes
+//! a letter, or swaps two neighbours), then the term on most jobs. Candidates
+//! come from a Levenshtein automaton run over the term dictionaries, so only
+//! terms within reach are visited. With a stemming tokenizer the suggestions
+//! are the indexed stems.
+
+use levenshtein_automata::{DFA, Distance, LevenshteinAutomatonBuilder, SINK_STATE};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use tantivy::{Searcher, Term, schema::Field};
+
+/// Edits allowed in a word of `chars` characters: none below 3, where
+/// almost any short word would match, and one below 6
+pub fn max_edits(chars: usize) -> u8 {
+    match chars {
+        0..=2 => 0,
+        3..=5 => 1,
+        _ => 2,
+    }
+}
+
+/// Up to `limit` rewrites of `query` with its unknown plain words replaced
+/// by terms of `fields` within `max_edits` of them: the first uses each
+/// word's best candidate, the next ones its runners-up. Words that are
+/// indexed, have no close term or aren't plain (query syntax) stay as typed.
+/// Empty when nothing could be corrected.
+pub fn corrections(
+    searcher: &Searcher,
+    fields: &[Field],
+    query: &str,
+    limit: usize,
+) -> tantivy::Result<Vec<String>> {
+    let words: Vec<&str> = query.split_whitespace().collect();
+    let mut candidates: Vec<Vec<String>> = Vec::with_capacity(words.len());
+    for word in &words {
+        if word.chars().all(char::is_alphanumeric) {
+            candidates.push(word_candidates(searcher, fields, word, limit)?);
+        } else {
+            candidates.push(Vec::new());
+        }
+    }
+    if candidates.iter().all(Vec::is_empty) {
+        return Ok(Vec::new());
+    }
+
+    let mut rewrites: Vec<String> = Vec::new();
+    for rank in 0..limit {
+        let rewrite: Vec<&str> = words
+            .iter()
+            .zip(&candidates)
+            .map(|(word, terms)| match terms.get(rank).or(terms.last()) {
+                Some(term) => term.as_str(),
+                None => word,
+            })
+            .collect();
+        let rewrite = rewrite.join(" ");
+        if !rewrites.contains(&rewrite) {
+            rewrites.push(rewrite);
+        }
+    }
+    Ok(rewrites)
+}
+
+/// Closest terms to `word` over `fields`, or none when some field already
+/// has it (or its analyzer drops it, as with a stop word). A word its
+/// analyzer splits into several tokens isn't corrected.
+fn word_candidates(
+    searcher: &Searcher,
+    fields: &[Field],
+    word: &str,
+    limit: usize,
+) -> tantivy::Result<Vec<String>> {
+    let mut analyzed: Vec<(Field, String)> = Vec::new();
+    for &field in fields {
+        let tokens = analyze(searcher, field, word)?;
+        if tokens.is_empty() || is_indexed(searcher, field, &tokens)? {
+            return Ok(Vec::new());
+        }
+        if let [token] = tokens.as_slice() {
+            analyzed.push((field, token.clone()));
+        }
+    }
+    nearest_terms(searcher, &analyzed, limit)
+}
+
+/// `word` as `field`'s analyzer turns it into terms
+fn analyze(searcher: &Searcher, field: Field, word: &str) -> tantivy::Result<Vec<String>> {
+    let analyzer = searcher.index().tokenizer_for_field(field)?;
+    let mut stream = analyzer.token_stream(word);
+    let mut tokens = Vec::new();
+    while stream.advance() {
+        tokens.push(stream.token().text.clone());
+    }
+    Ok(tokens)
+}
+
+/// Whether `field` has every one of `tokens` as a term
+fn is_indexed(searcher: &Searcher, field: Field, tokens: &[String]) -> tantivy::Result<bool> {
+    for token in tokens {
+        if searcher.doc_freq(&Term::from_field_text(field, token))? == 0 {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Automaton builders for one and two edits, a swap of neighbours counting
+/// as one; building them is the costly part, so it is done once
+fn automaton_builder(max_distance: u8) -> &'static LevenshteinAutomatonBuilder {
+    static BUILDERS: OnceLock<[LevenshteinAutomatonBuilder; 2]> = OnceLock::new();
+    let builders = BUILDERS.get_or_init(|| {
+        [
+            LevenshteinAutomatonBuilder::new(1, true),
+            LevenshteinAutomatonBuilder::new(2, true),
+        ]
+    });
+    &builders[usize::from(max_distance.clamp(1, 2)) - 1]
+}
+
+/// A Levenshtein DFA as an automaton over term dictionary keys
+struct Within(DFA);
+
+impl tantivy_fst::Automaton for Within {
+    type State = u32;
+
+    fn start(&self) -> u32 {
+        self.0.initial_state()
+    }
+
+    fn is_match(&self, state: &u32) -> bool {
+        matches!(self.0.distance(*state), Distance::Exact(_))
+    }
+
+    fn can_match(&self, state: &u32) -> bool {
+        *state != SINK_STATE
+    }
+
+    fn accept(&self, state: &u32, byte: u8) -> u32 {
+        self.0.transition(*state, byte)
+    }
+}
+
+/// Up to `limit` terms within `max_edits` of each field's analyzed word,
+/// closest first, then on the most jobs
+fn nearest_terms(
+    searcher: &Searcher,
+    analyzed: &[(Field, String)],
+    limit: usize,
+) -> tantivy::Result<Vec<String>> {
+    let mut found: HashMap<String, (usize, u64)> = HashMap::new();
+    for (field, word) in analyzed {
+        let chars: Vec<char> = word.chars().collect();
+        let max_distance = max_edits(chars.len());
+        if max_distance == 0 {
+            continue;
+        }
+        for segment_reader in searcher.segment_readers() {
+            let inverted_index = segment_reader.inverted_index(*field)?;
+            let automaton = Within(automaton_builder(max_distance).build_dfa(word));
+            let mut terms = inverted_index.terms().search(automaton).into_stream()?;
+            while terms.advance() {
+                let Ok(text) = std::str::from_utf8(terms.key()) else {
+                    continue;
+                };
+                let term: Vec<char> = text.chars().collect();
+                let distance = edit_distance(&chars, &term);
+                let entry = found.entry(text.to_string()).or_insert((distance, 0));
+                entry.0 = entry.0.min(distance);
+                entry.1 += u64::from(terms.value().doc_freq);
+            }
+        }
+    }
+
+    let mut nearest: Vec<(String, (usize, u64))> = found.into_iter().collect();
+    nearest.sort_by(|(a, (a_distance, a_jobs)), (b, (b_distance, b_jobs))| {
+        a_distance
+            .cmp(b_distance)
+            .then_with(|| b_jobs.cmp(a_jobs))
+            .then_with(|| a.cmp(b))
+    });
+    nearest.truncate(limit);
+    Ok(nearest.into_iter().map(|(term, _)| term).collect())
+}
+
+/// Edits turning `a` into `b`, a swap of neighbours counting as one
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    // rows[i][j]: edits between the first i chars of a and the first j of b
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let change = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + change);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_distance_counts_swaps_as_one() {
+        let distance = |a: &str, b: &str| {
+            let a: Vec<char> = a.chars().collect();
+            let b: Vec<char> = b.chars().collect();
+            edit_distance(&a, &b)
+        };
+        assert_eq!(distance("develper", "developer"), 1);
+        assert_eq!(distance("develpoer", "developer"), 1);
+        assert_eq!(distance("rsut", "rust"), 1);
+        assert_eq!(distance("pyhton", "python"), 1);
+        assert_eq!(distance("kotlin", "rust"), 6);
+        assert_eq!(distance("", "go"), 2);
+    }
+}
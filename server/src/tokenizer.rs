@@ -0,0 +1,280 @@
+//! Custom Tantivy tokenizers
+//!
+//! The default tokenizer splits on whitespace and punctuation, which leaves
+//! CJK text (written without spaces) as one giant token per sentence.
+//!
+//! It also drops the symbols of language names, so "C++", "C#" and "C" all
+//! index as "c" and a search for one finds the others. Titles and
+//! descriptions keep those names whole instead.
+//!
+//! Descriptions get their own tokenizer name so a cap on how many of their
+//! tokens are indexed can be added on top of whichever word tokenizer is used.
+
+use tantivy::tokenizer::{BoxTokenStream, Token, TokenFilter, TokenStream, Tokenizer};
+
+/// Name the CJK tokenizer is registered under in the index's tokenizer manager
+pub const CJK_TOKENIZER: &str = "cjk";
+
+/// Name the description tokenizer (word tokenizer plus token cap) is registered
+/// under. It names the word tokenizer too: the schema records only this name,
+/// so an index built when descriptions used the default word tokenizer (as
+/// "description") differs from the current schema and gets rebuilt.
+pub const DESCRIPTION_TOKENIZER: &str = "description_tech";
+
+/// Name the tech term tokenizer is registered under
+pub const TECH_TOKENIZER: &str = "tech";
+
+/// Terms kept as one token, symbols included, in lowercase. Dotted names
+/// whose parts are words of their own ("Node.js") aren't listed: split, they
+/// still match as a phrase, and "node" alone still finds them.
+const TECH_TERMS: [&str; 4] = [".net", "c++", "c#", "f#"];
+
+/// Splits CJK runs into overlapping character bigrams and everything else into
+/// alphanumeric words, so "高级软件工程师" can be found by searching "软件".
+#[derive(Clone)]
+pub struct CjkTokenizer;
+
+/// Returns true for Han, Hiragana, Katakana and Hangul characters
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30FF}'
+        | '\u{3400}'..='\u{4DBF}'
+        | '\u{4E00}'..='\u{9FFF}'
+        | '\u{AC00}'..='\u{D7AF}'
+        | '\u{F900}'..='\u{FAFF}'
+        | '\u{20000}'..='\u{2A6DF}')
+}
+
+impl Tokenizer for CjkTokenizer {
+    fn token_stream<'a>(&self, text: &'a str) -> BoxTokenStream<'a> {
+        let mut tokens = Vec::new();
+        let mut push = |offset_from: usize, offset_to: usize| {
+            tokens.push(Token {
+                offset_from,
+                offset_to,
+                position: tokens.len(),
+                text: text[offset_from..offset_to].to_string(),
+                position_length: 1,
+            });
+        };
+
+        let chars: Vec<(usize, char)> = text.char_indices().collect();
+        let end_of = |i: usize| chars.get(i).map_or(text.len(), |&(offset, _)| offset);
+
+        let mut i = 0;
+        while i < chars.len() {
+            let (start, c) = chars[i];
+            if is_cjk(c) {
+                // Find the end of the CJK run, then emit its bigrams
+                let run_start = i;
+                while i < chars.len() && is_cjk(chars[i].1) {
+                    i += 1;
+                }
+                if i - run_start == 1 {
+                    push(start, end_of(i));
+                } else {
+                    for j in run_start + 2..=i {
+                        push(chars[j - 2].0, end_of(j));
+                    }
+                }
+            } else if c.is_alphanumeric() {
+                while i < chars.len() && chars[i].1.is_alphanumeric() && !is_cjk(chars[i].1) {
+                    i += 1;
+                }
+                push(start, end_of(i));
+            } else {
+                i += 1;
+            }
+        }
+
+        BoxTokenStream::from(VecTokenStream { tokens, current: 0 })
+    }
+}
+
+/// Splits text into alphanumeric words like Tantivy's simple tokenizer, but
+/// keeps the `TECH_TERMS` whole: "C++ and .NET" gives "C++", "and", ".NET".
+/// A term counts when no letter or digit follows it and, unless it starts
+/// with a dot ("ASP.NET" gives "ASP", ".NET"), none precedes it.
+#[derive(Clone)]
+pub struct TechTokenizer;
+
+/// Length of the tech term `text` starts with, if any
+fn tech_term_len(text: &str) -> Option<usize> {
+    TECH_TERMS
+        .iter()
+        .find(|term| {
+            text.get(..term.len())
+                .is_some_and(|start| start.eq_ignore_ascii_case(term))
+                && !text[term.len()..].starts_with(char::is_alphanumeric)
+        })
+        .map(|term| term.len())
+}
+
+impl Tokenizer for TechTokenizer {
+    fn token_stream<'a>(&self, text: &'a str) -> BoxTokenStream<'a> {
+        let mut tokens = Vec::new();
+        let mut push = |offset_from: usize, offset_to: usize| {
+            tokens.push(Token {
+                offset_from,
+                offset_to,
+                position: tokens.len(),
+                text: text[offset_from..offset_to].to_string(),
+                position_length: 1,
+            });
+        };
+
+        let mut offset = 0;
+        // Whether a word ends right before `offset`
+        let mut after_word = false;
+        while let Some(c) = text[offset..].chars().next() {
+            let rest = &text[offset..];
+            let term = if after_word && c != '.' {
+                None
+            } else {
+                tech_term_len(rest)
+            };
+            if let Some(len) = term {
+                push(offset, offset + len);
+                offset += len;
+                after_word = false;
+            } else if c.is_alphanumeric() {
+                let len = rest
+                    .find(|c: char| !c.is_alphanumeric())
+                    .unwrap_or(rest.len());
+                push(offset, offset + len);
+                offset += len;
+                after_word = true;
+            } else {
+                offset += c.len_utf8();
+                after_word = false;
+            }
+        }
+
+        BoxTokenStream::from(VecTokenStream { tokens, current: 0 })
+    }
+}
+
+/// Token stream over tokens computed up front
+struct VecTokenStream {
+    tokens: Vec<Token>,
+    /// One past the index of the current token (0 before the first `advance`)
+    current: usize,
+}
+
+impl TokenStream for VecTokenStream {
+    fn advance(&mut self) -> bool {
+        if self.current < self.tokens.len() {
+            self.current += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn token(&self) -> &Token {
+        &self.tokens[self.current - 1]
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.tokens[self.current - 1]
+    }
+}
+
+/// Ends a token stream after a fixed number of tokens, so one enormous
+/// description can't bloat the index or dominate term frequencies
+#[derive(Clone)]
+pub struct MaxTokensFilter {
+    limit: usize,
+}
+
+impl MaxTokensFilter {
+    pub fn limit(limit: usize) -> MaxTokensFilter {
+        MaxTokensFilter { limit }
+    }
+}
+
+impl TokenFilter for MaxTokensFilter {
+    fn transform<'a>(&self, token_stream: BoxTokenStream<'a>) -> BoxTokenStream<'a> {
+        BoxTokenStream::from(MaxTokensStream {
+            remaining: self.limit,
+            tail: token_stream,
+        })
+    }
+}
+
+struct MaxTokensStream<'a> {
+    /// Tokens still allowed through
+    remaining: usize,
+    tail: BoxTokenStream<'a>,
+}
+
+impl TokenStream for MaxTokensStream<'_> {
+    fn advance(&mut self) -> bool {
+        if self.remaining == 0 {
+            return false;
+        }
+        self.remaining -= 1;
+        self.tail.advance()
+    }
+
+    fn token(&self) -> &Token {
+        self.tail.token()
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.tail.token_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(text: &str) -> Vec<String> {
+        let mut stream = CjkTokenizer.token_stream(text);
+        let mut out = Vec::new();
+        while stream.advance() {
+            out.push(stream.token().text.clone());
+        }
+        out
+    }
+
+    #[test]
+    fn test_cjk_tokenizer_emits_bigrams() {
+        assert_eq!(tokens("软件工程"), vec!["软件", "件工", "工程"]);
+    }
+
+    #[test]
+    fn test_cjk_tokenizer_keeps_latin_words() {
+        assert_eq!(
+            tokens("Rust 工程师, remote"),
+            vec!["Rust", "工程", "程师", "remote"]
+        );
+    }
+
+    #[test]
+    fn test_cjk_tokenizer_single_character_run() {
+        assert_eq!(tokens("C 语"), vec!["C", "语"]);
+    }
+
+    #[test]
+    fn test_tech_tokenizer_keeps_language_names_whole() {
+        let tokens = |text: &str| {
+            let mut stream = TechTokenizer.token_stream(text);
+            let mut out = Vec::new();
+            while stream.advance() {
+                out.push(stream.token().text.clone());
+            }
+            out
+        };
+        assert_eq!(
+            tokens("C++/C# and .NET (ASP.NET), F#."),
+            vec!["C++", "C#", "and", ".NET", "ASP", ".NET", "F#"]
+        );
+        // Only whole terms: symbols inside or after other words are dropped
+        assert_eq!(
+            tokens("abc++ c++11 node.js x.net"),
+            vec!["abc", "c", "11", "node", "js", "x", ".net"]
+        );
+    }
+}
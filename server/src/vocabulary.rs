@@ -0,0 +1,165 @@
+//! Query synonyms and stopwords
+//!
+//! Both live in plain text files so they can be tuned without a rebuild, and
+//! reloaded without a restart through `POST /admin/reload-config`. They only
+//! change how search keywords are read; the index is unaffected.
+//!
+//! The synonyms file has one group of interchangeable terms per line,
+//! separated by commas (`js, javascript, ecmascript`); a term may be several
+//! words (`k8s, kubernetes, k 8 s`). The stopwords file has one word per line.
+//! In both, blank lines and lines starting with `#` are skipped.
+
+use common::Error;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Synonym groups and stopwords applied to search keywords
+#[derive(Debug, Default)]
+pub struct Vocabulary {
+    /// Each term, lowercased, and the other terms of its group(s)
+    synonyms: BTreeMap<String, Vec<String>>,
+    stopwords: BTreeSet<String>,
+}
+
+impl Vocabulary {
+    /// Reads both files. A missing file means no synonyms or no stopwords;
+    /// any other read error is returned.
+    pub fn load(synonyms_path: &Path, stopwords_path: &Path) -> Result<Vocabulary, Error> {
+        Ok(Vocabulary::parse(
+            &read_optional(synonyms_path)?,
+            &read_optional(stopwords_path)?,
+        ))
+    }
+
+    /// Builds a vocabulary from the contents of the two files
+    pub fn parse(synonyms: &str, stopwords: &str) -> Vocabulary {
+        let mut vocabulary = Vocabulary::default();
+        for line in content_lines(synonyms) {
+            let group: Vec<String> = line
+                .split(',')
+                .map(|term| term.split_whitespace().collect::<Vec<_>>().join(" "))
+                .map(|term| term.to_lowercase())
+                .filter(|term| !term.is_empty())
+                .collect();
+            for term in &group {
+                let others = vocabulary.synonyms.entry(term.clone()).or_default();
+                for other in &group {
+                    if other != term && !others.contains(other) {
+                        others.push(other.clone());
+                    }
+                }
+            }
+        }
+        vocabulary.stopwords = content_lines(stopwords).map(str::to_lowercase).collect();
+        vocabulary
+    }
+
+    /// Number of terms with synonyms
+    pub fn synonym_count(&self) -> usize {
+        self.synonyms
+            .values()
+            .filter(|others| !others.is_empty())
+            .count()
+    }
+
+    /// Number of stopwords
+    pub fn stopword_count(&self) -> usize {
+        self.stopwords.len()
+    }
+
+    /// Rewrites search words for the query parser: stopwords are dropped and
+    /// a word with synonyms becomes `(word OR "synonym" ...)`. Only plain
+    /// alphanumeric words are touched, so query syntax passes through. If
+    /// every word is a stopword they are all kept, rather than searching for nothing.
+    pub fn rewrite(&self, words: &[&str]) -> Vec<String> {
+        let plain = |word: &str| word.chars().all(char::is_alphanumeric);
+        let kept: Vec<&str> = words
+            .iter()
+            .copied()
+            .filter(|word| !(plain(word) && self.stopwords.contains(&word.to_lowercase())))
+            .collect();
+        let kept = if kept.is_empty() {
+            words.to_vec()
+        } else {
+            kept
+        };
+
+        kept.into_iter()
+            .map(|word| {
+                let others = plain(word)
+                    .then(|| self.synonyms.get(&word.to_lowercase()))
+                    .flatten()
+                    .filter(|others| !others.is_empty());
+                match others {
+                    Some(others) => {
+                        let alternatives: Vec<String> = others
+                            .iter()
+                            .map(|other| format!("\"{}\"", other))
+                            .collect();
+                        format!("({} OR {})", word, alternatives.join(" OR "))
+                    }
+                    None => word.to_string(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Reads a file, treating a missing one as empty
+fn read_optional(path: &Path) -> Result<String, Error> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(content),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(String::new()),
+        Err(e) => Err(Error::io(path, e)),
+    }
+}
+
+/// Trimmed lines that aren't blank or `#` comments
+fn content_lines(content: &str) -> impl Iterator<Item = &str> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rewrite_expands_synonyms_and_drops_stopwords() {
+        let vocabulary = Vocabulary::parse(
+            "# languages\njs, JavaScript\nk8s, kubernetes\n",
+            "the\nfor\n",
+        );
+        assert_eq!(vocabulary.synonym_count(), 4);
+        assert_eq!(vocabulary.stopword_count(), 2);
+
+        assert_eq!(
+            vocabulary.rewrite(&["Developer", "for", "JS"]),
+            ["Developer", "(JS OR \"javascript\")"]
+        );
+        assert_eq!(
+            vocabulary.rewrite(&["javascript"]),
+            ["(javascript OR \"js\")"]
+        );
+        // Query syntax is left alone, and a query of only stopwords is kept
+        assert_eq!(vocabulary.rewrite(&["title:js"]), ["title:js"]);
+        assert_eq!(vocabulary.rewrite(&["the"]), ["the"]);
+    }
+
+    #[test]
+    fn test_shipped_synonyms_parse() {
+        let vocabulary = Vocabulary::parse(include_str!("../../synonyms.txt"), "");
+        assert_eq!(vocabulary.rewrite(&["js"]), ["(js OR \"javascript\")"]);
+        assert_eq!(vocabulary.rewrite(&["K8s"]), ["(K8s OR \"kubernetes\")"]);
+        // "go" is mostly the verb, so it isn't tied to golang
+        assert_eq!(vocabulary.rewrite(&["go"]), ["go"]);
+        assert_eq!(
+            vocabulary.rewrite(&["frontend"]),
+            ["(frontend OR \"front end\")"]
+        );
+    }
+}